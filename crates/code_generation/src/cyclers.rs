@@ -25,9 +25,7 @@ pub fn generate_cyclers(cyclers: &Cyclers, mode: Execution) -> TokenStream {
             let instance_name = format_ident!("{}", instance);
             quote! {
                 #instance_name {
-                    timestamp: std::time::SystemTime,
-                    duration: std::time::Duration,
-                    data: std::vec::Vec<u8>,
+                    frames: std::vec::Vec<framework::RecordingFrame>,
                 },
             }
         });
@@ -113,6 +111,7 @@ fn generate_struct(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) -> Token
         quote! {
             recording_sender: std::sync::mpsc::SyncSender<crate::cyclers::RecordingFrame>,
             recording_trigger: framework::RecordingTrigger,
+            record_hardware_inputs: bool,
         }
     } else {
         Default::default()
@@ -122,6 +121,7 @@ fn generate_struct(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) -> Token
         pub(crate) struct Cycler<HardwareInterface>  {
             instance: CyclerInstance,
             hardware_interface: std::sync::Arc<HardwareInterface>,
+            hardware_inputs_interface: std::sync::Arc<hardware::HardwareInputsInterface<HardwareInterface>>,
             own_writer: framework::Writer<Database>,
             own_changed: std::sync::Arc<tokio::sync::Notify>,
             own_subscribed_outputs_reader: framework::Reader<std::collections::HashSet<String>>,
@@ -230,6 +230,7 @@ fn generate_new_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) -> T
         quote! {
             recording_sender: std::sync::mpsc::SyncSender<crate::cyclers::RecordingFrame>,
             recording_trigger: framework::RecordingTrigger,
+            record_hardware_inputs: bool,
         }
     } else {
         Default::default()
@@ -238,6 +239,7 @@ fn generate_new_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) -> T
         quote! {
             recording_sender,
             recording_trigger,
+            record_hardware_inputs,
         }
     } else {
         Default::default()
@@ -259,6 +261,9 @@ fn generate_new_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) -> T
             #node_initializers
             Ok(Self {
                 instance,
+                hardware_inputs_interface: std::sync::Arc::new(
+                    hardware::HardwareInputsInterface::new(hardware_interface.clone())
+                ),
                 hardware_interface,
                 own_writer,
                 own_changed,
@@ -438,10 +443,7 @@ fn generate_cycle_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) ->
             pub fn cycle(&mut self, now: std::time::SystemTime, mut recording_frame: &[u8]) -> color_eyre::Result<()>
         },
     };
-    let setup_node_executions = cycler
-        .setup_nodes
-        .iter()
-        .map(|node| generate_node_execution(node, cycler, NodeType::Setup, mode));
+    let setup_node_executions = generate_setup_node_executions(cycler, mode);
     let cycle_node_executions = cycler
         .cycle_nodes
         .iter()
@@ -459,6 +461,10 @@ fn generate_cycle_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) ->
             let enable_recording = self.recording_trigger.should_record() && self.hardware_interface.should_record();
             self.recording_trigger.update();
             let mut recording_frame = Vec::new(); // TODO: possible optimization: cache capacity
+            let enable_hardware_inputs_recording = enable_recording && self.record_hardware_inputs;
+            if enable_recording {
+                bincode::serialize_into(&mut recording_frame, &enable_hardware_inputs_recording).wrap_err("failed to record whether hardware inputs are recorded")?;
+            }
         },
         Execution::Replay => Default::default(),
     };
@@ -519,9 +525,7 @@ fn generate_cycle_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) ->
                 let instance_name = format_ident!("{}", instance);
                 quote! {
                     CyclerInstance::#instance_name => crate::cyclers::RecordingFrame::#instance_name {
-                        timestamp: recording_timestamp,
-                        duration: recording_duration,
-                        data: recording_frame,
+                        frames,
                     },
                 }
             });
@@ -531,9 +535,15 @@ fn generate_cycle_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) ->
                 let recording_duration = recording_timestamp.elapsed().expect("time ran backwards");
 
                 if enable_recording {
-                    self.recording_sender.try_send(match instance {
-                        #(#recording_variants)*
-                    }).wrap_err("failed to send recording frame")?;
+                    let frames = self.recording_trigger.frames_to_write(
+                        framework::RecordingFrame::new(recording_timestamp, recording_duration, recording_frame),
+                        self.hardware_interface.get_last_event_recording_trigger(),
+                    );
+                    if !frames.is_empty() {
+                        self.recording_sender.try_send(match instance {
+                            #(#recording_variants)*
+                        }).wrap_err("failed to send recording frame")?;
+                    }
                 }
             }
         }
@@ -559,7 +569,7 @@ fn generate_cycle_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) ->
                 {
                     let own_subscribed_outputs = self.own_subscribed_outputs_reader.next();
                     let parameters = self.parameters_reader.next();
-                    #(#setup_node_executions)*
+                    #setup_node_executions
                 }
 
                 #post_setup
@@ -580,6 +590,49 @@ fn generate_cycle_method(cycler: &Cycler, cyclers: &Cyclers, mode: Execution) ->
     }
 }
 
+fn generate_setup_node_executions(cycler: &Cycler, mode: Execution) -> TokenStream {
+    let setup_node_executions = cycler
+        .setup_nodes
+        .iter()
+        .map(|node| generate_node_execution(node, cycler, NodeType::Setup, mode));
+    match mode {
+        Execution::None => Default::default(),
+        Execution::Run => {
+            let record_setup_node_states =
+                cycler.setup_nodes.iter().map(generate_record_node_state);
+            quote! {
+                if enable_hardware_inputs_recording {
+                    #(#record_setup_node_states)*
+                    self.hardware_inputs_interface.start_recording();
+                }
+                #(#setup_node_executions)*
+                if enable_hardware_inputs_recording {
+                    bincode::serialize_into(&mut recording_frame, &self.hardware_inputs_interface.finish_recording()).wrap_err("failed to record hardware inputs")?;
+                }
+            }
+        }
+        Execution::Replay => {
+            let restore_setup_node_states =
+                cycler.setup_nodes.iter().map(generate_restore_node_state);
+            let setup_node_reexecutions = cycler.setup_nodes.iter().map(|node| {
+                generate_execute_node_and_write_main_outputs(node, cycler, Execution::Replay)
+            });
+            quote! {
+                let replay_hardware_inputs: bool = bincode::deserialize_from(&mut recording_frame).wrap_err("failed to extract whether hardware inputs are recorded")?;
+                if replay_hardware_inputs {
+                    #(#restore_setup_node_states)*
+                    let hardware_inputs = bincode::deserialize_from(&mut recording_frame).wrap_err("failed to extract hardware inputs")?;
+                    self.hardware_inputs_interface.start_replaying(hardware_inputs);
+                    #(#setup_node_reexecutions)*
+                    self.hardware_inputs_interface.finish_replaying();
+                } else {
+                    #(#setup_node_executions)*
+                }
+            }
+        }
+    }
+}
+
 fn get_cross_input_fields(cycler: &Cycler) -> BTreeSet<Field> {
     cycler
         .setup_nodes
@@ -944,7 +997,7 @@ fn generate_record_main_outputs(node: &Node) -> TokenStream {
             Field::MainOutput { name, .. } => {
                 let error_message = format!("failed to record {name}");
                 Some(quote! {
-                    if enable_recording {
+                    if enable_recording && !enable_hardware_inputs_recording {
                         bincode::serialize_into(&mut recording_frame, &own_database_reference.main_outputs.#name).wrap_err(#error_message)?;
                     }
                 })
@@ -1091,9 +1144,21 @@ fn generate_context_initializers(node: &Node, cycler: &Cycler, mode: Execution)
                         },
                     }
                 }
-                Field::HardwareInterface { .. } => quote! {
-                    &self.hardware_interface
-                },
+                Field::HardwareInterface { .. } => {
+                    let is_setup_node = cycler
+                        .setup_nodes
+                        .iter()
+                        .any(|setup_node| setup_node.name == node.name);
+                    if is_setup_node {
+                        quote! {
+                            &self.hardware_inputs_interface
+                        }
+                    } else {
+                        quote! {
+                            &self.hardware_interface
+                        }
+                    }
+                }
                 Field::HistoricInput { path, data_type, .. } => {
                     match mode {
                         Execution::None => Default::default(),
@@ -25,12 +25,16 @@ pub fn generate_run_function(cyclers: &Cyclers) -> TokenStream {
         pub fn run(
             hardware_interface: std::sync::Arc<impl crate::HardwareInterface + Send + Sync + 'static>,
             addresses: Option<impl tokio::net::ToSocketAddrs + std::marker::Send + std::marker::Sync + 'static>,
+            communication_read_only_key: Option<String>,
+            communication_admin_key: Option<String>,
             parameters_directory: impl std::convert::AsRef<std::path::Path> + std::marker::Send + std::marker::Sync + 'static,
             log_path: impl std::convert::AsRef<std::path::Path> + std::marker::Send + std::marker::Sync + 'static,
             body_id: String,
             head_id: String,
             keep_running: tokio_util::sync::CancellationToken,
             recording_intervals: std::collections::HashMap<String, usize>,
+            record_hardware_inputs: std::collections::HashSet<String>,
+            event_recordings: std::collections::HashMap<String, framework::EventRecording>,
         ) -> color_eyre::Result<()>
         {
             use color_eyre::eyre::WrapErr;
@@ -51,8 +55,12 @@ pub fn generate_run_function(cyclers: &Cyclers) -> TokenStream {
             #construct_future_queues
             let (recording_sender, recording_receiver) = std::sync::mpsc::sync_channel(420);
 
+            let access_keys = communication::server::AccessKeys {
+                read_only: communication_read_only_key,
+                admin: communication_admin_key,
+            };
             let communication_server = communication::server::Runtime::start(
-                addresses, parameters_directory, body_id, head_id, #number_of_parameter_slots, keep_running.clone())
+                addresses, access_keys, parameters_directory, body_id, head_id, #number_of_parameter_slots, keep_running.clone())
                 .wrap_err("failed to start communication server")?;
 
             let recording_thread = #recording_thread;
@@ -114,6 +122,8 @@ pub fn generate_replayer_struct(cyclers: &Cyclers) -> TokenStream {
     let recording_index_entries_mut =
         generate_recording_index_entries(cyclers, ReferenceKind::Mutable);
     let cycler_replays = generate_cycler_replays(cyclers);
+    let output_subscriptions = generate_output_subscriptions(cyclers);
+    let output_getters = generate_output_getters(cyclers);
 
     quote! {
         pub struct Replayer<Hardware> {
@@ -138,8 +148,10 @@ pub fn generate_replayer_struct(cyclers: &Cyclers) -> TokenStream {
                 #construct_multiple_buffers
                 #construct_future_queues
 
+                // without addresses no client can subscribe, outputs are then subscribed via the replayer
+                let serve_communication = addresses.is_some();
                 let communication_server = communication::server::Runtime::start(
-                    addresses, parameters_directory, body_id, head_id, #number_of_parameter_slots, keep_running.clone())
+                    addresses, communication::server::AccessKeys::default(), parameters_directory, body_id, head_id, #number_of_parameter_slots, keep_running.clone())
                     .wrap_err("failed to start communication server")?;
 
                 #construct_cyclers
@@ -170,6 +182,34 @@ pub fn generate_replayer_struct(cyclers: &Cyclers) -> TokenStream {
                     _ => bail!("unexpected cycler instance name {cycler_instance_name}"),
                 }
             }
+
+            /// Sets which outputs are filled during replay, paths are relative to the database of the cycler instance (e.g. `additional_outputs.foo`)
+            ///
+            /// Only available if the replayer was created without communication addresses, otherwise connected clients manage the subscriptions.
+            pub fn subscribe_outputs(&mut self, cycler_instance_name: &str, paths: std::collections::HashSet<String>) -> color_eyre::Result<()> {
+                use color_eyre::eyre::bail;
+
+                let subscribed_outputs_writer = match cycler_instance_name {
+                    #output_subscriptions
+                    _ => bail!("unexpected cycler instance name {cycler_instance_name}"),
+                };
+                let Some(subscribed_outputs_writer) = subscribed_outputs_writer else {
+                    bail!("subscribed outputs of {cycler_instance_name} are managed by the communication server");
+                };
+                *subscribed_outputs_writer.next() = paths;
+                Ok(())
+            }
+
+            pub fn get_output(&self, cycler_instance_name: &str, path: &str) -> color_eyre::Result<serde_json::Value> {
+                use color_eyre::eyre::{bail, WrapErr};
+                use serialize_hierarchy::SerializeHierarchy;
+
+                match cycler_instance_name {
+                    #output_getters
+                    _ => bail!("unexpected cycler instance name {cycler_instance_name}"),
+                }
+                .wrap_err_with(|| format!("failed to serialize {cycler_instance_name}.{path}"))
+            }
         }
     }
 }
@@ -182,9 +222,17 @@ fn generate_cycler_fields(cyclers: &Cyclers) -> TokenStream {
                 format_ident!("{}_cycler", instance.to_case(Case::Snake));
             let cycler_module_name = format_ident!("{}", cycler.name.to_case(Case::Snake));
             let cycler_index_identifier = format_ident!("{}_index", instance.to_case(Case::Snake));
+            let cycler_reader_identifier =
+                format_ident!("{}_reader", instance.to_case(Case::Snake));
+            let cycler_subscribed_outputs_writer_identifier = format_ident!(
+                "{}_subscribed_outputs_writer",
+                instance.to_case(Case::Snake)
+            );
             quote! {
                 #cycler_variable_identifier: crate::cyclers::#cycler_module_name::Cycler<Hardware>,
                 #cycler_index_identifier: framework::RecordingIndex,
+                #cycler_reader_identifier: framework::Reader<crate::cyclers::#cycler_module_name::Database>,
+                #cycler_subscribed_outputs_writer_identifier: Option<framework::Writer<std::collections::HashSet<String>>>,
             }
         })
         .collect()
@@ -240,7 +288,7 @@ fn generate_recording_thread(cyclers: &Cyclers) -> TokenStream {
                     .expect("recording file path has no parent directory")
             ).wrap_err("failed to create logs folder")?;
 
-            let mut #instance_name_snake_case = std::io::BufWriter::new(std::fs::File::create(recording_file_path).wrap_err(#error_message_file)?); // TODO: possible optimization: buffer size
+            let mut #instance_name_snake_case = framework::RecordingWriter::create(recording_file_path, #instance).wrap_err(#error_message_file)?;
         }
     });
     let frame_writes = cyclers.instances().map(|(_cycler, instance)| {
@@ -248,17 +296,22 @@ fn generate_recording_thread(cyclers: &Cyclers) -> TokenStream {
         let instance_name_snake_case = format_ident!("{}", instance.to_case(Case::Snake));
         let error_message = format!("failed to write into recording file for {instance}");
         quote! {
-            crate::cyclers::RecordingFrame::#instance_name { timestamp, duration, data } => {
-                let mut recording_header = Vec::new();
-                bincode::serialize_into(&mut recording_header, &timestamp).wrap_err("failed to serialize timestamp")?;
-                bincode::serialize_into(&mut recording_header, &duration).wrap_err("failed to serialize duration")?;
-                bincode::serialize_into(&mut recording_header, &data.len()).wrap_err("failed to serialize data length")?;
-                #instance_name_snake_case.write_all(recording_header.as_slice()).wrap_err(#error_message)?;
-                #instance_name_snake_case.write_all(data.as_slice()).wrap_err(#error_message)?;
+            crate::cyclers::RecordingFrame::#instance_name { frames } => {
+                for frame in frames {
+                    #instance_name_snake_case.write_frame(frame.timing.timestamp, frame.timing.duration, frame.data.as_slice()).wrap_err(#error_message)?;
+                }
             },
         }
     });
 
+    let file_finishes = cyclers.instances().map(|(_cycler, instance)| {
+        let instance_name_snake_case = format_ident!("{}", instance.to_case(Case::Snake));
+        let error_message = format!("failed to finish recording file for {instance}");
+        quote! {
+            #instance_name_snake_case.finish().wrap_err(#error_message)?;
+        }
+    });
+
     quote! {
         {
             let keep_running = keep_running.clone();
@@ -267,7 +320,6 @@ fn generate_recording_thread(cyclers: &Cyclers) -> TokenStream {
                 .name("Recording".to_string())
                 .spawn(move || -> color_eyre::Result<()> {
                     let result = (|| {
-                        use std::io::Write;
                         std::fs::write(log_path.as_ref().join("default.json"), serde_json::to_string_pretty(&*parameters_reader.next())?)?;
                         #(#file_creations)*
                         for recording_frame in recording_receiver {
@@ -275,6 +327,7 @@ fn generate_recording_thread(cyclers: &Cyclers) -> TokenStream {
                                 #(#frame_writes)*
                             }
                         }
+                        #(#file_finishes)*
                         Ok(())
                     })();
 
@@ -300,10 +353,13 @@ fn generate_cycler_constructors(cyclers: &Cyclers, mode: Execution) -> TokenStre
         let own_subscribed_outputs_writer_identifier = format_ident!("{instance_name_snake_case}_subscribed_outputs_writer");
         let own_subscribed_outputs_reader_identifier = format_ident!("{instance_name_snake_case}_subscribed_outputs_reader");
         let recording_trigger = if mode == Execution::Run {
+            let recording_trigger_error_message =
+                format!("failed to create recording trigger for {instance}");
             quote! {
                 let recording_trigger = framework::RecordingTrigger::new(
-                    recording_intervals.get(#cycler_instance_name).copied().unwrap_or(0)
-                );
+                    recording_intervals.get(#cycler_instance_name).copied().unwrap_or(0),
+                    event_recordings.get(#cycler_instance_name).copied(),
+                ).wrap_err(#recording_trigger_error_message)?;
             }
         } else {
             Default::default()
@@ -343,11 +399,32 @@ fn generate_cycler_constructors(cyclers: &Cyclers, mode: Execution) -> TokenStre
             quote! {
                 recording_sender.clone(),
                 recording_trigger,
+                record_hardware_inputs.contains(#cycler_instance_name),
             }
         } else {
             Default::default()
         };
         let error_message = format!("failed to create cycler `{}`", instance);
+        let register_cycler_instance = quote! {
+            communication_server.register_cycler_instance(
+                #cycler_instance_name,
+                #cycler_database_changed_identifier,
+                #own_reader_identifier.clone(),
+                #own_subscribed_outputs_writer_identifier,
+            );
+        };
+        let register_cycler_instance = if mode == Execution::Replay {
+            quote! {
+                let #own_subscribed_outputs_writer_identifier = if serve_communication {
+                    #register_cycler_instance
+                    None
+                } else {
+                    Some(#own_subscribed_outputs_writer_identifier)
+                };
+            }
+        } else {
+            register_cycler_instance
+        };
         quote! {
             let #cycler_database_changed_identifier = std::sync::Arc::new(tokio::sync::Notify::new());
             let (#own_subscribed_outputs_writer_identifier, #own_subscribed_outputs_reader_identifier) = framework::multiple_buffer_with_slots([
@@ -369,12 +446,7 @@ fn generate_cycler_constructors(cyclers: &Cyclers, mode: Execution) -> TokenStre
                 #recording_parameters
             )
             .wrap_err(#error_message)?;
-            communication_server.register_cycler_instance(
-                #cycler_instance_name,
-                #cycler_database_changed_identifier,
-                #own_reader_identifier.clone(),
-                #own_subscribed_outputs_writer_identifier,
-            );
+            #register_cycler_instance
         }
     })
     .collect()
@@ -432,9 +504,17 @@ fn generate_cycler_parameters(cyclers: &Cyclers) -> TokenStream {
             let cycler_variable_identifier =
                 format_ident!("{}_cycler", instance.to_case(Case::Snake));
             let cycler_index_identifier = format_ident!("{}_index", instance.to_case(Case::Snake));
+            let cycler_reader_identifier =
+                format_ident!("{}_reader", instance.to_case(Case::Snake));
+            let cycler_subscribed_outputs_writer_identifier = format_ident!(
+                "{}_subscribed_outputs_writer",
+                instance.to_case(Case::Snake)
+            );
             quote! {
                 #cycler_variable_identifier,
                 #cycler_index_identifier,
+                #cycler_reader_identifier,
+                #cycler_subscribed_outputs_writer_identifier,
             }
         })
         .collect()
@@ -472,3 +552,31 @@ fn generate_cycler_replays(cyclers: &Cyclers) -> TokenStream {
         })
         .collect()
 }
+
+fn generate_output_subscriptions(cyclers: &Cyclers) -> TokenStream {
+    cyclers
+        .instances()
+        .map(|(_cycler, instance)| {
+            let cycler_subscribed_outputs_writer_identifier = format_ident!(
+                "{}_subscribed_outputs_writer",
+                instance.to_case(Case::Snake)
+            );
+            quote! {
+                #instance => &self.#cycler_subscribed_outputs_writer_identifier,
+            }
+        })
+        .collect()
+}
+
+fn generate_output_getters(cyclers: &Cyclers) -> TokenStream {
+    cyclers
+        .instances()
+        .map(|(_cycler, instance)| {
+            let cycler_reader_identifier =
+                format_ident!("{}_reader", instance.to_case(Case::Snake));
+            quote! {
+                #instance => self.#cycler_reader_identifier.next().serialize_path(path, serde_json::value::Serializer),
+            }
+        })
+        .collect()
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, sync::Arc};
 
 use serde_json::Value;
 use tokio::{
@@ -17,7 +17,7 @@ use crate::{
         parameter_subscription_manager::{self, parameter_subscription_manager},
         SubscriberMessage,
     },
-    messages::{Fields, Format, Path},
+    messages::{Fields, Format, Path, SubscriptionOptions},
 };
 
 use super::{
@@ -34,6 +34,7 @@ pub struct Communication {
     parameter_subscription_manager: mpsc::Sender<parameter_subscription_manager::Message>,
     update_sender: broadcast::Sender<()>,
     connection_status_update_receiver: watch::Receiver<ConnectionStatus>,
+    access_key_sender: Arc<watch::Sender<Option<String>>>,
 }
 
 impl Communication {
@@ -51,6 +52,7 @@ impl Communication {
                 address: address.clone(),
                 connect,
             });
+        let (access_key_sender, access_key_receiver) = watch::channel(None);
 
         spawn(connector(
             connector_receiver,
@@ -60,6 +62,7 @@ impl Communication {
             responder_sender.clone(),
             update_sender.clone(),
             connection_status_update_sender,
+            access_key_receiver,
             address,
             connect,
         ));
@@ -86,6 +89,7 @@ impl Communication {
             parameter_subscription_manager: parameter_subscription_manager_sender,
             update_sender,
             connection_status_update_receiver,
+            access_key_sender: Arc::new(access_key_sender),
         }
     }
 
@@ -103,6 +107,11 @@ impl Communication {
             .unwrap();
     }
 
+    /// Sets the pre-shared key which is sent to the server on the next connection
+    pub fn set_access_key(&self, key: Option<String>) {
+        self.access_key_sender.send_replace(key);
+    }
+
     pub fn subscribe_connection_status_updates(&self) -> watch::Receiver<ConnectionStatus> {
         self.connection_status_update_receiver.clone()
     }
@@ -115,6 +124,7 @@ impl Communication {
         &self,
         output: CyclerOutput,
         format: Format,
+        options: SubscriptionOptions,
     ) -> (Uuid, Receiver<SubscriberMessage>) {
         let (subscriber_sender, subscriber_receiver) = mpsc::channel(10);
         let (response_sender, response_receiver) = oneshot::channel();
@@ -122,6 +132,7 @@ impl Communication {
             .send(output_subscription_manager::Message::Subscribe {
                 output,
                 format,
+                options,
                 subscriber: subscriber_sender,
                 response_sender,
             })
@@ -16,9 +16,12 @@ use tokio::{
 };
 use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 
-use crate::client::{
-    output_subscription_manager, parameter_subscription_manager,
-    receiver::receiver as receiver_task, requester::requester, responder,
+use crate::{
+    client::{
+        output_subscription_manager, parameter_subscription_manager,
+        receiver::receiver as receiver_task, requester::requester, responder,
+    },
+    messages::{AuthenticationRequest, Request},
 };
 
 #[derive(Debug)]
@@ -68,6 +71,7 @@ pub async fn connector(
     responder: Sender<responder::Message>,
     update_sender: broadcast::Sender<()>,
     connection_status_update_sender: watch::Sender<ConnectionStatus>,
+    access_key: watch::Receiver<Option<String>>,
     initial_address: Option<String>,
     initial_connect: bool,
 ) {
@@ -210,6 +214,17 @@ pub async fn connector(
                 Message::Connected(ws_stream) => {
                     let (writer, reader) = (*ws_stream).split();
                     let (requester_sender, requester_receiver) = channel(10);
+                    let key = access_key.borrow().clone();
+                    if let Some(key) = key {
+                        // requests are handled in order, so the server knows the role of this
+                        // client before the subscription managers send their requests
+                        requester_sender
+                            .send(Request::Authentication(
+                                AuthenticationRequest::Authenticate { key },
+                            ))
+                            .await
+                            .unwrap();
+                    }
                     output_subscription_manager
                         .send(output_subscription_manager::Message::Connect {
                             requester: requester_sender.clone(),
@@ -4,10 +4,14 @@ mod id_tracker;
 mod output_subscription_manager;
 mod parameter_subscription_manager;
 mod receiver;
+pub mod recorder;
 mod requester;
 mod responder;
 mod types;
 
 pub use crate::client::communication::Communication;
 pub use connector::ConnectionStatus;
+pub use recorder::{
+    read_subscription_records, record_subscriptions, RecordedUpdate, SubscriptionRecord,
+};
 pub use types::{Cycler, CyclerOutput, HierarchyType, Output, OutputHierarchy, SubscriberMessage};
@@ -2,6 +2,7 @@ use std::collections::{hash_map::Entry, HashMap};
 
 use color_eyre::Result;
 use log::{error, info, warn};
+use lz4_flex::decompress_size_prepended;
 use tokio::{
     spawn,
     sync::{broadcast, mpsc, oneshot},
@@ -14,7 +15,7 @@ use crate::{
         responder, Output, SubscriberMessage,
     },
     messages::{
-        Fields, Format, OutputsRequest, Request,
+        Fields, Format, OutputsRequest, Request, SubscriptionOptions,
         TextualDataOrBinaryReference::{self, BinaryReference, TextualData},
     },
 };
@@ -30,6 +31,7 @@ pub enum Message {
     Subscribe {
         output: CyclerOutput,
         format: Format,
+        options: SubscriptionOptions,
         subscriber: mpsc::Sender<SubscriberMessage>,
         response_sender: oneshot::Sender<Uuid>,
     },
@@ -50,11 +52,13 @@ pub enum Message {
     },
 }
 
+type SubscriptionKey = (CyclerOutput, Format, SubscriptionOptions);
+
 #[derive(Default)]
 struct SubscriptionManager {
-    ids_to_outputs: HashMap<usize, (CyclerOutput, Format)>,
+    ids_to_outputs: HashMap<usize, SubscriptionKey>,
     outputs_to_subscribers:
-        HashMap<(CyclerOutput, Format), HashMap<Uuid, mpsc::Sender<SubscriberMessage>>>,
+        HashMap<SubscriptionKey, HashMap<Uuid, mpsc::Sender<SubscriberMessage>>>,
 }
 
 pub async fn output_subscription_manager(
@@ -68,7 +72,7 @@ pub async fn output_subscription_manager(
     let mut requester = None;
     let mut fields = None;
     let mut binary_data_waiting_for_references: HashMap<usize, Vec<u8>> = HashMap::new();
-    let mut binary_references_waiting_for_data: HashMap<usize, CyclerOutput> = HashMap::new();
+    let mut binary_references_waiting_for_data: HashMap<usize, SubscriptionKey> = HashMap::new();
 
     while let Some(message) = receiver.recv().await {
         match message {
@@ -76,11 +80,12 @@ pub async fn output_subscription_manager(
                 requester: new_requester,
             } => {
                 assert!(manager.ids_to_outputs.is_empty());
-                for ((output, format), subscribers) in &manager.outputs_to_subscribers {
+                for ((output, format, options), subscribers) in &manager.outputs_to_subscribers {
                     let subscribers = subscribers.values().cloned().collect();
                     if let Some(subscription_id) = subscribe(
                         output.clone(),
                         *format,
+                        *options,
                         subscribers,
                         &id_tracker,
                         &responder,
@@ -90,7 +95,7 @@ pub async fn output_subscription_manager(
                     {
                         manager
                             .ids_to_outputs
-                            .insert(subscription_id, (output.clone(), *format));
+                            .insert(subscription_id, (output.clone(), *format, *options));
                     }
                 }
                 match query_output_fields(sender.clone(), &id_tracker, &responder, &new_requester)
@@ -109,6 +114,7 @@ pub async fn output_subscription_manager(
             Message::Subscribe {
                 output,
                 format,
+                options,
                 subscriber: output_sender,
                 response_sender,
             } => {
@@ -120,6 +126,7 @@ pub async fn output_subscription_manager(
                             uuid,
                             output,
                             format,
+                            options,
                             output_sender,
                             &id_tracker,
                             &responder,
@@ -184,6 +191,9 @@ pub async fn output_subscription_manager(
                                 if let Some(image) =
                                     binary_data_waiting_for_references.remove(&reference_id)
                                 {
+                                    let Some(image) = decode_binary_data(output.1, image) else {
+                                        continue;
+                                    };
                                     for sender in senders.values() {
                                         if let Err(error) = sender
                                             .send(SubscriberMessage::UpdateBinary {
@@ -196,7 +206,7 @@ pub async fn output_subscription_manager(
                                     }
                                 } else {
                                     binary_references_waiting_for_data
-                                        .insert(reference_id, output.0.clone());
+                                        .insert(reference_id, output.clone());
                                 }
                             }
                         }
@@ -216,9 +226,10 @@ pub async fn output_subscription_manager(
             Message::UpdateBinary { referenced_items } => {
                 for (reference_id, data) in referenced_items {
                     if let Some(output) = binary_references_waiting_for_data.get(&reference_id) {
-                        let subscribers = manager
-                            .outputs_to_subscribers
-                            .get(&(output.clone(), Format::Binary));
+                        let subscribers = manager.outputs_to_subscribers.get(output);
+                        let Some(data) = decode_binary_data(output.1, data) else {
+                            continue;
+                        };
                         if let Some(senders) = subscribers {
                             for sender in senders.values() {
                                 if let Err(error) = sender
@@ -240,6 +251,19 @@ pub async fn output_subscription_manager(
     info!("Finished manager");
 }
 
+fn decode_binary_data(format: Format, data: Vec<u8>) -> Option<Vec<u8>> {
+    match format {
+        Format::CompressedBinary => match decompress_size_prepended(&data) {
+            Ok(data) => Some(data),
+            Err(error) => {
+                error!("failed to decompress binary data: {error}");
+                None
+            }
+        },
+        Format::Textual | Format::Binary => Some(data),
+    }
+}
+
 async fn query_output_fields(
     manager: mpsc::Sender<Message>,
     id_tracker: &mpsc::Sender<id_tracker::Message>,
@@ -276,6 +300,7 @@ async fn add_subscription(
     uuid: Uuid,
     output: CyclerOutput,
     format: Format,
+    options: SubscriptionOptions,
     output_sender: mpsc::Sender<SubscriberMessage>,
     id_tracker: &mpsc::Sender<id_tracker::Message>,
     responder: &mpsc::Sender<responder::Message>,
@@ -283,7 +308,7 @@ async fn add_subscription(
 ) {
     match manager
         .outputs_to_subscribers
-        .entry((output.clone(), format))
+        .entry((output.clone(), format, options))
     {
         Entry::Occupied(mut entry) => {
             entry.get_mut().insert(uuid, output_sender);
@@ -293,6 +318,7 @@ async fn add_subscription(
                 if let Some(subscription_id) = subscribe(
                     output.clone(),
                     format,
+                    options,
                     vec![output_sender.clone()],
                     id_tracker,
                     responder,
@@ -302,7 +328,7 @@ async fn add_subscription(
                 {
                     manager
                         .ids_to_outputs
-                        .insert(subscription_id, (output, format));
+                        .insert(subscription_id, (output, format, options));
                 }
             };
             entry.insert(HashMap::new()).insert(uuid, output_sender);
@@ -313,6 +339,7 @@ async fn add_subscription(
 async fn subscribe(
     output: CyclerOutput,
     format: Format,
+    options: SubscriptionOptions,
     subscribers: Vec<mpsc::Sender<SubscriberMessage>>,
     id_tracker: &mpsc::Sender<id_tracker::Message>,
     responder: &mpsc::Sender<responder::Message>,
@@ -339,6 +366,7 @@ async fn subscribe(
         cycler_instance: output.cycler.to_string(),
         path,
         format,
+        options,
     });
     if let Err(error) = requester.send(request).await {
         error!("{error}");
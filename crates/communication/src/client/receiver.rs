@@ -11,8 +11,8 @@ use crate::{
         responder::{Message, Response},
     },
     messages::{
-        BinaryOutputsResponse, BinaryResponse, ParametersResponse, TextualOutputsResponse,
-        TextualResponse,
+        AuthenticationResponse, BinaryOutputsResponse, BinaryResponse, ParametersResponse,
+        TextualOutputsResponse, TextualResponse,
     },
 };
 
@@ -38,6 +38,12 @@ pub async fn receiver(
                         }
                     };
                     match message {
+                        TextualResponse::Authentication(AuthenticationResponse::Authenticate {
+                            result,
+                        }) => match result {
+                            Ok(role) => info!("Authenticated as {role:?}"),
+                            Err(error) => error!("Failed to authenticate: {error}"),
+                        },
                         TextualResponse::Outputs(outputs_message) => match outputs_message {
                             TextualOutputsResponse::GetFields { id, fields } => {
                                 respond(&responder, id, Response::Fields(fields)).await
@@ -0,0 +1,165 @@
+use std::{
+    io::{BufRead, Write},
+    time::SystemTime,
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_writer, Value};
+use tokio::{select, spawn, sync::mpsc};
+use tokio_util::sync::CancellationToken;
+
+use crate::messages::{Format, SubscriptionOptions};
+
+use super::{Communication, CyclerOutput, Output, SubscriberMessage};
+
+/// One update of a subscribed output as stored in a subscription recording
+///
+/// Subscription recordings are JSON Lines files with one record per line.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubscriptionRecord {
+    /// Time when the update was received
+    pub timestamp: SystemTime,
+    pub robot: String,
+    pub output: CyclerOutput,
+    pub update: RecordedUpdate,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RecordedUpdate {
+    Textual(Value),
+    /// Stored as Base64 string, JSON arrays of numbers would multiply the size of images
+    Binary(#[serde(with = "base64_data")] Vec<u8>),
+}
+
+mod base64_data {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(Error::custom)
+    }
+}
+
+/// Outputs whose last path segment is a JPEG projection (e.g. `image.jpeg`) are recorded in binary
+/// format like Twix subscribes them, all other outputs are recorded as JSON
+pub fn recording_format(output: &CyclerOutput) -> Format {
+    let path = match &output.output {
+        Output::Main { path } | Output::Additional { path } => path,
+    };
+    let last_segment = path.rsplit('.').next().unwrap_or_default();
+    if last_segment.starts_with("jpeg") {
+        Format::Binary
+    } else {
+        Format::Textual
+    }
+}
+
+/// Records all updates of the outputs from all robots until `keep_running` is cancelled
+///
+/// Robots are given as websocket addresses, e.g. `ws://10.1.24.42:1337`.
+pub async fn record_subscriptions(
+    robots: Vec<String>,
+    outputs: Vec<CyclerOutput>,
+    access_key: Option<String>,
+    mut writer: impl Write,
+    keep_running: CancellationToken,
+) -> Result<()> {
+    let (record_sender, mut record_receiver) = mpsc::channel(100);
+    let mut communications = Vec::new();
+    for robot in robots {
+        let communication = Communication::new(Some(robot.clone()), false);
+        communication.set_access_key(access_key.clone());
+        communication.set_connect(true).await;
+        for output in &outputs {
+            let (_uuid, mut receiver) = communication
+                .subscribe_output(
+                    output.clone(),
+                    recording_format(output),
+                    SubscriptionOptions::default(),
+                )
+                .await;
+            let record_sender = record_sender.clone();
+            let robot = robot.clone();
+            let output = output.clone();
+            spawn(async move {
+                while let Some(message) = receiver.recv().await {
+                    let update = match message {
+                        SubscriberMessage::Update { value } => RecordedUpdate::Textual(value),
+                        SubscriberMessage::UpdateBinary { data } => RecordedUpdate::Binary(data),
+                        SubscriberMessage::SubscriptionSuccess => {
+                            info!("{robot}: subscribed to {output:?}");
+                            continue;
+                        }
+                        SubscriberMessage::SubscriptionFailure { info } => {
+                            error!("{robot}: failed to subscribe to {output:?}: {info}");
+                            break;
+                        }
+                    };
+                    let record = SubscriptionRecord {
+                        timestamp: SystemTime::now(),
+                        robot: robot.clone(),
+                        output: output.clone(),
+                        update,
+                    };
+                    if record_sender.send(record).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        communications.push(communication);
+    }
+    drop(record_sender);
+
+    loop {
+        let record = select! {
+            record = record_receiver.recv() => match record {
+                Some(record) => record,
+                None => break,
+            },
+            _ = keep_running.cancelled() => break,
+        };
+        to_writer(&mut writer, &record).wrap_err("failed to serialize record")?;
+        writeln!(writer).wrap_err("failed to write record")?;
+    }
+    writer.flush().wrap_err("failed to flush recording")
+}
+
+pub fn read_subscription_records(reader: impl BufRead) -> Result<Vec<SubscriptionRecord>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(index, line)| {
+            let line = line.wrap_err("failed to read line")?;
+            from_str(&line)
+                .wrap_err_with(|| format!("failed to parse record in line {}", index + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::to_string;
+
+    use super::*;
+
+    #[test]
+    fn binary_updates_are_stored_as_base64() {
+        let update = RecordedUpdate::Binary(vec![0, 1, 2, 253, 254, 255]);
+        let serialized = to_string(&update).unwrap();
+        assert_eq!(serialized, r#"{"Binary":"AAEC/f7/"}"#);
+
+        let RecordedUpdate::Binary(data) = from_str(&serialized).unwrap() else {
+            panic!("expected binary update");
+        };
+        assert_eq!(data, [0, 1, 2, 253, 254, 255]);
+    }
+}
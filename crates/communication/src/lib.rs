@@ -1,4 +1,6 @@
 pub mod client;
 pub mod messages;
 #[cfg(feature = "server")]
+pub mod playback;
+#[cfg(feature = "server")]
 pub mod server;
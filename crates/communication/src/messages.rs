@@ -1,4 +1,7 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::Duration,
+};
 
 use parameters::directory::Scope;
 use serde::{Deserialize, Serialize};
@@ -13,6 +16,7 @@ pub type Fields = BTreeMap<CyclerInstance, BTreeSet<Path>>;
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Request {
+    Authentication(AuthenticationRequest),
     Injections(InjectionsRequest),
     Outputs(OutputsRequest),
     Parameters(ParametersRequest),
@@ -27,6 +31,7 @@ pub enum Response {
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum TextualResponse {
+    Authentication(AuthenticationResponse),
     Injections(InjectionsResponse),
     Outputs(TextualOutputsResponse),
     Parameters(ParametersResponse),
@@ -37,6 +42,25 @@ pub enum BinaryResponse {
     Outputs(BinaryOutputsResponse),
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AuthenticationRequest {
+    Authenticate { key: String },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AuthenticationResponse {
+    Authenticate { result: Result<Role, Reason> },
+}
+
+/// Capabilities of a connected client, ordered by increasing capabilities
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Role {
+    /// May subscribe to outputs and read parameters
+    ReadOnly,
+    /// May additionally inject values, update parameters, and store them to disk
+    Admin,
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum InjectionsRequest {
     Set {
@@ -81,6 +105,8 @@ pub enum OutputsRequest {
         cycler_instance: CyclerInstance,
         path: Path,
         format: Format,
+        #[serde(default)]
+        options: SubscriptionOptions,
     },
     Unsubscribe {
         id: usize,
@@ -89,6 +115,51 @@ pub enum OutputsRequest {
     UnsubscribeEverything,
 }
 
+/// Limits how often the data of an output subscription is sent
+///
+/// All limits are applied per subscription, the default sends the data of every cycle.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SubscriptionOptions {
+    /// Minimum time between two sent values, i.e. the inverse of the maximum rate
+    pub minimum_interval: Option<Duration>,
+    /// Only every n-th cycle is considered for sending
+    pub decimation: Option<usize>,
+    /// Values equal to the previously sent value are skipped
+    pub on_change_only: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("maximum rate has to be positive, got {0}")]
+pub struct InvalidMaximumRate(pub f32);
+
+impl SubscriptionOptions {
+    pub fn with_maximum_rate(self, maximum_rate: f32) -> Result<Self, InvalidMaximumRate> {
+        if maximum_rate.is_nan() || maximum_rate <= 0.0 {
+            return Err(InvalidMaximumRate(maximum_rate));
+        }
+        let minimum_interval = Duration::try_from_secs_f32(1.0 / maximum_rate)
+            .map_err(|_| InvalidMaximumRate(maximum_rate))?;
+        Ok(Self {
+            minimum_interval: Some(minimum_interval),
+            ..self
+        })
+    }
+
+    pub fn with_decimation(self, decimation: usize) -> Self {
+        Self {
+            decimation: Some(decimation),
+            ..self
+        }
+    }
+
+    pub fn with_on_change_only(self) -> Self {
+        Self {
+            on_change_only: true,
+            ..self
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum TextualOutputsResponse {
     GetFields {
@@ -179,6 +250,34 @@ pub enum ParametersResponse {
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Format {
+    /// JSON, sent inline in the textual response
     Textual,
+    /// Bincode, sent in a separate binary response
     Binary,
+    /// LZ4 compressed bincode, sent in a separate binary response
+    ///
+    /// Clients decompress the data before passing it on, subscribers receive plain bincode.
+    CompressedBinary,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maximum_rate_is_converted_to_minimum_interval() {
+        let options = SubscriptionOptions::default()
+            .with_maximum_rate(4.0)
+            .unwrap();
+        assert_eq!(options.minimum_interval, Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn non_positive_maximum_rates_are_rejected() {
+        for maximum_rate in [0.0, -1.0, f32::NAN, f32::MIN_POSITIVE / 4.0] {
+            assert!(SubscriptionOptions::default()
+                .with_maximum_rate(maximum_rate)
+                .is_err());
+        }
+    }
 }
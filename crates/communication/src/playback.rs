@@ -0,0 +1,495 @@
+//! Stand-in server which plays back a subscription recording
+//!
+//! The server speaks the same websocket protocol as the framework, clients like Twix cannot tell it
+//! apart from a live robot. Only recorded outputs can be subscribed, parameters and injections are
+//! not available during playback.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+use bincode::serialize;
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use futures_util::{SinkExt, StreamExt};
+use log::{error, info};
+use lz4_flex::compress_prepend_size;
+use serde_json::{from_str, to_string, Value};
+use tokio::{
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    select, spawn,
+    sync::broadcast::{self, error::RecvError},
+    time::sleep,
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    client::{CyclerOutput, Output, RecordedUpdate, SubscriptionRecord},
+    messages::{
+        AuthenticationRequest, AuthenticationResponse, BinaryOutputsResponse, BinaryResponse,
+        CyclerInstance, Fields, Format, InjectionsRequest, InjectionsResponse, OutputsRequest,
+        ParametersRequest, ParametersResponse, Path, Request, Role, TextualDataOrBinaryReference,
+        TextualOutputsResponse, TextualResponse,
+    },
+};
+
+const PAUSE_BEFORE_REPETITION: Duration = Duration::from_secs(1);
+
+/// Serves the records of a single robot in their original timing until `keep_running` is cancelled
+///
+/// The recording is repeated after a short pause.
+pub async fn serve_playback(
+    address: impl ToSocketAddrs,
+    mut records: Vec<SubscriptionRecord>,
+    keep_running: CancellationToken,
+) -> Result<()> {
+    if records.is_empty() {
+        bail!("recording does not contain any records");
+    }
+    records.sort_by_key(|record| record.timestamp);
+    let fields = recorded_fields(&records);
+    let listener = TcpListener::bind(address)
+        .await
+        .wrap_err("failed to bind playback server")?;
+    let (record_sender, _) = broadcast::channel(100);
+
+    {
+        let record_sender = record_sender.clone();
+        let keep_running = keep_running.clone();
+        spawn(async move {
+            select! {
+                _ = play_records(records, record_sender) => {},
+                _ = keep_running.cancelled() => {},
+            }
+        });
+    }
+
+    loop {
+        let stream = select! {
+            result = listener.accept() => match result {
+                Ok((stream, address)) => {
+                    info!("accepted connection from {address}");
+                    stream
+                }
+                Err(error) => {
+                    error!("failed to accept connection: {error}");
+                    continue;
+                }
+            },
+            _ = keep_running.cancelled() => break,
+        };
+        let fields = fields.clone();
+        let record_receiver = record_sender.subscribe();
+        let keep_running = keep_running.clone();
+        spawn(async move {
+            let websocket = match accept_async(stream).await {
+                Ok(websocket) => websocket,
+                Err(error) => {
+                    error!("failed to accept websocket: {error}");
+                    return;
+                }
+            };
+            select! {
+                result = serve_connection(websocket, fields, record_receiver) => {
+                    if let Err(error) = result {
+                        error!("{error:?}");
+                    }
+                },
+                _ = keep_running.cancelled() => {},
+            }
+        });
+    }
+    Ok(())
+}
+
+async fn play_records(
+    records: Vec<SubscriptionRecord>,
+    record_sender: broadcast::Sender<Arc<SubscriptionRecord>>,
+) {
+    let records: Vec<_> = records.into_iter().map(Arc::new).collect();
+    loop {
+        let mut previous_timestamp = records[0].timestamp;
+        for record in &records {
+            let waiting_time = record
+                .timestamp
+                .duration_since(previous_timestamp)
+                .unwrap_or_default();
+            sleep(waiting_time).await;
+            previous_timestamp = record.timestamp;
+            // sending only fails if no client is connected
+            let _ = record_sender.send(record.clone());
+        }
+        sleep(PAUSE_BEFORE_REPETITION).await;
+    }
+}
+
+fn server_path(output: &CyclerOutput) -> (CyclerInstance, Path) {
+    let path = match &output.output {
+        Output::Main { path } => format!("main_outputs.{path}"),
+        Output::Additional { path } => format!("additional_outputs.{path}"),
+    };
+    (output.cycler.to_string(), path)
+}
+
+fn recorded_fields(records: &[SubscriptionRecord]) -> Fields {
+    let mut fields = Fields::new();
+    for record in records {
+        let (cycler_instance, path) = server_path(&record.output);
+        let paths: &mut BTreeSet<Path> = fields.entry(cycler_instance).or_default();
+        let mut prefix = String::new();
+        for segment in path.split('.') {
+            if !prefix.is_empty() {
+                prefix.push('.');
+            }
+            prefix.push_str(segment);
+            paths.insert(prefix.clone());
+        }
+    }
+    fields
+}
+
+struct Subscription {
+    cycler_instance: CyclerInstance,
+    path: Path,
+    format: Format,
+}
+
+async fn serve_connection(
+    websocket: WebSocketStream<TcpStream>,
+    fields: Fields,
+    mut record_receiver: broadcast::Receiver<Arc<SubscriptionRecord>>,
+) -> Result<()> {
+    let (mut writer, mut reader) = websocket.split();
+    let mut subscriptions = HashMap::new();
+    let mut next_binary_reference_id = 0;
+    loop {
+        let messages = select! {
+            message = reader.next() => {
+                let Some(message) = message else {
+                    return Ok(());
+                };
+                match message.wrap_err("failed to receive message")? {
+                    Message::Text(text) => {
+                        let request = from_str(&text).wrap_err("failed to parse request")?;
+                        Vec::from_iter(handle_request(request, &fields, &mut subscriptions))
+                    }
+                    Message::Close(_) => return Ok(()),
+                    _ => Vec::new(),
+                }
+            },
+            record = record_receiver.recv() => match record {
+                Ok(record) => {
+                    subscribed_data(&record, &subscriptions, &mut next_binary_reference_id)?
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    error!("client is too slow, skipped {skipped} records");
+                    Vec::new()
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            },
+        };
+        for message in messages {
+            writer
+                .send(message)
+                .await
+                .wrap_err("failed to send message")?;
+        }
+    }
+}
+
+fn handle_request(
+    request: Request,
+    fields: &Fields,
+    subscriptions: &mut HashMap<usize, Subscription>,
+) -> Option<Message> {
+    const REASON: &str = "not available during playback";
+    let response = match request {
+        Request::Authentication(AuthenticationRequest::Authenticate { .. }) => {
+            TextualResponse::Authentication(AuthenticationResponse::Authenticate {
+                result: Ok(Role::Admin),
+            })
+        }
+        Request::Injections(InjectionsRequest::Set { id, .. }) => {
+            TextualResponse::Injections(InjectionsResponse::Set {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Injections(InjectionsRequest::Unset { id, .. }) => {
+            TextualResponse::Injections(InjectionsResponse::Unset {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Injections(InjectionsRequest::UnsetEverything) => return None,
+        Request::Outputs(OutputsRequest::GetFields { id }) => {
+            TextualResponse::Outputs(TextualOutputsResponse::GetFields {
+                id,
+                fields: fields.clone(),
+            })
+        }
+        Request::Outputs(OutputsRequest::GetNext { id, .. }) => {
+            TextualResponse::Outputs(TextualOutputsResponse::GetNext {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Outputs(OutputsRequest::Subscribe {
+            id,
+            cycler_instance,
+            path,
+            format,
+            ..
+        }) => {
+            let is_recorded = fields
+                .get(&cycler_instance)
+                .is_some_and(|paths| paths.contains(&path));
+            let result = if !is_recorded {
+                Err(format!("{cycler_instance}.{path} is not recorded"))
+            } else if subscriptions.contains_key(&id) {
+                Err(format!("already subscribed with id {id}"))
+            } else {
+                subscriptions.insert(
+                    id,
+                    Subscription {
+                        cycler_instance,
+                        path,
+                        format,
+                    },
+                );
+                Ok(())
+            };
+            TextualResponse::Outputs(TextualOutputsResponse::Subscribe { id, result })
+        }
+        Request::Outputs(OutputsRequest::Unsubscribe {
+            id,
+            subscription_id,
+        }) => {
+            let result = match subscriptions.remove(&subscription_id) {
+                Some(_) => Ok(()),
+                None => Err(format!("never subscribed with id {subscription_id}")),
+            };
+            TextualResponse::Outputs(TextualOutputsResponse::Unsubscribe { id, result })
+        }
+        Request::Outputs(OutputsRequest::UnsubscribeEverything) => {
+            subscriptions.clear();
+            return None;
+        }
+        Request::Parameters(ParametersRequest::GetFields { id }) => {
+            TextualResponse::Parameters(ParametersResponse::GetFields {
+                id,
+                fields: Default::default(),
+            })
+        }
+        Request::Parameters(ParametersRequest::GetCurrent { id, .. }) => {
+            TextualResponse::Parameters(ParametersResponse::GetCurrent {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Parameters(ParametersRequest::Subscribe { id, .. }) => {
+            TextualResponse::Parameters(ParametersResponse::Subscribe {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Parameters(ParametersRequest::Unsubscribe { id, .. }) => {
+            TextualResponse::Parameters(ParametersResponse::Unsubscribe {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Parameters(ParametersRequest::UnsubscribeEverything) => return None,
+        Request::Parameters(ParametersRequest::Update { id, .. }) => {
+            TextualResponse::Parameters(ParametersResponse::Update {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Parameters(ParametersRequest::LoadFromDisk { id }) => {
+            TextualResponse::Parameters(ParametersResponse::LoadFromDisk {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+        Request::Parameters(ParametersRequest::StoreToDisk { id, .. }) => {
+            TextualResponse::Parameters(ParametersResponse::StoreToDisk {
+                id,
+                result: Err(REASON.to_string()),
+            })
+        }
+    };
+    Some(Message::Text(
+        to_string(&response).expect("responses should always be serializable"),
+    ))
+}
+
+/// Data of all subscriptions which are affected by the record
+///
+/// Textual subscriptions of fields below a recorded output receive the corresponding part of the
+/// recorded value, binary subscriptions require the exact path to be recorded in binary format.
+fn subscribed_data(
+    record: &SubscriptionRecord,
+    subscriptions: &HashMap<usize, Subscription>,
+    next_binary_reference_id: &mut usize,
+) -> Result<Vec<Message>> {
+    let (cycler_instance, recorded_path) = server_path(&record.output);
+    let mut items = HashMap::new();
+    let mut referenced_items = HashMap::new();
+    for (subscription_id, subscription) in subscriptions {
+        if subscription.cycler_instance != cycler_instance {
+            continue;
+        }
+        let item = match (&record.update, subscription.format) {
+            (RecordedUpdate::Textual(value), Format::Textual) => {
+                let Some(data) = project(value, &recorded_path, &subscription.path) else {
+                    continue;
+                };
+                TextualDataOrBinaryReference::TextualData { data }
+            }
+            (RecordedUpdate::Binary(data), Format::Binary | Format::CompressedBinary)
+                if subscription.path == recorded_path =>
+            {
+                let data = if subscription.format == Format::CompressedBinary {
+                    compress_prepend_size(data)
+                } else {
+                    data.clone()
+                };
+                let reference_id = *next_binary_reference_id;
+                *next_binary_reference_id += 1;
+                referenced_items.insert(reference_id, data);
+                TextualDataOrBinaryReference::BinaryReference { reference_id }
+            }
+            _ => continue,
+        };
+        items.insert(*subscription_id, item);
+    }
+
+    let mut messages = Vec::new();
+    if !items.is_empty() {
+        let response = TextualResponse::Outputs(TextualOutputsResponse::SubscribedData { items });
+        messages.push(Message::Text(
+            to_string(&response).wrap_err("failed to serialize response")?,
+        ));
+    }
+    if !referenced_items.is_empty() {
+        let response =
+            BinaryResponse::Outputs(BinaryOutputsResponse::SubscribedData { referenced_items });
+        messages.push(Message::Binary(
+            serialize(&response).wrap_err("failed to serialize response")?,
+        ));
+    }
+    Ok(messages)
+}
+
+fn project(value: &Value, recorded_path: &str, subscribed_path: &str) -> Option<Value> {
+    if subscribed_path == recorded_path {
+        return Some(value.clone());
+    }
+    let relative_path = subscribed_path
+        .strip_prefix(recorded_path)?
+        .strip_prefix('.')?;
+    relative_path
+        .split('.')
+        .try_fold(value, |value, segment| value.get(segment))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use serde_json::json;
+
+    use crate::client::Cycler;
+
+    use super::*;
+
+    fn record(path: &str, update: RecordedUpdate) -> SubscriptionRecord {
+        SubscriptionRecord {
+            timestamp: SystemTime::UNIX_EPOCH,
+            robot: "ws://localhost:1337".to_string(),
+            output: CyclerOutput {
+                cycler: Cycler::Control,
+                output: Output::Main {
+                    path: path.to_string(),
+                },
+            },
+            update,
+        }
+    }
+
+    #[test]
+    fn fields_contain_recorded_outputs_and_their_parents() {
+        let fields = recorded_fields(&[record(
+            "ball.position",
+            RecordedUpdate::Textual(Value::Null),
+        )]);
+
+        assert_eq!(
+            fields,
+            [(
+                "Control".to_string(),
+                [
+                    "main_outputs".to_string(),
+                    "main_outputs.ball".to_string(),
+                    "main_outputs.ball.position".to_string(),
+                ]
+                .into()
+            )]
+            .into()
+        );
+    }
+
+    #[test]
+    fn textual_subscriptions_receive_projected_values() {
+        let record = record(
+            "ball",
+            RecordedUpdate::Textual(json!({"position": [1.0, 2.0], "velocity": [0.0, 0.0]})),
+        );
+        let subscriptions = HashMap::from([
+            (
+                0,
+                Subscription {
+                    cycler_instance: "Control".to_string(),
+                    path: "main_outputs.ball.position".to_string(),
+                    format: Format::Textual,
+                },
+            ),
+            (
+                1,
+                Subscription {
+                    cycler_instance: "Control".to_string(),
+                    path: "main_outputs.robot_position".to_string(),
+                    format: Format::Textual,
+                },
+            ),
+        ]);
+        let mut next_binary_reference_id = 0;
+
+        let messages = subscribed_data(&record, &subscriptions, &mut next_binary_reference_id)
+            .expect("record should be serializable");
+
+        let [Message::Text(text)] = messages.as_slice() else {
+            panic!("expected exactly one textual message, got {messages:?}");
+        };
+        let response: TextualResponse = from_str(text).unwrap();
+        assert_eq!(
+            response,
+            TextualResponse::Outputs(TextualOutputsResponse::SubscribedData {
+                items: [(
+                    0,
+                    TextualDataOrBinaryReference::TextualData {
+                        data: json!([1.0, 2.0])
+                    }
+                )]
+                .into()
+            })
+        );
+    }
+}
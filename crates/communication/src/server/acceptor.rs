@@ -15,6 +15,7 @@ use tokio_util::sync::CancellationToken;
 use crate::messages::ParametersRequest;
 
 use super::{
+    access_control::AccessKeys,
     client_request::ClientRequest,
     connection::{connection, ConnectionError},
     outputs,
@@ -32,6 +33,7 @@ pub enum AcceptError {
 
 pub fn acceptor(
     addresses: impl ToSocketAddrs + Send + Sync + 'static,
+    access_keys: AccessKeys,
     keep_running: CancellationToken,
     outputs_sender: Sender<outputs::Request>,
     parameters_sender: Sender<ClientRequest<ParametersRequest>>,
@@ -53,6 +55,7 @@ pub fn acceptor(
             let client_id = next_client_id.fetch_add(1, Ordering::SeqCst);
             connection(
                 stream,
+                access_keys.clone(),
                 keep_running.clone(),
                 error_sender.clone(),
                 outputs_sender.clone(),
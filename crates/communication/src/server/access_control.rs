@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use crate::messages::{
+    InjectionsRequest, InjectionsResponse, ParametersRequest, ParametersResponse, Reason, Request,
+    Role, TextualResponse,
+};
+
+/// Failed authentications after which the connection is closed
+pub const MAXIMUM_FAILED_AUTHENTICATIONS: usize = 3;
+/// Delay before a failed authentication is answered, slowing down guessing of keys
+pub const FAILED_AUTHENTICATION_DELAY: Duration = Duration::from_secs(1);
+
+/// Pre-shared keys which grant roles to clients
+///
+/// If no key is configured, clients do not need to authenticate and every client is an admin.
+/// Keys are sent in plain text, everyone on the network can read them since the connection is not
+/// encrypted with TLS.
+#[derive(Clone, Debug, Default)]
+pub struct AccessKeys {
+    pub read_only: Option<String>,
+    pub admin: Option<String>,
+}
+
+impl AccessKeys {
+    pub fn initial_role(&self) -> Option<Role> {
+        if self.read_only.is_none() && self.admin.is_none() {
+            Some(Role::Admin)
+        } else {
+            None
+        }
+    }
+
+    pub fn authenticate(&self, key: &str) -> Result<Role, Reason> {
+        // both keys are always compared to not reveal which one is configured via timing
+        let is_admin = matches_key(self.admin.as_deref(), key);
+        let is_read_only = matches_key(self.read_only.as_deref(), key);
+        if is_admin {
+            Ok(Role::Admin)
+        } else if is_read_only {
+            Ok(Role::ReadOnly)
+        } else {
+            Err("invalid access key".to_string())
+        }
+    }
+}
+
+fn matches_key(configured_key: Option<&str>, key: &str) -> bool {
+    configured_key.is_some_and(|configured_key| {
+        constant_time_equal(configured_key.as_bytes(), key.as_bytes())
+    })
+}
+
+/// Compares without returning early at the first differing byte, only the length is revealed
+fn constant_time_equal(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+    left.iter()
+        .zip(right)
+        .fold(0, |difference, (left, right)| difference | (left ^ right))
+        == 0
+}
+
+/// Read-only clients may only subscribe to outputs, parameters may contain confidential values
+pub fn required_role(request: &Request) -> Role {
+    match request {
+        Request::Authentication(_) | Request::Outputs(_) => Role::ReadOnly,
+        Request::Parameters(_) | Request::Injections(_) => Role::Admin,
+    }
+}
+
+/// Response to a request which is not permitted for the role of the client
+///
+/// Returns `None` for requests without response. Field requests are answered without fields
+/// since their response cannot carry an error.
+pub fn rejection(request: &Request, role: Role) -> Option<TextualResponse> {
+    let reason = format!(
+        "{:?} role required, client has {role:?} role",
+        required_role(request)
+    );
+    match *request {
+        Request::Parameters(ParametersRequest::GetFields { id }) => {
+            Some(TextualResponse::Parameters(ParametersResponse::GetFields {
+                id,
+                fields: Default::default(),
+            }))
+        }
+        Request::Parameters(ParametersRequest::GetCurrent { id, .. }) => Some(
+            TextualResponse::Parameters(ParametersResponse::GetCurrent {
+                id,
+                result: Err(reason),
+            }),
+        ),
+        Request::Parameters(ParametersRequest::Subscribe { id, .. }) => {
+            Some(TextualResponse::Parameters(ParametersResponse::Subscribe {
+                id,
+                result: Err(reason),
+            }))
+        }
+        Request::Parameters(ParametersRequest::Unsubscribe { id, .. }) => Some(
+            TextualResponse::Parameters(ParametersResponse::Unsubscribe {
+                id,
+                result: Err(reason),
+            }),
+        ),
+        Request::Injections(InjectionsRequest::Set { id, .. }) => {
+            Some(TextualResponse::Injections(InjectionsResponse::Set {
+                id,
+                result: Err(reason),
+            }))
+        }
+        Request::Injections(InjectionsRequest::Unset { id, .. }) => {
+            Some(TextualResponse::Injections(InjectionsResponse::Unset {
+                id,
+                result: Err(reason),
+            }))
+        }
+        Request::Parameters(ParametersRequest::Update { id, .. }) => {
+            Some(TextualResponse::Parameters(ParametersResponse::Update {
+                id,
+                result: Err(reason),
+            }))
+        }
+        Request::Parameters(ParametersRequest::LoadFromDisk { id }) => Some(
+            TextualResponse::Parameters(ParametersResponse::LoadFromDisk {
+                id,
+                result: Err(reason),
+            }),
+        ),
+        Request::Parameters(ParametersRequest::StoreToDisk { id, .. }) => Some(
+            TextualResponse::Parameters(ParametersResponse::StoreToDisk {
+                id,
+                result: Err(reason),
+            }),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parameters::directory::{Id, Location, Scope};
+    use serde_json::Value;
+
+    use crate::messages::OutputsRequest;
+
+    use super::*;
+
+    #[test]
+    fn clients_are_admins_without_keys() {
+        assert_eq!(AccessKeys::default().initial_role(), Some(Role::Admin));
+    }
+
+    #[test]
+    fn keys_grant_roles() {
+        let access_keys = AccessKeys {
+            read_only: Some("reader".to_string()),
+            admin: Some("admin".to_string()),
+        };
+        assert_eq!(access_keys.initial_role(), None);
+        assert_eq!(access_keys.authenticate("reader"), Ok(Role::ReadOnly));
+        assert_eq!(access_keys.authenticate("admin"), Ok(Role::Admin));
+        assert!(access_keys.authenticate("guess").is_err());
+    }
+
+    #[test]
+    fn keys_are_compared_completely() {
+        assert!(constant_time_equal(b"secret", b"secret"));
+        assert!(!constant_time_equal(b"secret", b"secreT"));
+        assert!(!constant_time_equal(b"secret", b"Secret"));
+        assert!(!constant_time_equal(b"secret", b"secret!"));
+        assert!(!constant_time_equal(b"", b"secret"));
+    }
+
+    #[test]
+    fn read_only_clients_may_not_modify_parameters() {
+        let subscribe = Request::Outputs(OutputsRequest::GetFields { id: 42 });
+        assert!(required_role(&subscribe) <= Role::ReadOnly);
+
+        let update = Request::Parameters(ParametersRequest::Update {
+            id: 42,
+            path: "a.b.c".to_string(),
+            data: Value::from(1337),
+        });
+        assert!(required_role(&update) > Role::ReadOnly);
+        assert!(matches!(
+            rejection(&update, Role::ReadOnly),
+            Some(TextualResponse::Parameters(ParametersResponse::Update {
+                id: 42,
+                result: Err(_)
+            }))
+        ));
+
+        let store = Request::Parameters(ParametersRequest::StoreToDisk {
+            id: 42,
+            scope: Scope {
+                location: Location::All,
+                id: Id::All,
+            },
+            path: "a.b.c".to_string(),
+        });
+        assert!(required_role(&store) > Role::ReadOnly);
+    }
+
+    #[test]
+    fn read_only_clients_may_not_read_parameters() {
+        let get_current = Request::Parameters(ParametersRequest::GetCurrent {
+            id: 42,
+            path: "a.b.c".to_string(),
+        });
+        assert!(required_role(&get_current) > Role::ReadOnly);
+        assert!(matches!(
+            rejection(&get_current, Role::ReadOnly),
+            Some(TextualResponse::Parameters(
+                ParametersResponse::GetCurrent {
+                    id: 42,
+                    result: Err(_)
+                }
+            ))
+        ));
+
+        let subscribe = Request::Parameters(ParametersRequest::Subscribe {
+            id: 42,
+            path: "a.b.c".to_string(),
+        });
+        assert!(required_role(&subscribe) > Role::ReadOnly);
+        assert!(matches!(
+            rejection(&subscribe, Role::ReadOnly),
+            Some(TextualResponse::Parameters(ParametersResponse::Subscribe {
+                id: 42,
+                result: Err(_)
+            }))
+        ));
+
+        let get_fields = Request::Parameters(ParametersRequest::GetFields { id: 42 });
+        assert!(matches!(
+            rejection(&get_fields, Role::ReadOnly),
+            Some(TextualResponse::Parameters(ParametersResponse::GetFields { id: 42, fields }))
+                if fields.is_empty()
+        ));
+    }
+}
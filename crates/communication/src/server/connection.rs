@@ -12,7 +12,10 @@ use tokio_util::sync::CancellationToken;
 
 use crate::messages::ParametersRequest;
 
-use super::{client_request::ClientRequest, outputs, receiver::receiver, sender::sender};
+use super::{
+    access_control::AccessKeys, client_request::ClientRequest, outputs, receiver::receiver,
+    sender::sender,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ConnectionError {
@@ -48,6 +51,7 @@ pub enum ReceiverOrSenderError {
 
 pub fn connection(
     stream: TcpStream,
+    access_keys: AccessKeys,
     keep_running: CancellationToken,
     connection_error_sender: UnboundedSender<ConnectionError>,
     outputs_sender: Sender<outputs::Request>,
@@ -86,6 +90,7 @@ pub fn connection(
 
         spawn(receiver(
             reader,
+            access_keys,
             receiver_or_sender_error_sender.clone(),
             keep_running,
             keep_only_self_running.clone(),
@@ -1,4 +1,5 @@
 mod acceptor;
+mod access_control;
 mod client;
 mod client_request;
 mod connection;
@@ -8,4 +9,5 @@ mod receiver;
 mod runtime;
 mod sender;
 
+pub use access_control::AccessKeys;
 pub use runtime::Runtime;
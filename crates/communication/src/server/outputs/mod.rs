@@ -1,8 +1,10 @@
 use std::collections::BTreeSet;
 
-use tokio::sync::mpsc::Sender;
+use serde_json::Value;
 
-use crate::messages::{Format, OutputsRequest, Path};
+use tokio::{sync::mpsc::Sender, time::Instant};
+
+use crate::messages::{Format, OutputsRequest, Path, SubscriptionOptions};
 
 use super::client_request::ClientRequest;
 
@@ -24,4 +26,58 @@ struct Subscription {
     pub path: Path,
     pub format: Format,
     pub once: bool,
+    pub options: SubscriptionOptions,
+    pub number_of_notified_cycles: usize,
+    pub last_sent_time: Option<Instant>,
+    pub last_sent_data: Option<SentData>,
+}
+
+impl Subscription {
+    fn new(path: Path, format: Format, once: bool, options: SubscriptionOptions) -> Self {
+        Self {
+            path,
+            format,
+            once,
+            options,
+            number_of_notified_cycles: 0,
+            last_sent_time: None,
+            last_sent_data: None,
+        }
+    }
+
+    /// Counts the notified cycle and returns whether its data may be sent w.r.t. decimation and
+    /// rate limit
+    fn is_due(&mut self, now: Instant) -> bool {
+        let cycle_index = self.number_of_notified_cycles;
+        self.number_of_notified_cycles += 1;
+        let decimation = self.options.decimation.unwrap_or(1).max(1);
+        if cycle_index % decimation != 0 {
+            return false;
+        }
+        match (self.options.minimum_interval, self.last_sent_time) {
+            (Some(minimum_interval), Some(last_sent_time)) => {
+                now.duration_since(last_sent_time) >= minimum_interval
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns whether the serialized data has to be sent and remembers it as sent if so
+    fn should_send(&mut self, data: impl FnOnce() -> SentData, now: Instant) -> bool {
+        if self.options.on_change_only {
+            let data = data();
+            if self.last_sent_data.as_ref() == Some(&data) {
+                return false;
+            }
+            self.last_sent_data = Some(data);
+        }
+        self.last_sent_time = Some(now);
+        true
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum SentData {
+    Textual(Value),
+    Binary(Vec<u8>),
 }
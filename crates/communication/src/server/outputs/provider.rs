@@ -8,6 +8,7 @@ use bincode::{DefaultOptions, Options};
 use framework::{Reader, Writer};
 use futures_util::{stream::FuturesUnordered, StreamExt};
 use log::error;
+use lz4_flex::compress_prepend_size;
 use serialize_hierarchy::SerializeHierarchy;
 use tokio::{
     select, spawn,
@@ -16,17 +17,18 @@ use tokio::{
         Notify,
     },
     task::JoinHandle,
+    time::Instant,
 };
 
 use crate::{
     messages::{
         BinaryOutputsResponse, BinaryResponse, Format, OutputsRequest, Response,
-        TextualDataOrBinaryReference, TextualOutputsResponse, TextualResponse,
+        SubscriptionOptions, TextualDataOrBinaryReference, TextualOutputsResponse, TextualResponse,
     },
     server::{client::Client, client_request::ClientRequest},
 };
 
-use super::{Request, Subscription};
+use super::{Request, SentData, Subscription};
 
 pub fn provider<Outputs>(
     outputs_sender: Sender<Request>,
@@ -96,6 +98,10 @@ where
     Outputs: SerializeHierarchy,
 {
     let is_get_next = matches!(request.request, OutputsRequest::GetNext { .. });
+    let options = match request.request {
+        OutputsRequest::Subscribe { options, .. } => options,
+        _ => SubscriptionOptions::default(),
+    };
     match request.request {
         OutputsRequest::GetFields { .. } => {
             panic!("GetFields should be answered by output router");
@@ -111,6 +117,7 @@ where
             cycler_instance: received_cycler_instance,
             path,
             format,
+            ..
         } => {
             assert_eq!(cycler_instance, received_cycler_instance);
             if Outputs::exists(&path) {
@@ -138,11 +145,7 @@ where
                         SubscriptionsState::Unchanged
                     }
                     Entry::Vacant(entry) => {
-                        entry.insert(Subscription {
-                            path,
-                            format,
-                            once: is_get_next,
-                        });
+                        entry.insert(Subscription::new(path, format, is_get_next, options));
                         if !is_get_next {
                             request
                                 .client
@@ -245,7 +248,11 @@ async fn handle_notified_output(
     let mut subscriptions_state = SubscriptionsState::Unchanged;
     {
         let output = outputs_reader.next();
+        let now = Instant::now();
         subscriptions.retain(|(client, subscription_id), subscription| {
+            if !subscription.is_due(now) {
+                return true;
+            }
             let data = match subscription.format {
                 Format::Textual => {
                     let data = match output
@@ -257,9 +264,12 @@ async fn handle_notified_output(
                             return true;
                         }
                     };
+                    if !subscription.should_send(|| SentData::Textual(data.clone()), now) {
+                        return true;
+                    }
                     TextualDataOrBinaryReference::TextualData { data }
                 }
-                Format::Binary => {
+                Format::Binary | Format::CompressedBinary => {
                     let mut data = Vec::new();
                     let options = DefaultOptions::new()
                         .with_fixint_encoding()
@@ -269,6 +279,12 @@ async fn handle_notified_output(
                         error!("failed to serialize {:?}: {error:?}", subscription.path);
                         return true;
                     }
+                    if !subscription.should_send(|| SentData::Binary(data.clone()), now) {
+                        return true;
+                    }
+                    if subscription.format == Format::CompressedBinary {
+                        data = compress_prepend_size(&data);
+                    }
                     let reference_id = next_binary_reference_id.0;
                     *next_binary_reference_id += 1;
                     if subscription.once {
@@ -359,7 +375,7 @@ mod tests {
     use serialize_hierarchy::Error;
     use tokio::{sync::mpsc::error::TryRecvError, task::yield_now, time::timeout};
 
-    use crate::messages::Format;
+    use crate::messages::{Format, SubscriptionOptions};
 
     use super::*;
 
@@ -536,6 +552,7 @@ mod tests {
                     cycler_instance: cycler_instance.clone(),
                     path: path.clone(),
                     format,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -573,6 +590,7 @@ mod tests {
                     cycler_instance,
                     path: path.clone(),
                     format,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -637,6 +655,7 @@ mod tests {
                     cycler_instance: cycler_instance.clone(),
                     path: path.clone(),
                     format,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: 1337,
@@ -674,6 +693,7 @@ mod tests {
                     cycler_instance,
                     path: path.clone(),
                     format,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: 7331,
@@ -738,6 +758,7 @@ mod tests {
                     cycler_instance: cycler_instance.clone(),
                     path: path.clone(),
                     format,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -775,6 +796,7 @@ mod tests {
                     cycler_instance,
                     path: path.clone(),
                     format,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -892,6 +914,7 @@ mod tests {
                     cycler_instance: cycler_instance.to_string(),
                     path: path.clone(),
                     format: Format::Textual,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -1016,6 +1039,7 @@ mod tests {
                     cycler_instance: cycler_instance.to_string(),
                     path: path.clone(),
                     format: Format::Textual,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: 1337,
@@ -1132,6 +1156,7 @@ mod tests {
                     cycler_instance: cycler_instance.to_string(),
                     path: path.clone(),
                     format: Format::Textual,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -1259,6 +1284,7 @@ mod tests {
                     cycler_instance: cycler_instance.to_string(),
                     path: path.clone(),
                     format: Format::Binary,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -1364,6 +1390,191 @@ mod tests {
         provider_task.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn decimated_on_change_subscription_skips_cycles_and_unchanged_data() {
+        let cycler_instance = "CyclerInstance";
+        let path = "a.b.c".to_string();
+        let outputs_changed = Arc::new(Notify::new());
+        let outputs = || OutputsFake {
+            existing_fields: [(path.clone(), Value::from(42))].into(),
+        };
+        let (output_writer, outputs_reader) =
+            multiple_buffer_with_slots([outputs(), outputs(), outputs()]);
+
+        let (provider_task, _fields, request_sender, _subscribed_outputs_reader) =
+            get_registered_request_sender_from_provider(
+                cycler_instance,
+                outputs_changed.clone(),
+                outputs_reader,
+            )
+            .await;
+
+        const SUBSCRIPTION_ID: usize = 42;
+        let (response_sender, mut response_receiver) = channel(1);
+        request_sender
+            .send(ClientRequest {
+                request: OutputsRequest::Subscribe {
+                    id: SUBSCRIPTION_ID,
+                    cycler_instance: cycler_instance.to_string(),
+                    path: path.clone(),
+                    format: Format::Textual,
+                    options: SubscriptionOptions::default()
+                        .with_decimation(2)
+                        .with_on_change_only(),
+                },
+                client: Client {
+                    id: 1337,
+                    response_sender,
+                },
+            })
+            .await
+            .unwrap();
+        let response = response_receiver.recv().await.unwrap();
+        assert!(
+            matches!(
+                response,
+                Response::Textual(TextualResponse::Outputs(
+                    TextualOutputsResponse::Subscribe {
+                        id: SUBSCRIPTION_ID,
+                        result: Ok(()),
+                    }
+                ))
+            ),
+            "unexpected {response:?}",
+        );
+
+        let expected_data = |value: Value| {
+            Response::Textual(TextualResponse::Outputs(
+                TextualOutputsResponse::SubscribedData {
+                    items: [(
+                        SUBSCRIPTION_ID,
+                        TextualDataOrBinaryReference::TextualData { data: value },
+                    )]
+                    .into(),
+                },
+            ))
+        };
+
+        outputs_changed.notify_one();
+        assert_eq!(
+            response_receiver.recv().await.unwrap(),
+            expected_data(Value::from(42)),
+        );
+
+        // second cycle is decimated, third cycle is unchanged
+        for _ in 0..2 {
+            outputs_changed.notify_one();
+            yield_now().await;
+            match response_receiver.try_recv() {
+                Err(TryRecvError::Empty) => {}
+                response => panic!("unexpected result from try_recv(): {response:?}"),
+            }
+        }
+
+        output_writer
+            .next()
+            .existing_fields
+            .insert(path.clone(), Value::from(1337));
+
+        // fourth cycle is decimated, fifth cycle has changed
+        outputs_changed.notify_one();
+        yield_now().await;
+        match response_receiver.try_recv() {
+            Err(TryRecvError::Empty) => {}
+            response => panic!("unexpected result from try_recv(): {response:?}"),
+        }
+        outputs_changed.notify_one();
+        assert_eq!(
+            response_receiver.recv().await.unwrap(),
+            expected_data(Value::from(1337)),
+        );
+
+        drop(request_sender);
+        provider_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn compressed_binary_data_from_notified_output_is_decompressible() {
+        let cycler_instance = "CyclerInstance";
+        let path = "a.b.c".to_string();
+        let value = vec![42; 1000];
+        let serialized_value = serialize(&value).unwrap();
+        let outputs_changed = Arc::new(Notify::new());
+        let (_output_writer, outputs_reader) = multiple_buffer_with_slots([OutputsFake {
+            existing_fields: [(path.clone(), value.clone())].into(),
+        }]);
+
+        let (provider_task, _fields, request_sender, _subscribed_outputs_reader) =
+            get_registered_request_sender_from_provider(
+                cycler_instance,
+                outputs_changed.clone(),
+                outputs_reader,
+            )
+            .await;
+
+        const SUBSCRIPTION_ID: usize = 42;
+        let (response_sender, mut response_receiver) = channel(1);
+        request_sender
+            .send(ClientRequest {
+                request: OutputsRequest::Subscribe {
+                    id: SUBSCRIPTION_ID,
+                    cycler_instance: cycler_instance.to_string(),
+                    path,
+                    format: Format::CompressedBinary,
+                    options: Default::default(),
+                },
+                client: Client {
+                    id: 1337,
+                    response_sender,
+                },
+            })
+            .await
+            .unwrap();
+        let response = response_receiver.recv().await.unwrap();
+        assert!(
+            matches!(
+                response,
+                Response::Textual(TextualResponse::Outputs(
+                    TextualOutputsResponse::Subscribe {
+                        id: SUBSCRIPTION_ID,
+                        result: Ok(()),
+                    }
+                ))
+            ),
+            "unexpected {response:?}",
+        );
+
+        outputs_changed.notify_one();
+        let subscribed_data = response_receiver.recv().await.unwrap();
+        let Response::Textual(TextualResponse::Outputs(TextualOutputsResponse::SubscribedData {
+            items,
+        })) = subscribed_data
+        else {
+            panic!("unexpected subscribed data: {subscribed_data:?}");
+        };
+        let Some(TextualDataOrBinaryReference::BinaryReference { reference_id }) =
+            items.get(&SUBSCRIPTION_ID)
+        else {
+            panic!("an item with subscription ID {SUBSCRIPTION_ID} should exist");
+        };
+        let binary_data = response_receiver.recv().await.unwrap();
+        let Response::Binary(BinaryResponse::Outputs(BinaryOutputsResponse::SubscribedData {
+            referenced_items,
+        })) = binary_data
+        else {
+            panic!("unexpected binary data: {binary_data:?}");
+        };
+        let compressed_data = &referenced_items[reference_id];
+        assert!(compressed_data.len() < serialized_value.len());
+        assert_eq!(
+            lz4_flex::decompress_size_prepended(compressed_data).unwrap(),
+            serialized_value,
+        );
+
+        drop(request_sender);
+        provider_task.await.unwrap();
+    }
+
     #[tokio::test]
     async fn multiple_subscriptions_duplicate_data() {
         let cycler_instance = "CyclerInstance";
@@ -1394,6 +1605,7 @@ mod tests {
                     cycler_instance: cycler_instance.to_string(),
                     path: path.clone(),
                     format: Format::Textual,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -1432,6 +1644,7 @@ mod tests {
                     cycler_instance: cycler_instance.to_string(),
                     path: path.clone(),
                     format: Format::Textual,
+                    options: Default::default(),
                 },
                 client: Client {
                     id: client_id,
@@ -343,6 +343,7 @@ mod tests {
                 cycler_instance: "CyclerInstance".to_string(),
                 path: "a.b.c".to_string(),
                 format: Format::Textual,
+                options: Default::default(),
             },
             client: client.clone(),
         };
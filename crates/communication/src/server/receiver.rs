@@ -1,6 +1,6 @@
 use futures_util::{stream::SplitStream, StreamExt};
 use serde_json::from_str;
-use tokio::{net::TcpStream, select, sync::mpsc::Sender};
+use tokio::{net::TcpStream, select, sync::mpsc::Sender, time::sleep};
 use tokio_tungstenite::{
     tungstenite::{protocol::frame::coding::CloseCode, Message},
     WebSocketStream,
@@ -8,15 +8,27 @@ use tokio_tungstenite::{
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    messages::{OutputsRequest, ParametersRequest, Request, Response},
+    messages::{
+        AuthenticationRequest, AuthenticationResponse, OutputsRequest, ParametersRequest, Request,
+        Response, Role, TextualResponse,
+    },
     server::client_request::ClientRequest,
 };
 
-use super::{client::Client, connection::ReceiverOrSenderError, outputs};
+use super::{
+    access_control::{
+        rejection, required_role, AccessKeys, FAILED_AUTHENTICATION_DELAY,
+        MAXIMUM_FAILED_AUTHENTICATIONS,
+    },
+    client::Client,
+    connection::ReceiverOrSenderError,
+    outputs,
+};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn receiver(
     mut reader: SplitStream<WebSocketStream<TcpStream>>,
+    access_keys: AccessKeys,
     error_sender: Sender<ReceiverOrSenderError>,
     keep_running: CancellationToken,
     keep_only_self_running: CancellationToken,
@@ -25,11 +37,16 @@ pub async fn receiver(
     outputs_sender: Sender<outputs::Request>,
     parameters_sender: Sender<ClientRequest<ParametersRequest>>,
 ) {
+    let mut role = access_keys.initial_role();
+    let mut failed_authentications = 0;
     select! {
         _ = async {
             while let Some(message) = reader.next().await {
                 handle_message(
                     message,
+                    &access_keys,
+                    &mut role,
+                    &mut failed_authentications,
                     &error_sender,
                     &keep_only_self_running,
                     client_id,
@@ -65,8 +82,12 @@ pub async fn receiver(
         .expect("receiver should always wait for all senders");
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_message(
     message: Result<Message, tokio_tungstenite::tungstenite::Error>,
+    access_keys: &AccessKeys,
+    role: &mut Option<Role>,
+    failed_authentications: &mut usize,
     error_sender: &Sender<ReceiverOrSenderError>,
     keep_only_self_running: &CancellationToken,
     client_id: usize,
@@ -104,11 +125,60 @@ async fn handle_message(
                 }
             };
 
+            if let Request::Authentication(AuthenticationRequest::Authenticate { key }) = &request {
+                let result = access_keys.authenticate(key);
+                match result {
+                    Ok(new_role) => *role = Some(new_role),
+                    Err(_) => {
+                        *failed_authentications += 1;
+                        sleep(FAILED_AUTHENTICATION_DELAY).await;
+                    }
+                }
+                response_sender
+                    .send(Response::Textual(TextualResponse::Authentication(
+                        AuthenticationResponse::Authenticate { result },
+                    )))
+                    .await
+                    .expect("receiver should always wait for all senders");
+                if *failed_authentications >= MAXIMUM_FAILED_AUTHENTICATIONS {
+                    response_sender
+                        .send(Response::Close {
+                            code: CloseCode::Policy,
+                            reason: "too many failed authentications".to_string(),
+                        })
+                        .await
+                        .expect("receiver should always wait for all senders");
+                    keep_only_self_running.cancel();
+                }
+                return;
+            }
+            let Some(role) = *role else {
+                response_sender
+                    .send(Response::Close {
+                        code: CloseCode::Policy,
+                        reason: "not authenticated, send an access key first".to_string(),
+                    })
+                    .await
+                    .expect("receiver should always wait for all senders");
+                keep_only_self_running.cancel();
+                return;
+            };
+            if required_role(&request) > role {
+                if let Some(response) = rejection(&request, role) {
+                    response_sender
+                        .send(Response::Textual(response))
+                        .await
+                        .expect("receiver should always wait for all senders");
+                }
+                return;
+            }
+
             let client = Client {
                 id: client_id,
                 response_sender: response_sender.clone(),
             };
             match request {
+                Request::Authentication(_) => {}
                 Request::Outputs(request) => {
                     outputs_sender
                         .send(outputs::Request::ClientRequest(ClientRequest {
@@ -26,6 +26,7 @@ use crate::server::outputs::router::router;
 
 use super::{
     acceptor::{acceptor, AcceptError},
+    access_control::AccessKeys,
     outputs::{provider::provider, Request},
     parameters::{storage::storage, subscriptions::subscriptions},
 };
@@ -58,6 +59,7 @@ where
 {
     pub fn start(
         addresses: Option<impl ToSocketAddrs + Send + Sync + 'static>,
+        access_keys: AccessKeys,
         parameters_directory: impl AsRef<Path> + Send + Sync + 'static,
         body_id: String,
         head_id: String,
@@ -117,6 +119,7 @@ where
                     let acceptor_task = addresses.map(|addresses| {
                         acceptor(
                             addresses,
+                            access_keys,
                             keep_running.clone(),
                             outputs_sender,
                             parameters_sender,
@@ -1,7 +1,7 @@
 use std::time::SystemTime;
 
 use color_eyre::Result;
-use nalgebra::{matrix, Matrix2, Matrix2x4, Matrix4, Matrix4x2};
+use nalgebra::{matrix, Matrix2, Matrix2x4, Matrix4, Vector2};
 use serde::{Deserialize, Serialize};
 
 use context_attribute::context;
@@ -14,7 +14,7 @@ use projection::{camera_matrices::CameraMatrices, camera_matrix::CameraMatrix, P
 use types::{
     ball::Ball,
     ball_filter::Hypothesis,
-    ball_position::BallPosition,
+    ball_position::{is_valid_deceleration, BallPosition},
     cycle_time::CycleTime,
     field_dimensions::FieldDimensions,
     limb::{is_above_limbs, Limb, ProjectedLimbs},
@@ -36,6 +36,7 @@ pub struct CycleContext {
     best_ball_hypothesis: AdditionalOutput<Option<Hypothesis>, "best_ball_hypothesis">,
     best_ball_state: AdditionalOutput<Option<MultivariateNormalDistribution<4>>, "best_ball_state">,
     chooses_resting_model: AdditionalOutput<bool, "chooses_resting_model">,
+    predicted_ball_trajectory: AdditionalOutput<Vec<Point2<Ground>>, "predicted_ball_trajectory">,
     filtered_balls_in_image_bottom:
         AdditionalOutput<Vec<Circle<Pixel>>, "filtered_balls_in_image_bottom">,
     filtered_balls_in_image_top:
@@ -100,7 +101,9 @@ impl BallFilter {
                 .get(detection_time)
                 .expect("current_odometry_to_last_odometry should not be None");
             self.predict_hypotheses_with_odometry(
-                context.ball_filter_configuration.velocity_decay_factor,
+                context
+                    .ball_filter_configuration
+                    .rolling_friction_deceleration,
                 current_odometry_to_last_odometry.inverse(),
                 Matrix4::from_diagonal(&context.ball_filter_configuration.process_noise),
             );
@@ -177,6 +180,20 @@ impl BallFilter {
                 .fill_if_subscribed(|| hypothesis.is_resting(context.ball_filter_configuration));
             hypothesis.selected_ball_position(context.ball_filter_configuration)
         });
+        context.predicted_ball_trajectory.fill_if_subscribed(|| {
+            ball_position
+                .map(|ball_position| {
+                    ball_position.predicted_trajectory(
+                        context
+                            .ball_filter_configuration
+                            .rolling_friction_deceleration,
+                        context
+                            .ball_filter_configuration
+                            .predicted_trajectory_time_step,
+                    )
+                })
+                .unwrap_or_default()
+        });
 
         Ok(MainOutputs {
             ball_position: ball_position.into(),
@@ -221,7 +238,7 @@ impl BallFilter {
 
     fn predict_hypotheses_with_odometry(
         &mut self,
-        velocity_decay_factor: f32,
+        rolling_friction_deceleration: f32,
         last_odometry_to_current_odometry: nalgebra::Isometry2<f32>,
         process_noise: Matrix4<f32>,
     ) {
@@ -230,8 +247,8 @@ impl BallFilter {
             let constant_velocity_prediction = matrix![
                 1.0, 0.0, cycle_time, 0.0;
                 0.0, 1.0, 0.0, cycle_time;
-                0.0, 0.0, velocity_decay_factor, 0.0;
-                0.0, 0.0, 0.0, velocity_decay_factor;
+                0.0, 0.0, 1.0, 0.0;
+                0.0, 0.0, 0.0, 1.0;
             ];
             let rotation = last_odometry_to_current_odometry
                 .rotation
@@ -243,20 +260,33 @@ impl BallFilter {
                 0.0, 0.0, rotation[(1, 0)], rotation[(1, 1)];
             ];
             let state_prediction = constant_velocity_prediction * state_rotation;
-            let control_input_model = Matrix4x2::identity();
+            // control is the odometry translation and the velocity change due to rolling friction
+            let control_input_model = matrix![
+                1.0, 0.0, 0.5 * cycle_time, 0.0;
+                0.0, 1.0, 0.0, 0.5 * cycle_time;
+                0.0, 0.0, 1.0, 0.0;
+                0.0, 0.0, 0.0, 1.0;
+            ];
             let odometry_translation = last_odometry_to_current_odometry.translation.vector;
-            hypothesis.moving_state.predict(
-                state_prediction,
-                control_input_model,
-                odometry_translation,
-                process_noise,
-            );
-            hypothesis.resting_state.predict(
-                state_prediction,
-                control_input_model,
-                odometry_translation,
-                process_noise,
-            );
+            for state in [&mut hypothesis.moving_state, &mut hypothesis.resting_state] {
+                let velocity = rotation * Vector2::new(state.mean.z, state.mean.w);
+                let velocity_change = rolling_friction_velocity_change(
+                    velocity,
+                    rolling_friction_deceleration,
+                    cycle_time,
+                );
+                state.predict(
+                    state_prediction,
+                    control_input_model,
+                    nalgebra::vector![
+                        odometry_translation.x,
+                        odometry_translation.y,
+                        velocity_change.x,
+                        velocity_change.y
+                    ],
+                    process_noise,
+                );
+            }
         }
     }
 
@@ -400,6 +430,20 @@ impl BallFilter {
     }
 }
 
+/// Velocity change within `cycle_time` due to constant deceleration, the ball does not roll back
+fn rolling_friction_velocity_change(
+    velocity: Vector2<f32>,
+    deceleration: f32,
+    cycle_time: f32,
+) -> Vector2<f32> {
+    let speed = velocity.norm();
+    // a negative deceleration would accelerate the ball
+    if speed == 0.0 || !is_valid_deceleration(deceleration) {
+        return Vector2::zeros();
+    }
+    -velocity * ((deceleration * cycle_time).min(speed) / speed)
+}
+
 fn project_to_image(
     ball_position: &[BallPosition<Ground>],
     camera_matrix: &CameraMatrix,
@@ -439,3 +483,22 @@ fn is_visible_to_camera(
         && (0.0..480.0).contains(&position_in_image.y())
         && is_above_limbs(position_in_image, projected_limbs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_deceleration_does_not_accelerate_the_ball() {
+        let velocity = Vector2::new(1.0, 0.0);
+
+        assert_eq!(
+            rolling_friction_velocity_change(velocity, -1.0, 0.1),
+            Vector2::zeros()
+        );
+        assert_eq!(
+            rolling_friction_velocity_change(velocity, f32::NAN, 0.1),
+            Vector2::zeros()
+        );
+    }
+}
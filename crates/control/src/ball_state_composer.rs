@@ -1,4 +1,4 @@
-use std::time::SystemTime;
+use std::time::Duration;
 
 use color_eyre::Result;
 use context_attribute::context;
@@ -9,10 +9,15 @@ use linear_algebra::{point, Isometry2, Point2, Vector2};
 use serde::{Deserialize, Serialize};
 use spl_network_messages::{SubState, Team};
 use types::{
-    ball_position::BallPosition, cycle_time::CycleTime, field_dimensions::FieldDimensions,
+    ball_position::{is_valid_deceleration, BallPosition},
+    cycle_time::CycleTime,
+    field_dimensions::FieldDimensions,
     filtered_game_controller_state::FilteredGameControllerState,
-    penalty_shot_direction::PenaltyShotDirection, primary_state::PrimaryState, support_foot::Side,
-    world_state::BallState,
+    penalty_shot_direction::PenaltyShotDirection,
+    primary_state::PrimaryState,
+    support_foot::Side,
+    team_ball::TeamBall,
+    world_state::{BallState, GoalLineCrossing},
 };
 
 #[derive(Deserialize, Serialize)]
@@ -29,11 +34,12 @@ pub struct CycleContext {
     ball_position: Input<Option<BallPosition<Ground>>, "ball_position?">,
     penalty_shot_direction: Input<Option<PenaltyShotDirection>, "penalty_shot_direction?">,
     ground_to_field: Input<Option<Isometry2<Ground, Field>>, "ground_to_field?">,
-    team_ball: Input<Option<BallPosition<Field>>, "team_ball?">,
+    team_ball_hypothesis: Input<Option<TeamBall>, "team_ball_hypothesis?">,
     primary_state: Input<PrimaryState, "primary_state">,
     filtered_game_controller_state:
         Input<Option<FilteredGameControllerState>, "filtered_game_controller_state?">,
     field_dimensions: Parameter<FieldDimensions, "field_dimensions">,
+    rolling_friction_deceleration: Parameter<f32, "ball_filter.rolling_friction_deceleration">,
 }
 
 #[context]
@@ -51,24 +57,30 @@ impl BallStateComposer {
     }
 
     pub fn cycle(&mut self, context: CycleContext) -> Result<MainOutputs> {
+        let prediction = BallPrediction {
+            own_goal_line_x: -context.field_dimensions.length / 2.0,
+            deceleration: *context.rolling_friction_deceleration,
+        };
         let ball = match (
             context.ball_position,
-            context.team_ball,
+            context.team_ball_hypothesis,
             context.ground_to_field,
         ) {
             (Some(ball_position), _, Some(ground_to_field)) => Some(create_ball_state(
-                ball_position.position,
-                ground_to_field * ball_position.position,
-                ball_position.velocity,
-                ball_position.last_seen,
+                *ball_position,
+                *ground_to_field,
+                prediction,
                 &mut self.last_ball_field_side,
                 context.penalty_shot_direction.copied(),
             )),
             (None, Some(team_ball), Some(ground_to_field)) => Some(create_ball_state(
-                ground_to_field.inverse() * team_ball.position,
-                team_ball.position,
-                ground_to_field.inverse() * team_ball.velocity,
-                team_ball.last_seen,
+                BallPosition {
+                    position: ground_to_field.inverse() * team_ball.position,
+                    velocity: ground_to_field.inverse() * team_ball.velocity,
+                    last_seen: team_ball.last_seen,
+                },
+                *ground_to_field,
+                prediction,
                 &mut self.last_ball_field_side,
                 context.penalty_shot_direction.copied(),
             )),
@@ -99,19 +111,25 @@ impl BallStateComposer {
                     - context.field_dimensions.penalty_marker_distance;
                 let penalty_spot_location = point![side_factor * penalty_spot_x, 0.0];
                 Some(create_ball_state(
-                    ground_to_field.inverse() * penalty_spot_location,
-                    penalty_spot_location,
-                    Vector2::zeros(),
-                    context.cycle_time.start_time,
+                    BallPosition {
+                        position: ground_to_field.inverse() * penalty_spot_location,
+                        velocity: Vector2::zeros(),
+                        last_seen: context.cycle_time.start_time,
+                    },
+                    *ground_to_field,
+                    prediction,
                     &mut self.last_ball_field_side,
                     context.penalty_shot_direction.copied(),
                 ))
             }
             (PrimaryState::Ready, Some(ground_to_field), ..) => Some(create_ball_state(
-                ground_to_field.inverse() * Point2::origin(),
-                Point2::origin(),
-                Vector2::zeros(),
-                context.cycle_time.start_time,
+                BallPosition {
+                    position: ground_to_field.inverse() * Point2::origin(),
+                    velocity: Vector2::zeros(),
+                    last_seen: context.cycle_time.start_time,
+                },
+                *ground_to_field,
+                prediction,
                 &mut self.last_ball_field_side,
                 context.penalty_shot_direction.copied(),
             )),
@@ -125,17 +143,28 @@ impl BallStateComposer {
     }
 }
 
+/// Parameters to predict where the ball rolls with constant deceleration
+#[derive(Clone, Copy)]
+struct BallPrediction {
+    own_goal_line_x: f32,
+    deceleration: f32,
+}
+
 fn create_ball_state(
-    ball_in_ground: Point2<Ground>,
-    ball_in_field: Point2<Field>,
-    ball_in_ground_velocity: Vector2<Ground>,
-    last_seen_ball: SystemTime,
+    ball: BallPosition<Ground>,
+    ground_to_field: Isometry2<Ground, Field>,
+    prediction: BallPrediction,
     last_ball_field_side: &mut Side,
     penalty_shot_direction: Option<PenaltyShotDirection>,
 ) -> BallState {
+    let ball_in_field = BallPosition {
+        position: ground_to_field * ball.position,
+        velocity: ground_to_field * ball.velocity,
+        last_seen: ball.last_seen,
+    };
     let was_in_left_half = *last_ball_field_side == Side::Left;
     let is_in_left_half =
-        greater_than_with_hysteresis(was_in_left_half, ball_in_field.y(), 0.0, 0.1);
+        greater_than_with_hysteresis(was_in_left_half, ball_in_field.position.y(), 0.0, 0.1);
     let side = if is_in_left_half {
         Side::Left
     } else {
@@ -144,11 +173,120 @@ fn create_ball_state(
     *last_ball_field_side = side;
     let field_side = side;
     BallState {
-        ball_in_ground,
-        ball_in_field,
-        ball_in_ground_velocity,
-        last_seen_ball,
+        ball_in_ground: ball.position,
+        ball_in_field: ball_in_field.position,
+        ball_in_ground_velocity: ball.velocity,
+        ball_in_ground_rest_position: ball.rest_position(prediction.deceleration),
+        own_goal_line_crossing: own_goal_line_crossing(
+            &ball_in_field,
+            prediction.own_goal_line_x,
+            prediction.deceleration,
+        ),
+        last_seen_ball: ball.last_seen,
         field_side,
         penalty_shot_direction,
     }
 }
+
+/// Where and when the ball rolling with constant deceleration crosses the goal line at `goal_line_x`
+fn own_goal_line_crossing(
+    ball: &BallPosition<Field>,
+    goal_line_x: f32,
+    deceleration: f32,
+) -> Option<GoalLineCrossing> {
+    let speed = ball.velocity.norm();
+    let distance_to_goal_line = ball.position.x() - goal_line_x;
+    if ball.velocity.x() >= 0.0
+        || distance_to_goal_line <= 0.0
+        || !is_valid_deceleration(deceleration)
+    {
+        return None;
+    }
+    let travelled_distance = distance_to_goal_line * speed / -ball.velocity.x();
+    let remaining_squared_speed = speed.powi(2) - 2.0 * deceleration * travelled_distance;
+    if remaining_squared_speed < 0.0 {
+        return None;
+    }
+    let time_to_crossing = (speed - remaining_squared_speed.sqrt()) / deceleration;
+    Some(GoalLineCrossing {
+        position: ball.position + ball.velocity * (travelled_distance / speed),
+        time_to_crossing: Duration::try_from_secs_f32(time_to_crossing).ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f32::consts::PI, time::SystemTime};
+
+    use linear_algebra::vector;
+
+    use super::*;
+
+    fn ball(position: Point2<Field>, velocity: Vector2<Field>) -> BallPosition<Field> {
+        BallPosition {
+            position,
+            velocity,
+            last_seen: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn fast_ball_crosses_goal_line() {
+        let crossing =
+            own_goal_line_crossing(&ball(point![-3.0, 0.0], vector![-2.0, 1.0]), -4.5, 0.5)
+                .unwrap();
+
+        assert!((crossing.position.x() + 4.5).abs() < 1e-5);
+        assert!((crossing.position.y() - 0.75).abs() < 1e-5);
+        let travelled_distance = 1.5_f32.hypot(0.75);
+        let speed = 5.0_f32.sqrt();
+        let time = crossing.time_to_crossing.as_secs_f32();
+        assert!((speed * time - 0.25 * time.powi(2) - travelled_distance).abs() < 1e-4);
+    }
+
+    #[test]
+    fn slow_ball_comes_to_rest_before_goal_line() {
+        let slow_ball = ball(point![-3.0, 0.0], vector![-1.0, 0.0]);
+
+        assert!(own_goal_line_crossing(&slow_ball, -4.5, 0.5).is_none());
+        assert!((slow_ball.rest_position(0.5).x() + 4.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn team_ball_state_predicts_rest_position_and_goal_line_crossing() {
+        let ground_to_field = Isometry2::from_parts(vector![-2.0, 0.0], PI);
+        let team_ball = ball(point![-3.0, 0.0], vector![-2.0, 1.0]);
+        let prediction = BallPrediction {
+            own_goal_line_x: -4.5,
+            deceleration: 0.5,
+        };
+
+        let ball_state = create_ball_state(
+            BallPosition {
+                position: ground_to_field.inverse() * team_ball.position,
+                velocity: ground_to_field.inverse() * team_ball.velocity,
+                last_seen: team_ball.last_seen,
+            },
+            ground_to_field,
+            prediction,
+            &mut Side::Left,
+            None,
+        );
+
+        let rest_position_in_field = ground_to_field * ball_state.ball_in_ground_rest_position;
+        let expected_rest_position = team_ball.rest_position(0.5);
+        assert!((rest_position_in_field.x() - expected_rest_position.x()).abs() < 1e-4);
+        assert!((rest_position_in_field.y() - expected_rest_position.y()).abs() < 1e-4);
+        let crossing = ball_state.own_goal_line_crossing.unwrap();
+        assert!((crossing.position.x() + 4.5).abs() < 1e-4);
+        assert!((crossing.position.y() - 0.75).abs() < 1e-4);
+    }
+
+    #[test]
+    fn no_goal_line_crossing_is_predicted_without_valid_deceleration() {
+        let fast_ball = ball(point![-3.0, 0.0], vector![-2.0, 1.0]);
+
+        assert!(own_goal_line_crossing(&fast_ball, -4.5, 0.0).is_none());
+        assert!(own_goal_line_crossing(&fast_ball, -4.5, -0.5).is_none());
+    }
+}
@@ -1,5 +1,4 @@
 use coordinate_systems::{Field, Ground};
-use geometry::line::Line;
 use geometry::line_segment::LineSegment;
 use linear_algebra::{Isometry2, Orientation2, Point};
 use spl_network_messages::{GamePhase, SubState};
@@ -62,11 +61,9 @@ pub fn execute(
                 return None;
             }
 
-            let ball_line = Line(
-                ball.ball_in_ground,
-                ball.ball_in_ground + ball.ball_in_ground_velocity,
-            );
-            let interception_point = ball_line.project_point(Point::origin());
+            let ball_trajectory =
+                LineSegment(ball.ball_in_ground, ball.ball_in_ground_rest_position);
+            let interception_point = ball_trajectory.closest_point(Point::origin());
 
             if interception_point.coords().norm() > parameters.maximum_intercept_distance {
                 return None;
@@ -113,3 +110,69 @@ fn ball_is_interception_candidate(
         && ball_is_moving_towards_robot
         && ball_is_moving_towards_own_half
 }
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::{distance, point, vector, Point2};
+
+    use super::*;
+
+    fn world_state(ball_in_ground_rest_position: Point2<Ground>) -> WorldState {
+        let ground_to_field = Isometry2::from_parts(vector![-3.0, 0.0], 0.0);
+        let mut world_state = WorldState::default();
+        world_state.robot.ground_to_field = Some(ground_to_field);
+        world_state.ball = Some(BallState {
+            ball_in_ground: point![2.0, 0.3],
+            ball_in_ground_velocity: vector![-1.5, 0.0],
+            ball_in_ground_rest_position,
+            ..BallState::new_at_center(ground_to_field)
+        });
+        world_state
+    }
+
+    fn parameters() -> InterceptBallParameters {
+        InterceptBallParameters {
+            maximum_ball_distance: 3.0,
+            minimum_ball_velocity: 0.4,
+            minimum_ball_velocity_towards_robot: 0.2,
+            minimum_ball_velocity_towards_own_half: 0.05,
+            maximum_intercept_distance: 0.5,
+        }
+    }
+
+    fn maximum_step_size() -> Step {
+        Step {
+            forward: 0.05,
+            left: 0.05,
+            turn: 0.3,
+        }
+    }
+
+    #[test]
+    fn intercepts_ball_rolling_past_the_robot() {
+        let motion_command = execute(
+            &world_state(point![-0.25, 0.3]),
+            parameters(),
+            maximum_step_size(),
+        );
+
+        let Some(MotionCommand::Walk { path, .. }) = motion_command else {
+            panic!("expected walk, got {motion_command:?}");
+        };
+        let [PathSegment::LineSegment(LineSegment(_, interception_point))] = path.as_slice() else {
+            panic!("expected single line segment, got {path:?}");
+        };
+        assert!(distance(*interception_point, point![0.0, 0.3]) < 1e-5);
+    }
+
+    #[test]
+    fn does_not_intercept_ball_coming_to_rest_before_the_robot() {
+        let motion_command = execute(
+            &world_state(point![1.0, 0.3]),
+            parameters(),
+            maximum_step_size(),
+        );
+
+        assert!(motion_command.is_none());
+    }
+}
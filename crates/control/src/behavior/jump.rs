@@ -5,15 +5,75 @@ use types::{
 };
 
 pub fn execute(world_state: &WorldState) -> Option<MotionCommand> {
-    world_state
-        .ball
-        .and_then(|ball| match ball.penalty_shot_direction {
-            Some(PenaltyShotDirection::Left) => Some(MotionCommand::Jump {
-                direction: JumpDirection::Left,
-            }),
-            Some(PenaltyShotDirection::Right) => Some(MotionCommand::Jump {
-                direction: JumpDirection::Right,
-            }),
-            Some(PenaltyShotDirection::NotMoving) | None => None,
-        })
+    let ball = world_state.ball?;
+    let shot_direction = match ball.penalty_shot_direction {
+        Some(PenaltyShotDirection::Left) => JumpDirection::Left,
+        Some(PenaltyShotDirection::Right) => JumpDirection::Right,
+        Some(PenaltyShotDirection::NotMoving) | None => return None,
+    };
+    // the predicted goal line crossing is more precise than the side the ball was shot to
+    let predicted_direction = ball
+        .own_goal_line_crossing
+        .zip(world_state.robot.ground_to_field)
+        .map(|(crossing, ground_to_field)| {
+            if (ground_to_field.inverse() * crossing.position).y() >= 0.0 {
+                JumpDirection::Left
+            } else {
+                JumpDirection::Right
+            }
+        });
+    Some(MotionCommand::Jump {
+        direction: predicted_direction.unwrap_or(shot_direction),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use linear_algebra::{point, vector, Isometry2};
+    use types::world_state::{BallState, GoalLineCrossing};
+
+    use super::*;
+
+    fn world_state(own_goal_line_crossing: Option<GoalLineCrossing>) -> WorldState {
+        let ground_to_field = Isometry2::from_parts(vector![-4.0, 0.0], 0.0);
+        let mut world_state = WorldState::default();
+        world_state.robot.ground_to_field = Some(ground_to_field);
+        world_state.ball = Some(BallState {
+            penalty_shot_direction: Some(PenaltyShotDirection::Left),
+            own_goal_line_crossing,
+            ..BallState::new_at_center(ground_to_field)
+        });
+        world_state
+    }
+
+    #[test]
+    fn jumps_toward_predicted_goal_line_crossing() {
+        let crossing = GoalLineCrossing {
+            position: point![-4.5, -0.5],
+            time_to_crossing: Duration::from_millis(500),
+        };
+
+        let motion_command = execute(&world_state(Some(crossing)));
+
+        assert!(matches!(
+            motion_command,
+            Some(MotionCommand::Jump {
+                direction: JumpDirection::Right
+            })
+        ));
+    }
+
+    #[test]
+    fn jumps_toward_shot_direction_without_goal_line_crossing() {
+        let motion_command = execute(&world_state(None));
+
+        assert!(matches!(
+            motion_command,
+            Some(MotionCommand::Jump {
+                direction: JumpDirection::Left
+            })
+        ));
+    }
 }
@@ -54,6 +54,7 @@ pub struct MainOutputs {
     pub penalty_shot_direction: MainOutput<Option<PenaltyShotDirection>>,
     pub primary_state: MainOutput<PrimaryState>,
     pub ground_to_field: MainOutput<Option<Isometry2<Ground, Field>>>,
+    pub ground_to_field_standard_deviation: MainOutput<Option<f32>>,
     pub sensor_data: MainOutput<SensorData>,
     pub stand_up_front_estimated_remaining_duration: MainOutput<Option<Duration>>,
     pub stand_up_back_estimated_remaining_duration: MainOutput<Option<Duration>>,
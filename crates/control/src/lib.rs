@@ -25,12 +25,15 @@ pub mod orientation_filter;
 pub mod path_planner;
 pub mod penalty_shot_direction_estimation;
 pub mod primary_state_filter;
+pub mod recording_event_trigger;
 pub mod role_assignment;
 pub mod rule_obstacle_composer;
 pub mod sensor_data_receiver;
 pub mod sole_pressure_filter;
 pub mod sonar_filter;
 pub mod support_foot_estimation;
+pub mod symmetry_resolution;
+pub mod team_world_model;
 pub mod time_to_reach_kick_position;
 pub mod visual_referee_filter;
 pub mod whistle_filter;
@@ -1,47 +1,85 @@
 use std::{
+    collections::HashMap,
     f32::consts::{FRAC_PI_2, PI},
     mem::take,
+    time::SystemTime,
 };
 
 use approx::assert_relative_eq;
 use color_eyre::{eyre::WrapErr, Result};
 use nalgebra::{matrix, Matrix, Matrix2, Matrix3, Rotation2, Translation2, Vector2, Vector3};
 use ordered_float::NotNan;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
 use serde::{Deserialize, Serialize};
 
 use context_attribute::context;
 use coordinate_systems::{Field, Ground};
-use filtering::pose_filter::PoseFilter;
-use framework::{AdditionalOutput, HistoricInput, MainOutput, PerceptionInput};
+use filtering::{
+    particle_filter::{effective_sample_size, resample_adaptively},
+    pose_filter::PoseFilter,
+};
+use framework::{
+    deserialize_not_implemented, AdditionalOutput, HistoricInput, MainOutput, PerceptionInput,
+};
 use geometry::line::{Line, Line2};
-use linear_algebra::{distance, point, vector, IntoTransform, Isometry2, Pose2};
-use spl_network_messages::{GamePhase, Penalty, PlayerNumber, Team};
+use linear_algebra::{distance, point, vector, IntoTransform, Isometry2, Point2, Pose2};
+use spl_network_messages::{GamePhase, HulkMessage, Penalty, PlayerNumber, Team};
 use types::{
+    ball_position::BallPosition,
+    cycle_time::CycleTime,
     field_dimensions::FieldDimensions,
-    field_marks::{field_marks_from_field_dimensions, CorrespondencePoints, Direction, FieldMark},
+    field_marks::{
+        field_marks_from_field_dimensions, point_marks_from_field_dimensions, CorrespondencePoints,
+        Direction, FieldMark, PointMark, PointMarkKind,
+    },
     filtered_game_controller_state::FilteredGameControllerState,
+    filtered_game_state::FilteredGameState,
     initial_pose::InitialPose,
     line_data::LineData,
-    localization::{ScoredPose, Update},
+    localization::{
+        LocalizationBackend, Particle, ScoredPose, SymmetryCheck, SymmetryDecision, Update,
+    },
+    messages::IncomingMessage,
     multivariate_normal_distribution::MultivariateNormalDistribution,
+    parameters::{ParticleFilterParameters, SymmetryResolutionParameters},
     players::Players,
     primary_state::PrimaryState,
     support_foot::Side,
 };
 
+use crate::symmetry_resolution::{
+    is_rather_mirrored, symmetry_votes, SymmetryEvidence, SymmetryObservations,
+};
+
 #[derive(Deserialize, Serialize)]
 pub struct Localization {
     field_marks: Vec<FieldMark>,
+    point_marks: Vec<PointMark>,
     last_primary_state: PrimaryState,
     hypotheses: Vec<ScoredPose>,
     hypotheses_when_entered_playing: Vec<ScoredPose>,
+    teammate_messages: Players<Option<ReceivedMessage>>,
+    symmetry_evidence: SymmetryEvidence,
+    particles: Vec<Particle>,
+    #[serde(skip, default = "deserialize_not_implemented")]
+    random_number_generator: StdRng,
+    slow_average_likelihood: f32,
+    fast_average_likelihood: f32,
     is_penalized_with_motion_in_set_or_initial: bool,
     was_picked_up_while_penalized_with_motion_in_set_or_initial: bool,
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+struct ReceivedMessage {
+    receive_time: SystemTime,
+    message: HulkMessage,
+}
+
 #[context]
 pub struct CreationContext {
     field_dimensions: Parameter<FieldDimensions, "field_dimensions">,
+    particle_filter: Parameter<ParticleFilterParameters, "localization.particle_filter">,
 }
 
 #[context]
@@ -50,17 +88,23 @@ pub struct CycleContext {
     fit_errors: AdditionalOutput<Vec<Vec<Vec<Vec<f32>>>>, "localization.fit_errors">,
     measured_lines_in_field:
         AdditionalOutput<Vec<Line2<Field>>, "localization.measured_lines_in_field">,
+    particles: AdditionalOutput<Vec<Particle>, "localization.particles">,
     pose_hypotheses: AdditionalOutput<Vec<ScoredPose>, "localization.pose_hypotheses">,
+    symmetry_check: AdditionalOutput<SymmetryCheck, "localization.symmetry_check">,
     updates: AdditionalOutput<Vec<Vec<Update>>, "localization.updates">,
 
     current_odometry_to_last_odometry:
         HistoricInput<Option<nalgebra::Isometry2<f32>>, "current_odometry_to_last_odometry?">,
 
+    ball_position: Input<Option<BallPosition<Ground>>, "ball_position?">,
+    cycle_time: Input<CycleTime, "cycle_time">,
     filtered_game_controller_state:
         Input<Option<FilteredGameControllerState>, "filtered_game_controller_state?">,
     has_ground_contact: Input<bool, "has_ground_contact">,
     primary_state: Input<PrimaryState, "primary_state">,
 
+    backend: Parameter<LocalizationBackend, "localization.backend">,
+    center_circle_fit_tolerance: Parameter<f32, "localization.center_circle_fit_tolerance">,
     circle_measurement_noise: Parameter<Vector2<f32>, "localization.circle_measurement_noise">,
     field_dimensions: Parameter<FieldDimensions, "field_dimensions">,
     good_matching_threshold: Parameter<f32, "localization.good_matching_threshold">,
@@ -81,20 +125,40 @@ pub struct CycleContext {
     maximum_amount_of_outer_iterations:
         Parameter<usize, "localization.maximum_amount_of_outer_iterations">,
     minimum_fit_error: Parameter<f32, "localization.minimum_fit_error">,
+    minimum_number_of_center_circle_chords:
+        Parameter<usize, "localization.minimum_number_of_center_circle_chords">,
     odometry_noise: Parameter<Vector3<f32>, "localization.odometry_noise">,
+    particle_filter: Parameter<ParticleFilterParameters, "localization.particle_filter">,
     player_number: Parameter<PlayerNumber, "player_number">,
     penalized_distance: Parameter<f32, "localization.penalized_distance">,
     penalized_hypothesis_covariance:
         Parameter<Matrix3<f32>, "localization.penalized_hypothesis_covariance">,
+    point_mark_association_distance: Parameter<f32, "localization.point_mark_association_distance">,
+    point_measurement_noise: Parameter<Vector2<f32>, "localization.point_measurement_noise">,
     score_per_good_match: Parameter<f32, "localization.score_per_good_match">,
+    symmetry_resolution:
+        Parameter<SymmetryResolutionParameters, "localization.symmetry_resolution">,
+    use_center_circle_measurements: Parameter<bool, "localization.use_center_circle_measurements">,
+    use_goal_post_measurements: Parameter<bool, "localization.use_goal_post_measurements">,
     use_line_measurements: Parameter<bool, "localization.use_line_measurements">,
+    use_penalty_marker_measurements:
+        Parameter<bool, "localization.use_penalty_marker_measurements">,
     injected_ground_to_field_of_home_after_coin_toss_before_second_half: Parameter<
         Option<Isometry2<Ground, Field>>,
         "injected_ground_to_field_of_home_after_coin_toss_before_second_half?",
     >,
 
+    detected_goal_posts_bottom:
+        PerceptionInput<Vec<Point2<Ground>>, "VisionBottom", "detected_goal_posts">,
+    detected_goal_posts_top:
+        PerceptionInput<Vec<Point2<Ground>>, "VisionTop", "detected_goal_posts">,
+    detected_penalty_markers_bottom:
+        PerceptionInput<Vec<Point2<Ground>>, "VisionBottom", "detected_penalty_markers">,
+    detected_penalty_markers_top:
+        PerceptionInput<Vec<Point2<Ground>>, "VisionTop", "detected_penalty_markers">,
     line_data_bottom: PerceptionInput<Option<LineData>, "VisionBottom", "line_data?">,
     line_data_top: PerceptionInput<Option<LineData>, "VisionTop", "line_data?">,
+    network_message: PerceptionInput<Option<IncomingMessage>, "SplNetwork", "filtered_message?">,
 
     ground_to_field: CyclerState<Isometry2<Ground, Field>, "ground_to_field">,
 }
@@ -105,6 +169,7 @@ pub struct MainOutputs {
     pub ground_to_field: MainOutput<Option<Isometry2<Ground, Field>>>,
     pub ground_to_field_of_home_after_coin_toss_before_second_half:
         MainOutput<Option<Isometry2<Ground, Field>>>,
+    pub ground_to_field_standard_deviation: MainOutput<Option<f32>>,
 }
 
 impl Localization {
@@ -116,9 +181,16 @@ impl Localization {
                     context.field_dimensions,
                 ))
                 .collect(),
+            point_marks: point_marks_from_field_dimensions(context.field_dimensions),
             last_primary_state: PrimaryState::Unstiff,
             hypotheses: vec![],
             hypotheses_when_entered_playing: vec![],
+            teammate_messages: Default::default(),
+            symmetry_evidence: Default::default(),
+            particles: vec![],
+            random_number_generator: StdRng::seed_from_u64(context.particle_filter.seed),
+            slow_average_likelihood: 0.0,
+            fast_average_likelihood: 0.0,
             is_penalized_with_motion_in_set_or_initial: false,
             was_picked_up_while_penalized_with_motion_in_set_or_initial: false,
         })
@@ -137,11 +209,11 @@ impl Localization {
                     &context.initial_poses[*context.player_number],
                     context.field_dimensions,
                 );
-                self.hypotheses = vec![ScoredPose::from_isometry(
+                self.reset_hypotheses(vec![ScoredPose::from_isometry(
                     initial_pose,
                     *context.initial_hypothesis_covariance,
                     *context.initial_hypothesis_score,
-                )];
+                )]);
                 self.hypotheses_when_entered_playing = self.hypotheses.clone();
             }
             (
@@ -156,11 +228,11 @@ impl Localization {
                         + (context.field_dimensions.length / 2.0),
                     0.0,
                 ]);
-                self.hypotheses = vec![ScoredPose::from_isometry(
+                self.reset_hypotheses(vec![ScoredPose::from_isometry(
                     penalty_shoot_out_striker_pose,
                     *context.initial_hypothesis_covariance,
                     *context.initial_hypothesis_score,
-                )];
+                )]);
                 self.hypotheses_when_entered_playing = self.hypotheses.clone();
             }
             (
@@ -172,11 +244,11 @@ impl Localization {
             ) => {
                 let penalty_shoot_out_keeper_pose =
                     Pose2::from(point![-context.field_dimensions.length / 2.0, 0.0]);
-                self.hypotheses = vec![ScoredPose::from_isometry(
+                self.reset_hypotheses(vec![ScoredPose::from_isometry(
                     penalty_shoot_out_keeper_pose,
                     *context.initial_hypothesis_covariance,
                     *context.initial_hypothesis_score,
-                )];
+                )]);
                 self.hypotheses_when_entered_playing = self.hypotheses.clone();
             }
             (PrimaryState::Set, PrimaryState::Playing, _) => {
@@ -195,7 +267,8 @@ impl Localization {
             (PrimaryState::Penalized, _, _) if primary_state != PrimaryState::Penalized => {
                 if self.is_penalized_with_motion_in_set_or_initial {
                     if self.was_picked_up_while_penalized_with_motion_in_set_or_initial {
-                        self.hypotheses = take(&mut self.hypotheses_when_entered_playing);
+                        let hypotheses = take(&mut self.hypotheses_when_entered_playing);
+                        self.reset_hypotheses(hypotheses);
 
                         let penalized_poses = generate_penalized_poses(
                             context.field_dimensions,
@@ -219,7 +292,26 @@ impl Localization {
                         context.field_dimensions,
                         *context.penalized_distance,
                     );
-                    self.hypotheses = penalized_poses
+                    self.reset_hypotheses(
+                        penalized_poses
+                            .into_iter()
+                            .map(|pose| {
+                                ScoredPose::from_isometry(
+                                    pose,
+                                    *context.penalized_hypothesis_covariance,
+                                    *context.initial_hypothesis_score,
+                                )
+                            })
+                            .collect(),
+                    );
+                    self.hypotheses_when_entered_playing = self.hypotheses.clone();
+                }
+            }
+            (PrimaryState::Unstiff, _, _) => {
+                let penalized_poses =
+                    generate_penalized_poses(context.field_dimensions, *context.penalized_distance);
+                self.reset_hypotheses(
+                    penalized_poses
                         .into_iter()
                         .map(|pose| {
                             ScoredPose::from_isometry(
@@ -228,29 +320,20 @@ impl Localization {
                                 *context.initial_hypothesis_score,
                             )
                         })
-                        .collect();
-                    self.hypotheses_when_entered_playing = self.hypotheses.clone();
-                }
-            }
-            (PrimaryState::Unstiff, _, _) => {
-                let penalized_poses =
-                    generate_penalized_poses(context.field_dimensions, *context.penalized_distance);
-                self.hypotheses = penalized_poses
-                    .into_iter()
-                    .map(|pose| {
-                        ScoredPose::from_isometry(
-                            pose,
-                            *context.penalized_hypothesis_covariance,
-                            *context.initial_hypothesis_score,
-                        )
-                    })
-                    .collect();
+                        .collect(),
+                );
                 self.hypotheses_when_entered_playing = self.hypotheses.clone();
             }
             _ => {}
         }
     }
 
+    /// Replaces the hypotheses, the particles are sampled around them in the next update
+    fn reset_hypotheses(&mut self, hypotheses: Vec<ScoredPose>) {
+        self.hypotheses = hypotheses;
+        self.particles.clear();
+    }
+
     fn update_state(&mut self, context: &mut CycleContext) -> Result<()> {
         let mut fit_errors_per_measurement = vec![];
 
@@ -274,6 +357,14 @@ impl Localization {
             let current_odometry_to_last_odometry = context
                 .current_odometry_to_last_odometry
                 .get(line_data_top_timestamp);
+            let point_measurements = collect_point_measurements(
+                context,
+                line_data_top_timestamp,
+                line_data_top
+                    .iter()
+                    .chain(line_data_bottom.iter())
+                    .filter_map(|data| *data),
+            );
 
             let mut fit_errors_per_hypothesis = vec![];
             for (hypothesis_index, scored_state) in self.hypotheses.iter_mut().enumerate() {
@@ -286,6 +377,48 @@ impl Localization {
                     .wrap_err("failed to predict pose filter")?;
                     scored_state.score *= *context.hypothesis_prediction_score_reduction_factor;
                 }
+                for &(kind, measured_position) in &point_measurements {
+                    let ground_to_field: Isometry2<Ground, Field> =
+                        scored_state.state.as_isometry().framed_transform();
+                    let measured_position_in_field = ground_to_field * measured_position;
+                    let Some(point_mark) = associate_point_mark(
+                        &self.point_marks,
+                        kind,
+                        measured_position_in_field,
+                        *context.point_mark_association_distance,
+                    ) else {
+                        continue;
+                    };
+                    context
+                        .correspondence_lines
+                        .mutate_if_subscribed(|correspondence_lines| {
+                            if let Some(correspondence_lines) = correspondence_lines {
+                                correspondence_lines
+                                    .push(Line(measured_position_in_field, point_mark.position));
+                            }
+                        });
+                    let distance_to_robot = measured_position.coords().norm();
+                    scored_state
+                        .state
+                        .update_with_2d_translation(
+                            measured_position.inner.coords,
+                            Matrix::from_diagonal(context.point_measurement_noise)
+                                * distance_to_robot,
+                            |state| {
+                                let ground_to_field = nalgebra::Isometry2::new(
+                                    nalgebra::vector![state.x, state.y],
+                                    state.z,
+                                );
+                                (ground_to_field.inverse() * point_mark.position.inner).coords
+                            },
+                        )
+                        .context("Failed to update pose filter")?;
+                    if distance(measured_position_in_field, point_mark.position)
+                        < *context.good_matching_threshold
+                    {
+                        scored_state.score += *context.score_per_good_match;
+                    }
+                }
                 if *context.use_line_measurements {
                     let ground_to_field: Isometry2<Ground, Field> =
                         scored_state.state.as_isometry().framed_transform();
@@ -465,6 +598,10 @@ impl Localization {
             }
         }
 
+        if context.symmetry_resolution.enable {
+            self.resolve_symmetry(context);
+        }
+
         let best_hypothesis = self
             .get_best_hypothesis()
             .expect("Expected at least one hypothesis");
@@ -499,6 +636,7 @@ impl Localization {
 
         self.reset_state(primary_state, game_phase, &context, &penalty);
         self.last_primary_state = primary_state;
+        self.update_teammate_messages(&context);
 
         if self.is_penalized_with_motion_in_set_or_initial && !context.has_ground_contact {
             self.was_picked_up_while_penalized_with_motion_in_set_or_initial = true;
@@ -506,7 +644,13 @@ impl Localization {
 
         let ground_to_field = match primary_state {
             PrimaryState::Ready | PrimaryState::Set | PrimaryState::Playing => {
-                self.update_state(&mut context)?;
+                match context.backend {
+                    LocalizationBackend::Hypotheses => {
+                        self.particles.clear();
+                        self.update_state(&mut context)?
+                    }
+                    LocalizationBackend::ParticleFilter => self.update_particles(&mut context),
+                }
                 Some(*context.ground_to_field)
             }
             _ => None,
@@ -531,13 +675,295 @@ impl Localization {
                         }
                     })
             });
+        let ground_to_field_standard_deviation = ground_to_field
+            .and_then(|_| self.get_best_hypothesis())
+            .map(|best_hypothesis| position_standard_deviation(&best_hypothesis.state.covariance));
         Ok(MainOutputs {
             ground_to_field: ground_to_field.into(),
             ground_to_field_of_home_after_coin_toss_before_second_half:
                 ground_to_field_of_home_after_coin_toss_before_second_half.into(),
+            ground_to_field_standard_deviation: ground_to_field_standard_deviation.into(),
         })
     }
 
+    /// Monte Carlo localization with the same odometry and measurement models as the hypotheses
+    ///
+    /// Particles are resampled once their effective sample size drops, otherwise they keep their
+    /// weights. Random particles are injected during resampling when the short term measurement
+    /// likelihood drops below the long term one (augmented MCL), e.g. after the robot was moved by
+    /// a referee. The estimate replaces the hypotheses, such that switching the backend continues
+    /// from the same pose.
+    fn update_particles(&mut self, context: &mut CycleContext) {
+        let parameters = context.particle_filter;
+        if self.particles.is_empty() {
+            self.particles = sample_particles_around(
+                &self.hypotheses,
+                parameters,
+                &mut self.random_number_generator,
+            );
+        }
+
+        let line_datas = context
+            .line_data_top
+            .persistent
+            .iter()
+            .zip(context.line_data_bottom.persistent.values());
+        for ((timestamp, line_data_top), line_data_bottom) in line_datas {
+            if let Some(current_odometry_to_last_odometry) =
+                context.current_odometry_to_last_odometry.get(timestamp)
+            {
+                predict_particles(
+                    &mut self.particles,
+                    current_odometry_to_last_odometry,
+                    context.odometry_noise,
+                    &mut self.random_number_generator,
+                );
+            }
+
+            let line_datas: Vec<&LineData> = line_data_top
+                .iter()
+                .chain(line_data_bottom.iter())
+                .filter_map(|data| *data)
+                .collect();
+            let measured_lines: Vec<_> = if *context.use_line_measurements {
+                line_datas
+                    .iter()
+                    .flat_map(|line_data| line_data.lines.iter().copied())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let point_measurements =
+                collect_point_measurements(context, timestamp, line_datas.iter().copied());
+            if measured_lines.is_empty() && point_measurements.is_empty() {
+                continue;
+            }
+
+            let mut likelihood_sum = 0.0;
+            let mut weight_sum = 0.0;
+            for particle in self.particles.iter_mut() {
+                let likelihood = measurement_likelihood(
+                    particle.pose,
+                    &measured_lines,
+                    &point_measurements,
+                    &self.field_marks,
+                    &self.point_marks,
+                    *context.line_length_acceptance_factor,
+                    parameters,
+                );
+                particle.weight *= likelihood;
+                likelihood_sum += likelihood;
+                weight_sum += particle.weight;
+            }
+            if weight_sum > 0.0 {
+                for particle in self.particles.iter_mut() {
+                    particle.weight /= weight_sum;
+                }
+            }
+            let average_likelihood = likelihood_sum / self.particles.len() as f32;
+            self.slow_average_likelihood += parameters.slow_average_factor
+                * (average_likelihood - self.slow_average_likelihood);
+            self.fast_average_likelihood += parameters.fast_average_factor
+                * (average_likelihood - self.fast_average_likelihood);
+
+            // resampling discards the weights, it is only done once they degenerated
+            let minimum_effective_sample_size =
+                parameters.minimum_effective_sample_size_ratio * self.particles.len() as f32;
+            if effective_sample_size(&self.particles) >= minimum_effective_sample_size {
+                continue;
+            }
+            self.particles = resample_adaptively(
+                &self.particles,
+                &parameters.resampling,
+                &mut self.random_number_generator,
+            );
+            let random_particle_probability = if self.slow_average_likelihood > 0.0 {
+                (1.0 - self.fast_average_likelihood / self.slow_average_likelihood).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            for particle in self.particles.iter_mut() {
+                if self
+                    .random_number_generator
+                    .gen_bool(random_particle_probability as f64)
+                {
+                    particle.pose = random_pose_on_field(
+                        context.field_dimensions,
+                        &mut self.random_number_generator,
+                    );
+                }
+            }
+        }
+
+        if context.symmetry_resolution.enable {
+            self.resolve_particle_symmetry(context);
+        }
+
+        context
+            .particles
+            .fill_if_subscribed(|| self.particles.clone());
+        if let Some(estimate) = estimate_pose(&self.particles, parameters.estimate_radius) {
+            self.hypotheses = vec![ScoredPose {
+                state: estimate,
+                score: *context.initial_hypothesis_score,
+            }];
+        }
+        let best_hypothesis = self
+            .get_best_hypothesis()
+            .expect("Expected at least one hypothesis");
+        *context.ground_to_field = best_hypothesis.state.as_isometry().framed_transform();
+        context
+            .pose_hypotheses
+            .fill_if_subscribed(|| self.hypotheses.clone());
+    }
+
+    fn update_teammate_messages(&mut self, context: &CycleContext) {
+        let messages =
+            context
+                .network_message
+                .persistent
+                .iter()
+                .flat_map(|(receive_time, messages)| {
+                    messages.iter().filter_map(move |message| match message {
+                        Some(IncomingMessage::Spl(message)) => Some(ReceivedMessage {
+                            receive_time: *receive_time,
+                            message: *message,
+                        }),
+                        _ => None,
+                    })
+                });
+        for received_message in messages {
+            if received_message.message.player_number != *context.player_number {
+                self.teammate_messages[received_message.message.player_number] =
+                    Some(received_message);
+            }
+        }
+    }
+
+    /// Balls reported by teammates, the own ball and, during set, the own half
+    fn symmetry_observations(&self, context: &CycleContext) -> SymmetryObservations {
+        let parameters = context.symmetry_resolution;
+        let now = context.cycle_time.start_time;
+        let is_recent = |time: SystemTime| {
+            now.duration_since(time).unwrap_or_default() <= parameters.observation_timeout
+        };
+        let own_ball = context
+            .ball_position
+            .filter(|ball| is_recent(ball.last_seen))
+            .map(|ball| ball.position);
+        let teammate_messages: Vec<_> = self
+            .teammate_messages
+            .iter()
+            .filter_map(|(_player, received_message)| received_message.as_ref())
+            .filter(|received_message| {
+                is_recent(received_message.receive_time) && !received_message.message.fallen
+            })
+            .map(|received_message| {
+                let mut message = received_message.message;
+                message.ball_position = message.ball_position.filter(|ball| {
+                    is_recent(
+                        received_message
+                            .receive_time
+                            .checked_sub(ball.age)
+                            .unwrap_or(received_message.receive_time),
+                    )
+                });
+                message
+            })
+            .collect();
+        let must_be_in_own_half =
+            context
+                .filtered_game_controller_state
+                .is_some_and(|game_controller_state| {
+                    game_controller_state.game_state == FilteredGameState::Set
+                        && !matches!(
+                            game_controller_state.game_phase,
+                            GamePhase::PenaltyShootout { .. }
+                        )
+                });
+        SymmetryObservations {
+            own_ball,
+            teammate_messages,
+            must_be_in_own_half,
+        }
+    }
+
+    /// Detects hypotheses which converged to the mirrored pose on the point symmetric field
+    ///
+    /// Each hypothesis is checked against the balls reported by teammates and, during
+    /// set, against the own half. Hypotheses rather explained by their mirrored pose are down
+    /// weighted. Since all hypotheses may be mirrored, the votes for the best hypothesis are
+    /// accumulated and it is mirrored once the evidence is strong enough.
+    fn resolve_symmetry(&mut self, context: &mut CycleContext) {
+        let parameters = context.symmetry_resolution;
+        let observations = self.symmetry_observations(context);
+
+        for hypothesis in self.hypotheses.iter_mut() {
+            let votes = symmetry_votes(
+                hypothesis.state.as_isometry().framed_transform(),
+                &observations,
+                parameters,
+            );
+            if is_rather_mirrored(&votes) {
+                hypothesis.score *= parameters.mirrored_hypothesis_score_factor;
+            }
+        }
+
+        let Some((best_index, best_hypothesis)) = self
+            .hypotheses
+            .iter()
+            .enumerate()
+            .max_by(|(_, left), (_, right)| left.score.total_cmp(&right.score))
+        else {
+            return;
+        };
+        let votes = symmetry_votes(
+            best_hypothesis.state.as_isometry().framed_transform(),
+            &observations,
+            parameters,
+        );
+        let symmetry_check = self.symmetry_evidence.accumulate(&votes, parameters);
+        if symmetry_check.decision == SymmetryDecision::Mirror {
+            self.hypotheses[best_index] = self.hypotheses[best_index].mirrored();
+        }
+        context.symmetry_check.fill_if_subscribed(|| symmetry_check);
+    }
+
+    /// Same as [`Self::resolve_symmetry`] for the particles
+    ///
+    /// Particles rather explained by their mirrored pose are down weighted. The votes for the pose
+    /// estimate are accumulated and all particles are mirrored once the evidence is strong enough,
+    /// since the measurements alone never move the particles to the other half.
+    fn resolve_particle_symmetry(&mut self, context: &mut CycleContext) {
+        let parameters = context.symmetry_resolution;
+        let observations = self.symmetry_observations(context);
+
+        for particle in self.particles.iter_mut() {
+            let votes = symmetry_votes(particle.pose.as_transform(), &observations, parameters);
+            if is_rather_mirrored(&votes) {
+                particle.weight *= parameters.mirrored_hypothesis_score_factor;
+            }
+        }
+
+        let Some(estimate) =
+            estimate_pose(&self.particles, context.particle_filter.estimate_radius)
+        else {
+            return;
+        };
+        let votes = symmetry_votes(
+            estimate.as_isometry().framed_transform(),
+            &observations,
+            parameters,
+        );
+        let symmetry_check = self.symmetry_evidence.accumulate(&votes, parameters);
+        if symmetry_check.decision == SymmetryDecision::Mirror {
+            for particle in self.particles.iter_mut() {
+                *particle = particle.mirrored();
+            }
+        }
+        context.symmetry_check.fill_if_subscribed(|| symmetry_check);
+    }
+
     fn get_best_hypothesis(&self) -> Option<&ScoredPose> {
         self.hypotheses
             .iter()
@@ -545,6 +971,324 @@ impl Localization {
     }
 }
 
+fn collect_point_measurements<'a>(
+    context: &CycleContext,
+    timestamp: &SystemTime,
+    line_datas: impl Iterator<Item = &'a LineData>,
+) -> Vec<(PointMarkKind, Point2<Ground>)> {
+    let mut point_measurements = Vec::new();
+    if *context.use_goal_post_measurements {
+        let detected_goal_posts = context
+            .detected_goal_posts_top
+            .persistent
+            .get(timestamp)
+            .into_iter()
+            .chain(context.detected_goal_posts_bottom.persistent.get(timestamp))
+            .flatten();
+        point_measurements.extend(detected_goal_posts.flat_map(|positions| {
+            positions
+                .iter()
+                .map(|&position| (PointMarkKind::GoalPost, position))
+        }));
+    }
+    if *context.use_penalty_marker_measurements {
+        let detected_penalty_markers = context
+            .detected_penalty_markers_top
+            .persistent
+            .get(timestamp)
+            .into_iter()
+            .chain(
+                context
+                    .detected_penalty_markers_bottom
+                    .persistent
+                    .get(timestamp),
+            )
+            .flatten();
+        point_measurements.extend(detected_penalty_markers.flat_map(|positions| {
+            positions
+                .iter()
+                .map(|&position| (PointMarkKind::PenaltyMarker, position))
+        }));
+    }
+    if *context.use_center_circle_measurements {
+        let lines: Vec<_> = line_datas
+            .flat_map(|line_data| line_data.lines.iter().copied())
+            .collect();
+        point_measurements.extend(
+            center_circle_center(
+                &lines,
+                context.field_dimensions.center_circle_diameter / 2.0,
+                *context.center_circle_fit_tolerance,
+                *context.minimum_number_of_center_circle_chords,
+            )
+            .map(|center| (PointMarkKind::CenterCircle, center)),
+        );
+    }
+    point_measurements
+}
+
+/// Closest point mark of the given kind, if it is within the association distance
+fn associate_point_mark(
+    point_marks: &[PointMark],
+    kind: PointMarkKind,
+    measured_position_in_field: Point2<Field>,
+    maximum_association_distance: f32,
+) -> Option<PointMark> {
+    point_marks
+        .iter()
+        .filter(|point_mark| point_mark.kind == kind)
+        .map(|point_mark| {
+            (
+                point_mark,
+                distance(point_mark.position, measured_position_in_field),
+            )
+        })
+        .filter(|(_, distance)| *distance <= maximum_association_distance)
+        .min_by(|(_, left_distance), (_, right_distance)| left_distance.total_cmp(right_distance))
+        .map(|(point_mark, _)| *point_mark)
+}
+
+/// Estimates the center of the center circle from measured lines which are chords of it
+///
+/// Each chord of the known radius has two candidate centers on its perpendicular bisector. The
+/// candidate supported by most chords is taken, lines which are not part of the circle rarely
+/// agree on a candidate.
+fn center_circle_center(
+    lines: &[Line2<Ground>],
+    radius: f32,
+    tolerance: f32,
+    minimum_number_of_chords: usize,
+) -> Option<Point2<Ground>> {
+    let candidates_per_chord: Vec<[Point2<Ground>; 2]> = lines
+        .iter()
+        .filter_map(|line| {
+            let half_length = line.length() / 2.0;
+            if half_length > radius + tolerance {
+                return None;
+            }
+            let offset = (radius.powi(2) - half_length.powi(2)).max(0.0).sqrt();
+            let direction = (line.1 - line.0).normalize();
+            let normal = vector![-direction.y(), direction.x()];
+            let center = line.center();
+            Some([center + normal * offset, center - normal * offset])
+        })
+        .collect();
+    candidates_per_chord
+        .iter()
+        .flatten()
+        .map(|seed| {
+            candidates_per_chord
+                .iter()
+                .filter_map(|candidates| {
+                    candidates
+                        .iter()
+                        .copied()
+                        .map(|candidate| (candidate, distance(*seed, candidate)))
+                        .filter(|(_, distance)| *distance <= tolerance)
+                        .min_by(|(_, left_distance), (_, right_distance)| {
+                            left_distance.total_cmp(right_distance)
+                        })
+                        .map(|(candidate, _)| candidate)
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|supporting_candidates| supporting_candidates.len() >= minimum_number_of_chords)
+        .max_by_key(|supporting_candidates| supporting_candidates.len())
+        .map(|supporting_candidates| {
+            let number_of_candidates = supporting_candidates.len() as f32;
+            (supporting_candidates
+                .into_iter()
+                .map(|candidate| candidate.coords())
+                .sum::<linear_algebra::Vector2<Ground>>()
+                / number_of_candidates)
+                .as_point()
+        })
+}
+
+fn sample_particles_around(
+    hypotheses: &[ScoredPose],
+    parameters: &ParticleFilterParameters,
+    random_number_generator: &mut impl Rng,
+) -> Vec<Particle> {
+    if hypotheses.is_empty() {
+        return Vec::new();
+    }
+    let number_of_particles_per_hypothesis =
+        parameters.resampling.maximum_number_of_particles / hypotheses.len();
+    let weight = 1.0 / (number_of_particles_per_hypothesis * hypotheses.len()) as f32;
+    let mut particles = Vec::new();
+    for hypothesis in hypotheses {
+        let mean = hypothesis.state.mean;
+        for _ in 0..number_of_particles_per_hypothesis {
+            let noise = parameters
+                .reset_standard_deviation
+                .map(|standard_deviation| {
+                    sample_normal(standard_deviation, random_number_generator)
+                });
+            particles.push(Particle {
+                pose: Pose2::new(
+                    vector![mean.x + noise.x, mean.y + noise.y],
+                    mean.z + noise.z,
+                ),
+                weight,
+            });
+        }
+    }
+    particles
+}
+
+fn predict_particles(
+    particles: &mut [Particle],
+    current_odometry_to_last_odometry: &nalgebra::Isometry2<f32>,
+    odometry_noise: &Vector3<f32>,
+    random_number_generator: &mut impl Rng,
+) {
+    for particle in particles {
+        // odometry noise is given as variance per cycle, like the process noise of the hypotheses
+        let noise = odometry_noise
+            .map(|variance| sample_normal(variance.max(0.0).sqrt(), random_number_generator));
+        let noisy_odometry = nalgebra::Isometry2::new(
+            current_odometry_to_last_odometry.translation.vector + noise.xy(),
+            current_odometry_to_last_odometry.rotation.angle() + noise.z,
+        );
+        particle.pose = Pose2::wrap(particle.pose.inner * noisy_odometry);
+    }
+}
+
+fn sample_normal(standard_deviation: f32, random_number_generator: &mut impl Rng) -> f32 {
+    if standard_deviation <= 0.0 {
+        return 0.0;
+    }
+    Normal::new(0.0, standard_deviation)
+        .map(|distribution| distribution.sample(random_number_generator))
+        .unwrap_or_default()
+}
+
+fn random_pose_on_field(
+    field_dimensions: &FieldDimensions,
+    random_number_generator: &mut impl Rng,
+) -> Pose2<Field> {
+    let half_length = field_dimensions.length / 2.0 + field_dimensions.border_strip_width;
+    let half_width = field_dimensions.width / 2.0 + field_dimensions.border_strip_width;
+    Pose2::new(
+        vector![
+            random_number_generator.gen_range(-half_length..=half_length),
+            random_number_generator.gen_range(-half_width..=half_width)
+        ],
+        random_number_generator.gen_range(-PI..PI),
+    )
+}
+
+/// Likelihood of the measurements given the pose, as geometric mean over all measurements
+///
+/// Lines are matched against the field marks like in the hypotheses update, points against the
+/// closest point mark of their kind. The geometric mean keeps the likelihood comparable across
+/// cycles with different numbers of measurements, which the random particle injection relies on.
+fn measurement_likelihood(
+    pose: Pose2<Field>,
+    measured_lines: &[Line2<Ground>],
+    point_measurements: &[(PointMarkKind, Point2<Ground>)],
+    field_marks: &[FieldMark],
+    point_marks: &[PointMark],
+    line_length_acceptance_factor: f32,
+    parameters: &ParticleFilterParameters,
+) -> f32 {
+    let ground_to_field: Isometry2<Ground, Field> = pose.as_transform();
+    let maximum_distance = parameters.maximum_field_mark_distance;
+    let log_likelihood = |distance: f32, standard_deviation: f32| {
+        -0.5 * (distance.min(maximum_distance) / standard_deviation).powi(2)
+    };
+
+    let measured_lines_in_field: Vec<_> = measured_lines
+        .iter()
+        .map(|&measured_line| ground_to_field * measured_line)
+        .collect();
+    let field_mark_correspondences = get_field_mark_correspondence(
+        &measured_lines_in_field,
+        nalgebra::Isometry2::identity(),
+        field_marks,
+        line_length_acceptance_factor,
+    );
+    let number_of_unmatched_lines = measured_lines.len() - field_mark_correspondences.len();
+    let line_log_likelihoods = field_mark_correspondences
+        .iter()
+        .map(|correspondence| correspondence.fit_error_sum() / 2.0)
+        .chain(std::iter::repeat(maximum_distance).take(number_of_unmatched_lines))
+        .map(|distance| log_likelihood(distance, parameters.line_standard_deviation));
+    let point_log_likelihoods = point_measurements.iter().map(|&(kind, position)| {
+        let position_in_field = ground_to_field * position;
+        let point_distance =
+            associate_point_mark(point_marks, kind, position_in_field, maximum_distance)
+                .map_or(maximum_distance, |point_mark| {
+                    distance(point_mark.position, position_in_field)
+                });
+        log_likelihood(point_distance, parameters.point_standard_deviation)
+    });
+
+    let log_likelihoods: Vec<_> = line_log_likelihoods.chain(point_log_likelihoods).collect();
+    if log_likelihoods.is_empty() {
+        return 1.0;
+    }
+    (log_likelihoods.iter().sum::<f32>() / log_likelihoods.len() as f32).exp()
+}
+
+/// Weighted mean and covariance of the particles around the densest region
+///
+/// The mean of all particles would lie between the modes of a multimodal distribution, e.g.
+/// between a pose and its mirrored pose.
+fn estimate_pose(particles: &[Particle], radius: f32) -> Option<MultivariateNormalDistribution<3>> {
+    let cell = |position: Point2<Field>| {
+        (
+            (position.x() / radius).floor() as i32,
+            (position.y() / radius).floor() as i32,
+        )
+    };
+    let mut cell_weights: HashMap<(i32, i32), f32> = HashMap::new();
+    for particle in particles {
+        *cell_weights
+            .entry(cell(particle.pose.position()))
+            .or_default() += particle.weight;
+    }
+    let (&(x, y), _) = cell_weights
+        .iter()
+        .max_by(|(_, left_weight), (_, right_weight)| left_weight.total_cmp(right_weight))?;
+    let center = point![(x as f32 + 0.5) * radius, (y as f32 + 0.5) * radius];
+
+    let cluster: Vec<_> = particles
+        .iter()
+        .filter(|particle| distance(particle.pose.position(), center) <= radius)
+        .collect();
+    let total_weight: f32 = cluster.iter().map(|particle| particle.weight).sum();
+    if total_weight <= 0.0 {
+        return None;
+    }
+    let (position_sum, sine_sum, cosine_sum) = cluster.iter().fold(
+        (nalgebra::Vector2::zeros(), 0.0, 0.0),
+        |(position_sum, sine_sum, cosine_sum), particle| {
+            let angle = particle.pose.angle();
+            (
+                position_sum + particle.pose.position().inner.coords * particle.weight,
+                sine_sum + angle.sin() * particle.weight,
+                cosine_sum + angle.cos() * particle.weight,
+            )
+        },
+    );
+    let position = position_sum / total_weight;
+    let mean = nalgebra::vector![position.x, position.y, sine_sum.atan2(cosine_sum)];
+    let covariance = cluster
+        .iter()
+        .map(|particle| {
+            let difference = nalgebra::vector![
+                particle.pose.position().x() - mean.x,
+                particle.pose.position().y() - mean.y,
+                Rotation2::new(particle.pose.angle() - mean.z).angle()
+            ];
+            difference * difference.transpose() * (particle.weight / total_weight)
+        })
+        .sum();
+    Some(MultivariateNormalDistribution { mean, covariance })
+}
+
 pub fn goal_support_structure_line_marks_from_field_dimensions(
     field_dimensions: &FieldDimensions,
 ) -> Vec<FieldMark> {
@@ -629,6 +1373,16 @@ impl FieldMarkCorrespondence {
     }
 }
 
+/// Standard deviation of the position along the most uncertain direction
+fn position_standard_deviation(pose_covariance: &Matrix3<f32>) -> f32 {
+    let position_covariance: Matrix2<f32> = pose_covariance.fixed_view::<2, 2>(0, 0).into();
+    position_covariance
+        .symmetric_eigenvalues()
+        .max()
+        .max(0.0)
+        .sqrt()
+}
+
 fn predict(
     state: &mut MultivariateNormalDistribution<3>,
     current_odometry_to_last_odometry: &nalgebra::Isometry2<f32>,
@@ -1701,4 +2455,85 @@ mod tests {
         let update = get_2d_translation_measurement(ground_to_field, field_mark_correspondence);
         assert_relative_eq!(update, nalgebra::vector![0.0, -2.0], epsilon = 0.0001);
     }
+
+    #[test]
+    fn center_circle_center_from_chords() {
+        let radius = 0.75;
+        let chord = |start_angle: f32, end_angle: f32| {
+            Line(
+                point![
+                    1.0 + radius * start_angle.cos(),
+                    0.5 + radius * start_angle.sin()
+                ],
+                point![
+                    1.0 + radius * end_angle.cos(),
+                    0.5 + radius * end_angle.sin()
+                ],
+            )
+        };
+        let lines = [
+            chord(0.0, 0.6),
+            chord(1.2, 1.8),
+            chord(2.5, 3.0),
+            Line(point![-2.0, -1.0], point![-2.0, 1.0]),
+        ];
+
+        let center = center_circle_center(&lines, radius, 0.05, 3).unwrap();
+
+        assert!(distance(center, point![1.0, 0.5]) < 1e-4);
+    }
+
+    #[test]
+    fn too_few_chords_yield_no_center_circle_center() {
+        let lines = [Line(point![0.75, 0.0], point![0.0, 0.75])];
+
+        assert!(center_circle_center(&lines, 0.75, 0.05, 2).is_none());
+    }
+
+    #[test]
+    fn point_mark_is_associated_with_closest_mark_of_same_kind() {
+        let field_dimensions = FieldDimensions {
+            length: 9.0,
+            penalty_marker_distance: 1.3,
+            goal_inner_width: 1.5,
+            goal_post_diameter: 0.1,
+            line_width: 0.05,
+            ..Default::default()
+        };
+        let point_marks = point_marks_from_field_dimensions(&field_dimensions);
+
+        let penalty_marker = associate_point_mark(
+            &point_marks,
+            PointMarkKind::PenaltyMarker,
+            point![3.1, 0.1],
+            0.4,
+        )
+        .unwrap();
+        let goal_post =
+            associate_point_mark(&point_marks, PointMarkKind::GoalPost, point![3.1, 0.1], 0.4);
+
+        assert_relative_eq!(penalty_marker.position, point![3.2, 0.0], epsilon = 1e-5);
+        assert!(goal_post.is_none());
+    }
+
+    #[test]
+    fn pose_estimate_ignores_mirrored_particles() {
+        let particle = |x: f32, y: f32, angle: f32| Particle {
+            pose: Pose2::new(vector![x, y], angle),
+            weight: 0.1,
+        };
+        let particles = [
+            particle(1.1, 0.1, 3.1),
+            particle(1.2, 0.2, -3.1),
+            particle(1.3, 0.1, PI),
+            particle(-1.2, -0.2, 0.0),
+            particle(-1.2, -0.1, 0.0),
+        ];
+
+        let estimate = estimate_pose(&particles, 0.5).unwrap();
+
+        assert_relative_eq!(estimate.mean.x, 1.2, epsilon = 1e-5);
+        assert_relative_eq!(estimate.mean.y, 0.4 / 3.0, epsilon = 1e-5);
+        assert_relative_eq!(estimate.mean.z.abs(), PI, epsilon = 1e-3);
+    }
 }
@@ -5,14 +5,15 @@ use context_attribute::context;
 use coordinate_systems::{Field, Ground};
 use filtering::kalman_filter::KalmanFilter;
 use framework::{AdditionalOutput, HistoricInput, MainOutput, PerceptionInput};
-use itertools::{chain, iproduct};
-use linear_algebra::{distance, point, IntoFramed, Isometry2, Point2};
+use itertools::chain;
+use linear_algebra::{distance, IntoFramed, Isometry2, Point2};
 use nalgebra::Matrix2;
 use serde::{Deserialize, Serialize};
 use types::{
     cycle_time::CycleTime,
     detected_feet::DetectedFeet,
     field_dimensions::FieldDimensions,
+    field_marks::goal_post_positions,
     foot_bumper_obstacle::FootBumperObstacle,
     multivariate_normal_distribution::MultivariateNormalDistribution,
     obstacle_filter::Hypothesis,
@@ -371,17 +372,8 @@ fn calculate_goal_post_positions(
     ground_to_field
         .map(|ground_to_field| {
             let field_to_robot = ground_to_field.inverse();
-            iproduct!([-1.0, 1.0], [-1.0, 1.0]).map(move |(x_sign, y_sign)| {
-                let radius = field_dimensions.goal_post_diameter / 2.0;
-                let position_on_field = point![
-                    x_sign
-                        * (field_dimensions.length / 2.0
-                            + field_dimensions.goal_post_diameter / 2.0
-                            - field_dimensions.line_width / 2.0),
-                    y_sign * (field_dimensions.goal_inner_width / 2.0 + radius)
-                ];
-                field_to_robot * position_on_field
-            })
+            goal_post_positions(field_dimensions)
+                .map(|position_on_field| field_to_robot * position_on_field)
         })
         .into_iter()
         .flatten()
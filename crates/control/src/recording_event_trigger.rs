@@ -0,0 +1,97 @@
+use color_eyre::Result;
+use context_attribute::context;
+use coordinate_systems::{Field, Ground};
+use framework::MainOutput;
+use hardware::RecordingInterface;
+use linear_algebra::{distance, Isometry2};
+use serde::{Deserialize, Serialize};
+use spl_network_messages::PlayerNumber;
+use types::{
+    cycle_time::CycleTime, fall_state::FallState,
+    filtered_game_controller_state::FilteredGameControllerState, filtered_whistle::FilteredWhistle,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct RecordingEventTrigger {
+    last_fall_state: FallState,
+    was_penalized: bool,
+    last_ground_to_field: Option<Isometry2<Ground, Field>>,
+}
+
+#[context]
+pub struct CreationContext {}
+
+#[context]
+pub struct CycleContext {
+    cycle_time: Input<CycleTime, "cycle_time">,
+    fall_state: Input<FallState, "fall_state">,
+    filtered_game_controller_state:
+        Input<Option<FilteredGameControllerState>, "filtered_game_controller_state?">,
+    filtered_whistle: Input<FilteredWhistle, "filtered_whistle">,
+    ground_to_field: Input<Option<Isometry2<Ground, Field>>, "ground_to_field?">,
+
+    player_number: Parameter<PlayerNumber, "player_number">,
+    on_fall: Parameter<bool, "recording_event_trigger.on_fall">,
+    on_penalty: Parameter<bool, "recording_event_trigger.on_penalty">,
+    on_whistle: Parameter<bool, "recording_event_trigger.on_whistle">,
+    localization_jump_distance:
+        Parameter<Option<f32>, "recording_event_trigger.localization_jump_distance?">,
+
+    hardware_interface: HardwareInterface,
+}
+
+#[context]
+#[derive(Default)]
+pub struct MainOutputs {}
+
+impl RecordingEventTrigger {
+    pub fn new(_context: CreationContext) -> Result<Self> {
+        Ok(Self {
+            last_fall_state: FallState::Upright,
+            was_penalized: false,
+            last_ground_to_field: None,
+        })
+    }
+
+    pub fn cycle(&mut self, context: CycleContext<impl RecordingInterface>) -> Result<MainOutputs> {
+        let started_falling = matches!(self.last_fall_state, FallState::Upright)
+            && !matches!(context.fall_state, FallState::Upright);
+        self.last_fall_state = *context.fall_state;
+
+        let is_penalized =
+            context
+                .filtered_game_controller_state
+                .is_some_and(|game_controller_state| {
+                    game_controller_state.penalties[*context.player_number].is_some()
+                });
+        let got_penalized = !self.was_penalized && is_penalized;
+        self.was_penalized = is_penalized;
+
+        let localization_jumped = match (
+            context.localization_jump_distance,
+            self.last_ground_to_field,
+            context.ground_to_field,
+        ) {
+            (Some(jump_distance), Some(last_ground_to_field), Some(ground_to_field)) => {
+                distance(
+                    last_ground_to_field.translation(),
+                    ground_to_field.translation(),
+                ) > *jump_distance
+            }
+            _ => false,
+        };
+        self.last_ground_to_field = context.ground_to_field.copied();
+
+        let is_event = (*context.on_fall && started_falling)
+            || (*context.on_penalty && got_penalized)
+            || (*context.on_whistle && context.filtered_whistle.started_this_cycle)
+            || localization_jumped;
+        if is_event {
+            context
+                .hardware_interface
+                .trigger_event_recording(context.cycle_time.start_time);
+        }
+
+        Ok(MainOutputs {})
+    }
+}
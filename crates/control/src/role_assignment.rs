@@ -7,9 +7,10 @@ use context_attribute::context;
 use coordinate_systems::{Field, Ground};
 use framework::{MainOutput, PerceptionInput};
 use hardware::NetworkInterface;
-use linear_algebra::{Isometry2, Point2, Vector};
+use linear_algebra::{distance, Isometry2, Point2, Vector};
 use spl_network_messages::{
-    GameControllerReturnMessage, GamePhase, HulkMessage, Penalty, PlayerNumber, SubState, Team,
+    GameControllerReturnMessage, GamePhase, HulkMessage, Penalty, PlayerNumber, SharedObstacle,
+    SubState, Team, MAXIMUM_NUMBER_OF_SHARED_OBSTACLES,
 };
 use types::{
     ball_position::BallPosition,
@@ -19,7 +20,7 @@ use types::{
     filtered_game_controller_state::FilteredGameControllerState,
     initial_pose::InitialPose,
     messages::{IncomingMessage, OutgoingMessage},
-    parameters::SplNetworkParameters,
+    parameters::{SplNetworkParameters, TeamWorldModelParameters},
     players::Players,
     primary_state::PrimaryState,
     roles::Role,
@@ -49,9 +50,11 @@ pub struct CycleContext {
         Input<Option<FilteredGameControllerState>, "filtered_game_controller_state?">,
     primary_state: Input<PrimaryState, "primary_state">,
     ground_to_field: Input<Option<Isometry2<Ground, Field>>, "ground_to_field?">,
+    ground_to_field_standard_deviation: Input<Option<f32>, "ground_to_field_standard_deviation?">,
     cycle_time: Input<CycleTime, "cycle_time">,
     network_message: PerceptionInput<Option<IncomingMessage>, "SplNetwork", "filtered_message?">,
     time_to_reach_kick_position: CyclerState<Duration, "time_to_reach_kick_position">,
+    shared_obstacles: CyclerState<Vec<Point2<Field>>, "shared_obstacles">,
 
     field_dimensions: Parameter<FieldDimensions, "field_dimensions">,
     forced_role: Parameter<Option<Role>, "role_assignment.forced_role?">,
@@ -61,6 +64,7 @@ pub struct CycleContext {
     optional_roles: Parameter<Vec<Role>, "behavior.optional_roles">,
     player_number: Parameter<PlayerNumber, "player_number">,
     spl_network: Parameter<SplNetworkParameters, "spl_network">,
+    team_world_model: Parameter<TeamWorldModelParameters, "team_world_model">,
 
     hardware: HardwareInterface,
 }
@@ -243,6 +247,18 @@ impl RoleAssignment {
                 let sender_position = ground_to_field.inverse() * spl_message.pose.position();
                 if spl_message.player_number != *context.player_number {
                     network_robot_obstacles.push(sender_position);
+                    network_robot_obstacles.extend(
+                        spl_message
+                            .obstacles
+                            .iter()
+                            .flatten()
+                            .map(|obstacle| ground_to_field.inverse() * obstacle.position())
+                            // teammates see this robot as obstacle as well
+                            .filter(|obstacle| {
+                                distance(*obstacle, Point2::origin())
+                                    > context.team_world_model.shared_obstacle_self_distance
+                            }),
+                    );
                 }
                 (role, send_spl_striker_message, team_ball) = process_role_state_machine(
                     role,
@@ -287,7 +303,8 @@ impl RoleAssignment {
                         .spl_network
                         .remaining_amount_of_messages_to_stop_sending
                 {
-                    let ball_position = if context.ball_position.is_none() && team_ball.is_some() {
+                    let ball_is_relayed = context.ball_position.is_none() && team_ball.is_some();
+                    let ball_position = if ball_is_relayed {
                         team_ball_to_network_ball_position(team_ball, cycle_start_time)
                     } else {
                         seen_ball_to_hulks_network_ball_position(
@@ -302,8 +319,14 @@ impl RoleAssignment {
                             player_number: *context.player_number,
                             fallen: matches!(context.fall_state, FallState::Fallen { .. }),
                             pose: ground_to_field.as_pose(),
+                            pose_standard_deviation: context
+                                .ground_to_field_standard_deviation
+                                .copied()
+                                .unwrap_or_default(),
                             ball_position,
+                            ball_is_relayed,
                             time_to_reach_kick_position: Some(*context.time_to_reach_kick_position),
+                            obstacles: shared_obstacles_for_message(context.shared_obstacles),
                         }))?;
                 }
             }
@@ -646,6 +669,16 @@ fn team_ball_to_network_ball_position(
     })
 }
 
+fn shared_obstacles_for_message(
+    obstacles: &[Point2<Field>],
+) -> [Option<SharedObstacle>; MAXIMUM_NUMBER_OF_SHARED_OBSTACLES] {
+    let mut shared_obstacles = [None; MAXIMUM_NUMBER_OF_SHARED_OBSTACLES];
+    for (shared_obstacle, obstacle) in shared_obstacles.iter_mut().zip(obstacles) {
+        *shared_obstacle = Some(SharedObstacle::new(*obstacle));
+    }
+    shared_obstacles
+}
+
 fn team_ball_from_spl_message(
     cycle_start_time: SystemTime,
     spl_message: &HulkMessage,
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::{Field, Ground};
+use linear_algebra::{distance, point, Isometry2, Point2};
+use spl_network_messages::HulkMessage;
+use types::{
+    localization::{SymmetryCheck, SymmetryDecision},
+    parameters::SymmetryResolutionParameters,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Vote {
+    Consistent,
+    Mirrored,
+}
+
+/// Observations which are not point symmetric on the field
+pub struct SymmetryObservations {
+    pub own_ball: Option<Point2<Ground>>,
+    pub teammate_messages: Vec<HulkMessage>,
+    pub must_be_in_own_half: bool,
+}
+
+/// Compares the pose with observations that are not point symmetric
+///
+/// Each observation votes whether it is better explained by the pose or by the mirrored pose.
+/// Observations explaining both equally well do not vote. Balls a teammate only relays from the
+/// team ball do not vote, they may originate from the own ball. Obstacles shared by teammates do
+/// not vote either: teammates are removed from them, so an opponent standing at the mirrored
+/// position would be the only match and flip a correct pose.
+pub fn symmetry_votes(
+    ground_to_field: Isometry2<Ground, Field>,
+    observations: &SymmetryObservations,
+    parameters: &SymmetryResolutionParameters,
+) -> Vec<Vote> {
+    let mirror = |point: Point2<Field>| point![-point.x(), -point.y()];
+    let vote = |consistent_distance: f32, mirrored_distance: f32| {
+        if mirrored_distance + parameters.distance_margin < consistent_distance {
+            Some(Vote::Mirrored)
+        } else if consistent_distance + parameters.distance_margin < mirrored_distance {
+            Some(Vote::Consistent)
+        } else {
+            None
+        }
+    };
+    let own_position = ground_to_field.as_pose().position();
+    let own_ball_in_field = observations.own_ball.map(|ball| ground_to_field * ball);
+
+    let ball_votes = observations
+        .teammate_messages
+        .iter()
+        .filter(|message| !message.ball_is_relayed)
+        .filter_map(|message| {
+            let teammate_ball = message.ball_position?.position;
+            let own_ball = own_ball_in_field?;
+            vote(
+                distance(own_ball, teammate_ball),
+                distance(mirror(own_ball), teammate_ball),
+            )
+        });
+    let own_half_vote = observations
+        .must_be_in_own_half
+        .then(|| {
+            if own_position.x() > parameters.own_half_margin {
+                Some(Vote::Mirrored)
+            } else if own_position.x() < -parameters.own_half_margin {
+                Some(Vote::Consistent)
+            } else {
+                None
+            }
+        })
+        .flatten();
+
+    ball_votes.chain(own_half_vote).collect()
+}
+
+/// Whether the majority of the votes is for the mirrored pose
+pub fn is_rather_mirrored(votes: &[Vote]) -> bool {
+    let mirrored_votes = votes.iter().filter(|vote| **vote == Vote::Mirrored).count();
+    2 * mirrored_votes > votes.len()
+}
+
+/// Decaying sum of the vote differences for the best pose
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct SymmetryEvidence {
+    evidence: f32,
+}
+
+impl SymmetryEvidence {
+    /// Accumulates the votes and decides whether the best pose is mirrored
+    ///
+    /// The evidence is reset once it reaches the threshold and the pose is mirrored.
+    pub fn accumulate(
+        &mut self,
+        votes: &[Vote],
+        parameters: &SymmetryResolutionParameters,
+    ) -> SymmetryCheck {
+        let consistent_votes = votes
+            .iter()
+            .filter(|vote| **vote == Vote::Consistent)
+            .count();
+        let mirrored_votes = votes.len() - consistent_votes;
+        self.evidence = (self.evidence * parameters.evidence_decay + mirrored_votes as f32
+            - consistent_votes as f32)
+            .max(0.0);
+        let decision = if self.evidence >= parameters.mirror_threshold {
+            self.evidence = 0.0;
+            SymmetryDecision::Mirror
+        } else {
+            SymmetryDecision::Keep
+        };
+        SymmetryCheck {
+            consistent_votes,
+            mirrored_votes,
+            evidence: self.evidence,
+            decision,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use linear_algebra::vector;
+    use spl_network_messages::{SharedObstacle, MAXIMUM_NUMBER_OF_SHARED_OBSTACLES};
+
+    use super::*;
+
+    fn teammate_ball_message(ball_is_relayed: bool) -> HulkMessage {
+        HulkMessage {
+            ball_position: Some(spl_network_messages::BallPosition {
+                position: point![-3.0, 0.1],
+                age: Default::default(),
+            }),
+            ball_is_relayed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn teammate_ball_votes_for_mirrored_pose() {
+        let parameters = SymmetryResolutionParameters {
+            distance_margin: 1.0,
+            ..Default::default()
+        };
+        let ground_to_field = Isometry2::from_parts(vector![2.0, 0.0], 0.0);
+        let observations = SymmetryObservations {
+            own_ball: Some(point![1.0, 0.0]),
+            teammate_messages: vec![teammate_ball_message(false)],
+            must_be_in_own_half: false,
+        };
+
+        let votes = symmetry_votes(ground_to_field, &observations, &parameters);
+
+        assert_eq!(votes, vec![Vote::Mirrored]);
+    }
+
+    #[test]
+    fn relayed_teammate_ball_does_not_vote() {
+        let parameters = SymmetryResolutionParameters {
+            distance_margin: 1.0,
+            ..Default::default()
+        };
+        let ground_to_field = Isometry2::from_parts(vector![2.0, 0.0], 0.0);
+        let observations = SymmetryObservations {
+            own_ball: Some(point![1.0, 0.0]),
+            teammate_messages: vec![teammate_ball_message(true)],
+            must_be_in_own_half: false,
+        };
+
+        let votes = symmetry_votes(ground_to_field, &observations, &parameters);
+
+        assert!(votes.is_empty());
+    }
+
+    #[test]
+    fn opponent_at_mirrored_position_does_not_flip_pose() {
+        let parameters = SymmetryResolutionParameters {
+            distance_margin: 1.0,
+            evidence_decay: 1.0,
+            mirror_threshold: 1.0,
+            ..Default::default()
+        };
+        let ground_to_field = Isometry2::from_parts(vector![-2.0, 1.0], 0.0);
+        let mut obstacles = [None; MAXIMUM_NUMBER_OF_SHARED_OBSTACLES];
+        obstacles[0] = Some(SharedObstacle::new(point![2.0, -1.0]));
+        let observations = SymmetryObservations {
+            own_ball: None,
+            teammate_messages: vec![HulkMessage {
+                obstacles,
+                ..Default::default()
+            }],
+            must_be_in_own_half: false,
+        };
+
+        let mut evidence = SymmetryEvidence::default();
+        let checks: Vec<_> = (0..10)
+            .map(|_| {
+                let votes = symmetry_votes(ground_to_field, &observations, &parameters);
+                evidence.accumulate(&votes, &parameters)
+            })
+            .collect();
+
+        assert!(checks
+            .iter()
+            .all(|check| check.mirrored_votes == 0 && check.decision == SymmetryDecision::Keep));
+    }
+
+    #[test]
+    fn pose_in_opponent_half_during_set_votes_for_mirrored_pose() {
+        let parameters = SymmetryResolutionParameters {
+            own_half_margin: 0.3,
+            ..Default::default()
+        };
+        let ground_to_field = Isometry2::from_parts(vector![3.0, 1.0], PI);
+        let observations = |must_be_in_own_half| SymmetryObservations {
+            own_ball: None,
+            teammate_messages: vec![],
+            must_be_in_own_half,
+        };
+
+        let votes_in_set = symmetry_votes(ground_to_field, &observations(true), &parameters);
+        let votes_in_playing = symmetry_votes(ground_to_field, &observations(false), &parameters);
+
+        assert_eq!(votes_in_set, vec![Vote::Mirrored]);
+        assert!(votes_in_playing.is_empty());
+    }
+
+    #[test]
+    fn evidence_mirrors_once_threshold_is_reached() {
+        let parameters = SymmetryResolutionParameters {
+            evidence_decay: 1.0,
+            mirror_threshold: 2.0,
+            ..Default::default()
+        };
+        let mut evidence = SymmetryEvidence::default();
+
+        let first = evidence.accumulate(&[Vote::Mirrored], &parameters);
+        let second = evidence.accumulate(&[Vote::Mirrored, Vote::Mirrored], &parameters);
+        let third = evidence.accumulate(&[Vote::Consistent], &parameters);
+
+        assert_eq!(first.decision, SymmetryDecision::Keep);
+        assert_eq!(second.decision, SymmetryDecision::Mirror);
+        assert_eq!(second.evidence, 0.0);
+        assert_eq!(third.evidence, 0.0);
+    }
+}
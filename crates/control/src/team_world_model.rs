@@ -0,0 +1,413 @@
+use std::time::{Duration, SystemTime};
+
+use color_eyre::Result;
+use context_attribute::context;
+use coordinate_systems::{Field, Ground};
+use framework::{MainOutput, PerceptionInput};
+use linear_algebra::{distance, Isometry2, Point2, Vector2};
+use nalgebra::{Matrix2, Rotation2};
+use serde::{Deserialize, Serialize};
+use spl_network_messages::{PlayerNumber, MAXIMUM_NUMBER_OF_SHARED_OBSTACLES};
+use types::{
+    ball_position::BallPosition,
+    cycle_time::CycleTime,
+    messages::IncomingMessage,
+    obstacles::{Obstacle, ObstacleKind},
+    parameters::TeamWorldModelParameters,
+    players::Players,
+    team_ball::TeamBall,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct TeamWorldModel {
+    ball_observations: Players<Option<BallObservation>>,
+    teammate_positions: Players<Option<TeammatePosition>>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+struct BallObservation {
+    position: Point2<Field>,
+    /// Only known for the own observation, teammates do not share ball velocities
+    velocity: Option<Vector2<Field>>,
+    covariance: Matrix2<f32>,
+    observed_at: SystemTime,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+struct TeammatePosition {
+    position: Point2<Field>,
+    received_at: SystemTime,
+}
+
+#[context]
+pub struct CreationContext {}
+
+#[context]
+pub struct CycleContext {
+    ball_position: Input<Option<BallPosition<Ground>>, "ball_position?">,
+    cycle_time: Input<CycleTime, "cycle_time">,
+    ground_to_field: Input<Option<Isometry2<Ground, Field>>, "ground_to_field?">,
+    ground_to_field_standard_deviation: Input<Option<f32>, "ground_to_field_standard_deviation?">,
+    obstacles: Input<Vec<Obstacle>, "obstacles">,
+    network_message: PerceptionInput<Option<IncomingMessage>, "SplNetwork", "filtered_message?">,
+
+    player_number: Parameter<PlayerNumber, "player_number">,
+    parameters: Parameter<TeamWorldModelParameters, "team_world_model">,
+
+    shared_obstacles: CyclerState<Vec<Point2<Field>>, "shared_obstacles">,
+}
+
+#[context]
+#[derive(Default)]
+pub struct MainOutputs {
+    pub team_ball_hypothesis: MainOutput<Option<TeamBall>>,
+}
+
+impl TeamWorldModel {
+    pub fn new(_context: CreationContext) -> Result<Self> {
+        Ok(Self {
+            ball_observations: Default::default(),
+            teammate_positions: Default::default(),
+        })
+    }
+
+    pub fn cycle(&mut self, mut context: CycleContext) -> Result<MainOutputs> {
+        let cycle_start_time = context.cycle_time.start_time;
+        let parameters = context.parameters;
+
+        let messages =
+            context
+                .network_message
+                .persistent
+                .iter()
+                .flat_map(|(receive_time, messages)| {
+                    messages.iter().filter_map(move |message| match message {
+                        Some(IncomingMessage::Spl(message)) => Some((*receive_time, message)),
+                        _ => None,
+                    })
+                });
+        for (receive_time, message) in messages {
+            if message.player_number != *context.player_number {
+                self.teammate_positions[message.player_number] = Some(TeammatePosition {
+                    position: message.pose.position(),
+                    received_at: receive_time,
+                });
+            }
+            if message.fallen {
+                continue;
+            }
+            // relayed team balls already contain the observations of this robot and its teammates
+            if message.ball_is_relayed {
+                continue;
+            }
+            if let Some(ball) = message.ball_position {
+                self.ball_observations[message.player_number] = Some(BallObservation {
+                    position: ball.position,
+                    velocity: None,
+                    covariance: observation_covariance(
+                        message.pose.position(),
+                        message.pose_standard_deviation,
+                        ball.position,
+                        parameters,
+                    ),
+                    observed_at: receive_time.checked_sub(ball.age).unwrap_or(receive_time),
+                });
+            }
+        }
+
+        if let (Some(ball), Some(ground_to_field)) =
+            (context.ball_position, context.ground_to_field)
+        {
+            let position = *ground_to_field * ball.position;
+            self.ball_observations[*context.player_number] = Some(BallObservation {
+                position,
+                velocity: Some(*ground_to_field * ball.velocity),
+                covariance: observation_covariance(
+                    ground_to_field.as_pose().position(),
+                    context
+                        .ground_to_field_standard_deviation
+                        .copied()
+                        .unwrap_or_default(),
+                    position,
+                    parameters,
+                ),
+                observed_at: ball.last_seen,
+            });
+        }
+
+        let observations: Vec<_> = self
+            .ball_observations
+            .iter()
+            .filter_map(|(_player, observation)| *observation)
+            .filter_map(|observation| {
+                let age = cycle_start_time
+                    .duration_since(observation.observed_at)
+                    .unwrap_or(Duration::ZERO);
+                (age <= parameters.ball_observation_timeout)
+                    .then(|| aged_observation(observation, age, parameters.ball_age_noise))
+            })
+            .collect();
+        let team_ball_hypothesis =
+            fuse_consistent_observations(&observations, parameters.ball_consistency_threshold);
+
+        let teammate_positions: Vec<_> = self
+            .teammate_positions
+            .iter()
+            .filter_map(|(_player, teammate)| *teammate)
+            .filter(|teammate| {
+                cycle_start_time
+                    .duration_since(teammate.received_at)
+                    .unwrap_or(Duration::ZERO)
+                    <= parameters.teammate_position_timeout
+            })
+            .map(|teammate| teammate.position)
+            .collect();
+        *context.shared_obstacles = context
+            .ground_to_field
+            .map(|ground_to_field| {
+                shared_obstacles(
+                    context.obstacles,
+                    *ground_to_field,
+                    &teammate_positions,
+                    parameters.teammate_obstacle_distance,
+                )
+            })
+            .unwrap_or_default();
+
+        Ok(MainOutputs {
+            team_ball_hypothesis: team_ball_hypothesis.into(),
+        })
+    }
+}
+
+/// Ball covariance from the observer's localization uncertainty and the distance to the ball
+///
+/// The uncertainty along the line of sight grows faster with distance than the one across it.
+fn observation_covariance(
+    observer_position: Point2<Field>,
+    observer_standard_deviation: f32,
+    ball_position: Point2<Field>,
+    parameters: &TeamWorldModelParameters,
+) -> Matrix2<f32> {
+    let line_of_sight = ball_position - observer_position;
+    let ball_distance = line_of_sight.norm();
+    let rotation = Rotation2::new(line_of_sight.y().atan2(line_of_sight.x()));
+    let distance_variance = (parameters.ball_distance_noise_factor * ball_distance).powi(2);
+    let bearing_variance = (parameters.ball_bearing_noise_factor * ball_distance).powi(2);
+    let measurement_covariance = rotation.matrix()
+        * Matrix2::from_diagonal(&[distance_variance, bearing_variance].into())
+        * rotation.matrix().transpose();
+    measurement_covariance + Matrix2::identity() * observer_standard_deviation.powi(2)
+}
+
+/// Grows the covariance with the age of the observation since the ball may have moved
+fn aged_observation(
+    observation: BallObservation,
+    age: Duration,
+    age_noise: f32,
+) -> BallObservation {
+    BallObservation {
+        covariance: observation.covariance
+            + Matrix2::identity() * (age_noise * age.as_secs_f32()).powi(2),
+        ..observation
+    }
+}
+
+/// Fuses the largest group of mutually consistent observations
+///
+/// Each observation is tried as seed, observations are consistent with the seed if their
+/// Mahalanobis distance is below the threshold. Outliers, e.g. from mislocalized teammates, thereby
+/// do not pull the team ball away from the majority.
+fn fuse_consistent_observations(
+    observations: &[BallObservation],
+    consistency_threshold: f32,
+) -> Option<TeamBall> {
+    observations
+        .iter()
+        .map(|seed| {
+            observations
+                .iter()
+                .filter(|observation| {
+                    mahalanobis_distance(seed, observation)
+                        .is_some_and(|distance| distance <= consistency_threshold)
+                })
+                .copied()
+                .collect::<Vec<_>>()
+        })
+        .filter_map(|group| fuse(&group))
+        .max_by(|first, second| {
+            first
+                .number_of_observations
+                .cmp(&second.number_of_observations)
+                .then(
+                    second
+                        .covariance
+                        .trace()
+                        .total_cmp(&first.covariance.trace()),
+                )
+        })
+}
+
+fn mahalanobis_distance(first: &BallObservation, second: &BallObservation) -> Option<f32> {
+    let difference = second.position - first.position;
+    let information = (first.covariance + second.covariance).try_inverse()?;
+    Some(
+        (difference.inner.transpose() * information * difference.inner)
+            .x
+            .max(0.0)
+            .sqrt(),
+    )
+}
+
+/// Information filter fusion of independent observations
+fn fuse(observations: &[BallObservation]) -> Option<TeamBall> {
+    let mut information = Matrix2::zeros();
+    let mut information_vector = nalgebra::Vector2::zeros();
+    for observation in observations {
+        let observation_information = observation.covariance.try_inverse()?;
+        information += observation_information;
+        information_vector += observation_information * observation.position.inner.coords;
+    }
+    let covariance = information.try_inverse()?;
+    let velocities: Vec<_> = observations
+        .iter()
+        .filter_map(|observation| observation.velocity)
+        .collect();
+    let velocity = if velocities.is_empty() {
+        Vector2::zeros()
+    } else {
+        velocities.iter().copied().sum::<Vector2<Field>>() / velocities.len() as f32
+    };
+    let last_seen = observations
+        .iter()
+        .map(|observation| observation.observed_at)
+        .max()?;
+    Some(TeamBall {
+        position: Point2::wrap((covariance * information_vector).into()),
+        velocity,
+        covariance,
+        last_seen,
+        number_of_observations: observations.len(),
+    })
+}
+
+/// Robot obstacles closest to the robot, the others are not seen reliably enough to be shared
+///
+/// Obstacles at the positions of teammates are the teammates themselves, they are known to the
+/// team from their own messages.
+fn shared_obstacles(
+    obstacles: &[Obstacle],
+    ground_to_field: Isometry2<Ground, Field>,
+    teammate_positions: &[Point2<Field>],
+    teammate_obstacle_distance: f32,
+) -> Vec<Point2<Field>> {
+    let mut robot_obstacles: Vec<_> = obstacles
+        .iter()
+        .filter(|obstacle| obstacle.kind == ObstacleKind::Robot)
+        .map(|obstacle| obstacle.position)
+        .filter(|position| {
+            let position_in_field = ground_to_field * *position;
+            teammate_positions.iter().all(|teammate_position| {
+                distance(*teammate_position, position_in_field) > teammate_obstacle_distance
+            })
+        })
+        .collect();
+    robot_obstacles.sort_by(|first, second| {
+        let first_distance = distance(*first, Point2::origin());
+        let second_distance = distance(*second, Point2::origin());
+        first_distance.total_cmp(&second_distance)
+    });
+    robot_obstacles
+        .into_iter()
+        .take(MAXIMUM_NUMBER_OF_SHARED_OBSTACLES)
+        .map(|position| ground_to_field * position)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::{point, vector};
+
+    use super::*;
+
+    fn observation(position: Point2<Field>, variance: f32) -> BallObservation {
+        BallObservation {
+            position,
+            velocity: None,
+            covariance: Matrix2::identity() * variance,
+            observed_at: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn equally_certain_observations_are_averaged() {
+        let observations = [
+            observation(point![1.0, 0.0], 0.1),
+            observation(point![1.2, 0.2], 0.1),
+        ];
+
+        let team_ball = fuse_consistent_observations(&observations, 3.0).unwrap();
+
+        assert_eq!(team_ball.number_of_observations, 2);
+        assert!(distance(team_ball.position, point![1.1, 0.1]) < 1e-5);
+        assert!((team_ball.covariance[(0, 0)] - 0.05).abs() < 1e-5);
+    }
+
+    #[test]
+    fn inconsistent_observation_is_ignored() {
+        let observations = [
+            observation(point![1.0, 0.0], 0.01),
+            observation(point![1.1, 0.0], 0.01),
+            observation(point![-1.0, 0.0], 0.01),
+        ];
+
+        let team_ball = fuse_consistent_observations(&observations, 3.0).unwrap();
+
+        assert_eq!(team_ball.number_of_observations, 2);
+        assert!(distance(team_ball.position, point![1.05, 0.0]) < 1e-5);
+    }
+
+    #[test]
+    fn fused_velocity_only_uses_known_velocities() {
+        let observations = [
+            BallObservation {
+                velocity: Some(vector![1.0, 0.0]),
+                ..observation(point![1.0, 0.0], 0.1)
+            },
+            observation(point![1.0, 0.0], 0.1),
+        ];
+
+        let team_ball = fuse_consistent_observations(&observations, 3.0).unwrap();
+
+        assert_eq!(team_ball.velocity, vector![1.0, 0.0]);
+    }
+
+    #[test]
+    fn teammates_are_not_shared_as_obstacles() {
+        let ground_to_field = Isometry2::from_parts(vector![1.0, 0.0], 0.0);
+        let obstacles = [
+            Obstacle::robot(point![1.0, 0.0], 0.1, 0.1),
+            Obstacle::robot(point![2.0, 1.0], 0.1, 0.1),
+        ];
+
+        let shared_obstacles =
+            shared_obstacles(&obstacles, ground_to_field, &[point![3.1, 1.0]], 0.4);
+
+        assert_eq!(shared_obstacles, [point![2.0, 0.0]]);
+    }
+
+    #[test]
+    fn uncertainty_grows_along_the_line_of_sight() {
+        let parameters = TeamWorldModelParameters {
+            ball_distance_noise_factor: 0.1,
+            ball_bearing_noise_factor: 0.05,
+            ..Default::default()
+        };
+
+        let covariance =
+            observation_covariance(point![0.0, 0.0], 0.0, point![0.0, 2.0], &parameters);
+
+        assert!((covariance[(1, 1)] - 0.04).abs() < 1e-5);
+        assert!((covariance[(0, 0)] - 0.01).abs() < 1e-5);
+    }
+}
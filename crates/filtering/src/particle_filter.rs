@@ -0,0 +1,210 @@
+use std::{collections::HashSet, f32::consts::PI};
+
+use rand::Rng;
+use types::{localization::Particle, parameters::KldSamplingParameters};
+
+/// Draws particles proportionally to their weights with an adaptive number of samples
+///
+/// Sampling stops as soon as enough particles are drawn to approximate the distribution within the
+/// configured Kullback-Leibler divergence, given the number of occupied histogram bins (KLD
+/// sampling, Fox 2003). Concentrated distributions thereby need fewer particles than spread ones.
+pub fn resample_adaptively(
+    particles: &[Particle],
+    parameters: &KldSamplingParameters,
+    random_number_generator: &mut impl Rng,
+) -> Vec<Particle> {
+    let cumulative_weights: Vec<f32> = particles
+        .iter()
+        .scan(0.0, |sum, particle| {
+            *sum += particle.weight;
+            Some(*sum)
+        })
+        .collect();
+    let Some(&total_weight) = cumulative_weights.last() else {
+        return Vec::new();
+    };
+    if total_weight <= 0.0 {
+        return particles.to_vec();
+    }
+
+    let mut resampled_particles = Vec::new();
+    let mut occupied_bins = HashSet::new();
+    while resampled_particles.len() < parameters.maximum_number_of_particles {
+        let sample = random_number_generator.gen_range(0.0..total_weight);
+        let index = cumulative_weights
+            .partition_point(|&cumulative_weight| cumulative_weight <= sample)
+            .min(particles.len() - 1);
+        let particle = particles[index];
+        occupied_bins.insert(bin(&particle, parameters));
+        resampled_particles.push(particle);
+
+        let required_number_of_particles =
+            kld_sample_count(occupied_bins.len(), parameters.error, parameters.quantile)
+                .max(parameters.minimum_number_of_particles);
+        if resampled_particles.len() >= required_number_of_particles {
+            break;
+        }
+    }
+
+    let weight = 1.0 / resampled_particles.len() as f32;
+    for particle in resampled_particles.iter_mut() {
+        particle.weight = weight;
+    }
+    resampled_particles
+}
+
+/// Number of particles which would carry the same information if all had equal weights
+///
+/// Drops towards one when few particles carry most of the weight, resampling is only needed then.
+pub fn effective_sample_size(particles: &[Particle]) -> f32 {
+    let (weight_sum, squared_weight_sum) =
+        particles
+            .iter()
+            .fold((0.0, 0.0), |(weight_sum, squared_weight_sum), particle| {
+                (
+                    weight_sum + particle.weight,
+                    squared_weight_sum + particle.weight.powi(2),
+                )
+            });
+    if squared_weight_sum <= 0.0 {
+        return 0.0;
+    }
+    weight_sum.powi(2) / squared_weight_sum
+}
+
+/// Number of samples needed to bound the Kullback-Leibler divergence with `number_of_bins` occupied
+/// bins to `error` with the probability given by the standard normal `quantile`
+pub fn kld_sample_count(number_of_bins: usize, error: f32, quantile: f32) -> usize {
+    if number_of_bins < 2 {
+        return 0;
+    }
+    let degrees_of_freedom = (number_of_bins - 1) as f32;
+    let factor = 2.0 / (9.0 * degrees_of_freedom);
+    let wilson_hilferty = 1.0 - factor + factor.sqrt() * quantile;
+    (degrees_of_freedom / (2.0 * error) * wilson_hilferty.powi(3)).ceil() as usize
+}
+
+fn bin(particle: &Particle, parameters: &KldSamplingParameters) -> (i32, i32, i32) {
+    let position = particle.pose.position();
+    let angle = particle.pose.angle() + PI;
+    (
+        (position.x() / parameters.translation_bin_size).floor() as i32,
+        (position.y() / parameters.translation_bin_size).floor() as i32,
+        (angle / parameters.rotation_bin_size).floor() as i32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use linear_algebra::{vector, Pose2};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const SEED: u64 = 42;
+
+    fn parameters() -> KldSamplingParameters {
+        KldSamplingParameters {
+            minimum_number_of_particles: 10,
+            maximum_number_of_particles: 500,
+            translation_bin_size: 0.25,
+            rotation_bin_size: 0.3,
+            error: 0.05,
+            quantile: 2.33,
+        }
+    }
+
+    fn particle(x: f32, y: f32, weight: f32) -> Particle {
+        Particle {
+            pose: Pose2::new(vector![x, y], 0.0),
+            weight,
+        }
+    }
+
+    #[test]
+    fn kld_sample_count_grows_with_number_of_bins() {
+        assert_eq!(kld_sample_count(0, 0.05, 2.33), 0);
+        assert_eq!(kld_sample_count(1, 0.05, 2.33), 0);
+
+        let few_bins = kld_sample_count(2, 0.05, 2.33);
+        let many_bins = kld_sample_count(50, 0.05, 2.33);
+
+        assert!(few_bins > 0);
+        assert!(many_bins > few_bins);
+        assert!(kld_sample_count(50, 0.1, 2.33) < many_bins);
+    }
+
+    #[test]
+    fn kld_sample_count_matches_wilson_hilferty_approximation() {
+        // k = 2: 1 / (2 * 0.05) * (1 - 2/9 + sqrt(2/9) * 2.33)^3 = 10 * 1.876^3 = 66.04
+        assert_eq!(kld_sample_count(2, 0.05, 2.33), 67);
+    }
+
+    #[test]
+    fn resampling_draws_only_weighted_particles_and_normalizes_weights() {
+        let particles = vec![
+            particle(0.0, 0.0, 0.0),
+            particle(1.0, 0.0, 1.0),
+            particle(2.0, 0.0, 0.0),
+        ];
+        let mut random_number_generator = StdRng::seed_from_u64(SEED);
+
+        let resampled =
+            resample_adaptively(&particles, &parameters(), &mut random_number_generator);
+
+        assert_eq!(resampled.len(), parameters().minimum_number_of_particles);
+        for particle in &resampled {
+            assert_relative_eq!(particle.pose.position().x(), 1.0);
+            assert_relative_eq!(particle.weight, 1.0 / resampled.len() as f32);
+        }
+    }
+
+    #[test]
+    fn spread_particles_need_more_samples_than_concentrated_ones() {
+        let concentrated = vec![particle(0.0, 0.0, 1.0); 100];
+        let spread: Vec<_> = (0..100)
+            .map(|index| particle((index % 10) as f32, (index / 10) as f32, 1.0))
+            .collect();
+        let mut random_number_generator = StdRng::seed_from_u64(SEED);
+
+        let resampled_concentrated =
+            resample_adaptively(&concentrated, &parameters(), &mut random_number_generator);
+        let resampled_spread =
+            resample_adaptively(&spread, &parameters(), &mut random_number_generator);
+
+        assert_eq!(
+            resampled_concentrated.len(),
+            parameters().minimum_number_of_particles
+        );
+        assert!(resampled_spread.len() > resampled_concentrated.len());
+        assert!(resampled_spread.len() <= parameters().maximum_number_of_particles);
+    }
+
+    #[test]
+    fn particles_without_weight_are_kept() {
+        let particles = vec![particle(0.0, 0.0, 0.0), particle(1.0, 0.0, 0.0)];
+        let mut random_number_generator = StdRng::seed_from_u64(SEED);
+
+        let resampled =
+            resample_adaptively(&particles, &parameters(), &mut random_number_generator);
+
+        assert_eq!(resampled.len(), 2);
+        assert!(resample_adaptively(&[], &parameters(), &mut random_number_generator).is_empty());
+    }
+
+    #[test]
+    fn effective_sample_size_drops_with_uneven_weights() {
+        let even = vec![particle(0.0, 0.0, 0.25); 4];
+        let uneven = vec![
+            particle(0.0, 0.0, 1.0),
+            particle(0.0, 0.0, 0.0),
+            particle(0.0, 0.0, 0.0),
+            particle(0.0, 0.0, 0.0),
+        ];
+
+        assert_relative_eq!(effective_sample_size(&even), 4.0);
+        assert_relative_eq!(effective_sample_size(&uneven), 1.0);
+        assert_relative_eq!(effective_sample_size(&[]), 0.0);
+    }
+}
@@ -10,6 +10,7 @@ mod perception_databases;
 mod perception_input;
 mod recording_index;
 mod recording_trigger;
+mod recording_writer;
 
 pub use additional_output::{should_be_filled, AdditionalOutput};
 pub use future_queue::{future_queue, Consumer, Item, Producer, Update, Updates};
@@ -21,5 +22,8 @@ pub use panic::deserialize_not_implemented;
 pub use parameters::Parameters;
 pub use perception_databases::PerceptionDatabases;
 pub use perception_input::PerceptionInput;
-pub use recording_index::{RecordingFrame, RecordingIndex, Timing};
-pub use recording_trigger::RecordingTrigger;
+pub use recording_index::{
+    RecordingFrame, RecordingIndex, RecordingMetadata, Timing, RECORDING_FORMAT_VERSION,
+};
+pub use recording_trigger::{EventRecording, RecordingTrigger};
+pub use recording_writer::RecordingWriter;
@@ -1,11 +1,29 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
 use serde::Deserialize;
 
+use crate::EventRecording;
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Parameters {
     pub communication_addresses: Option<String>,
+    /// Pre-shared key of clients which may only subscribe to outputs and read parameters
+    #[serde(default)]
+    pub communication_read_only_key: Option<String>,
+    /// Pre-shared key of clients which may additionally modify parameters
+    #[serde(default)]
+    pub communication_admin_key: Option<String>,
     pub recording_intervals: HashMap<String, usize>,
+    /// Cycler instances which additionally record camera, microphone, network and sensor reads to
+    /// re-execute their setup nodes during replay
+    #[serde(default)]
+    pub record_hardware_inputs: HashSet<String>,
+    /// Cycler instances which only record the cycles around events triggered by nodes
+    #[serde(default)]
+    pub event_recordings: HashMap<String, EventRecording>,
     pub hardware_parameters: PathBuf,
     pub parameters_directory: PathBuf,
 }
@@ -1,91 +1,113 @@
 use std::{
-    fs::File,
-    io::{self, Read, Seek, SeekFrom},
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
     path::Path,
     time::{Duration, SystemTime},
 };
 
-use bincode::{deserialize_from, Error};
-use color_eyre::eyre::WrapErr;
+use bincode::{deserialize_from, serialize_into, Error};
+use color_eyre::eyre::{bail, WrapErr};
+use serde::{Deserialize, Serialize};
+
+/// Version of the recording format written by [`crate::RecordingWriter`]
+///
+/// Recordings without a file header are treated as legacy recordings (a plain sequence of frames)
+/// and are always indexed by scanning the whole file.
+///
+/// Version 2 starts each frame with whether hardware inputs are recorded.
+pub const RECORDING_FORMAT_VERSION: u32 = 2;
+/// First version whose frames start with whether hardware inputs are recorded
+const HARDWARE_INPUTS_FLAG_VERSION: u32 = 2;
+
+const FILE_MAGIC: [u8; 8] = *b"HULKREC\0";
+const FOOTER_MAGIC: [u8; 8] = *b"HULKIDX\0";
+// magic + version
+const FILE_HEADER_LENGTH: u64 = 8 + 4;
+// footer offset + magic
+const TRAILER_LENGTH: u64 = 8 + 8;
 
 #[derive(Debug)]
 pub struct RecordingIndex {
     file: File,
+    /// Format version, `0` for legacy recordings
+    version: u32,
+    metadata: Option<RecordingMetadata>,
     frames: Vec<RecordingFrameMetadata>,
 }
 
 impl RecordingIndex {
     pub fn read_from(recording_file: impl AsRef<Path>) -> color_eyre::Result<Self> {
-        let file = File::open(&recording_file)
-            .wrap_err_with(|| format!("failed to open {}", recording_file.as_ref().display()))?;
-        Self::collect_frames(file).wrap_err("failed to collect frames")
-    }
-
-    fn collect_frames(mut recording_file: File) -> color_eyre::Result<Self> {
-        let mut frames = Vec::new();
-
-        recording_file
+        let recording_file = recording_file.as_ref();
+        let mut file = File::open(recording_file)
+            .wrap_err_with(|| format!("failed to open {}", recording_file.display()))?;
+        let file_length = file
             .seek(SeekFrom::End(0))
             .wrap_err("failed to seek to end of file")?;
-        let file_length = recording_file
-            .stream_position()
-            .wrap_err("failed to get stream position of end of file")?;
-        recording_file.rewind().wrap_err("failed to rewind file")?;
-
-        let mut offset = 0;
-        while offset < file_length {
-            let Some(timestamp) =
-                end_of_file_error_as_option(deserialize_from(&mut recording_file))
-                    .wrap_err("failed to deserialize timestamp")?
-            else {
-                eprintln!("unexpected end of file of recording file while deserializing timestamp");
-                break;
-            };
-            let Some(duration) = end_of_file_error_as_option(deserialize_from(&mut recording_file))
-                .wrap_err("failed to deserialize duration")?
-            else {
-                eprintln!("unexpected end of file of recording file while deserializing duration");
-                break;
-            };
-            let Some(length) = end_of_file_error_as_option(deserialize_from(&mut recording_file))
-                .wrap_err("failed to deserialize data length")?
-            else {
-                eprintln!("unexpected end of file of recording file while deserializing length");
-                break;
-            };
-            let header_length = recording_file
-                .stream_position()
-                .wrap_err("failed to get stream position")?
-                - offset;
-            recording_file
-                .seek(SeekFrom::Current(length as i64))
-                .wrap_err("failed to seek to end of data")?;
-            if offset + header_length + length as u64 > file_length {
-                eprintln!("unexpected end of file of recording file");
-                break;
-            }
-            frames.push(RecordingFrameMetadata {
-                timing: Timing {
-                    timestamp,
-                    duration,
-                },
-                offset: offset.try_into().unwrap(),
-                header_offset: header_length.try_into().unwrap(),
-                length,
+
+        let Some(version) =
+            read_file_header(&mut file, file_length).wrap_err("failed to read file header")?
+        else {
+            let (frames, _end_of_frames) =
+                scan_frames(&mut file, 0, file_length).wrap_err("failed to collect frames")?;
+            file.rewind().wrap_err("failed to rewind file")?;
+            return Ok(Self {
+                file,
+                version: 0,
+                metadata: None,
+                frames,
+            });
+        };
+        if version > RECORDING_FORMAT_VERSION {
+            bail!(
+                "recording format version {version} of {} is newer than supported version {RECORDING_FORMAT_VERSION}",
+                recording_file.display()
+            );
+        }
+
+        if let Some(footer) =
+            read_footer(&mut file, file_length).wrap_err("failed to read footer")?
+        {
+            file.rewind().wrap_err("failed to rewind file")?;
+            return Ok(Self {
+                file,
+                version,
+                metadata: Some(footer.metadata),
+                frames: footer.frames,
             });
-            offset = recording_file
-                .stream_position()
-                .wrap_err("failed to get stream position")?;
         }
 
-        recording_file.rewind().wrap_err("failed to rewind file")?;
+        eprintln!(
+            "recording {} has no frame index, scanning and repairing it",
+            recording_file.display()
+        );
+        let (frames, end_of_frames) = scan_frames(&mut file, FILE_HEADER_LENGTH, file_length)
+            .wrap_err("failed to collect frames")?;
+        let metadata = RecordingMetadata {
+            cycler_instance: recording_file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        if let Err(error) = repair(recording_file, end_of_frames, &metadata, &frames) {
+            eprintln!(
+                "failed to repair recording {}: {error:?}",
+                recording_file.display()
+            );
+        }
+        file.rewind().wrap_err("failed to rewind file")?;
 
         Ok(Self {
-            file: recording_file,
+            file,
+            version,
+            metadata: Some(metadata),
             frames,
         })
     }
 
+    pub fn metadata(&self) -> Option<&RecordingMetadata> {
+        self.metadata.as_ref()
+    }
+
     pub fn number_of_frames(&self) -> usize {
         self.frames.len()
     }
@@ -94,14 +116,18 @@ impl RecordingIndex {
         &mut self,
         timestamp: SystemTime,
     ) -> color_eyre::Result<Option<RecordingFrame>> {
-        let frame = match self
+        let number_of_frames_up_to_timestamp = self
             .frames
-            .iter()
-            .rev()
-            .find(|frame| frame.timing.timestamp <= timestamp)
-        {
-            Some(frame) => frame,
-            None => return Ok(None),
+            .partition_point(|frame| frame.timing.timestamp <= timestamp);
+        match number_of_frames_up_to_timestamp.checked_sub(1) {
+            Some(index) => self.read_frame(index),
+            None => Ok(None),
+        }
+    }
+
+    pub fn read_frame(&mut self, index: usize) -> color_eyre::Result<Option<RecordingFrame>> {
+        let Some(frame) = self.frames.get(index) else {
+            return Ok(None);
         };
         self.file
             .seek(SeekFrom::Start(
@@ -109,9 +135,14 @@ impl RecordingIndex {
             ))
             .wrap_err("failed to seek to frame")?;
         let mut data = Vec::new();
-        data.resize_with(frame.length, Default::default);
+        if self.version < HARDWARE_INPUTS_FLAG_VERSION {
+            // older frames never contain hardware inputs, prepend the missing flag
+            serialize_into(&mut data, &false).wrap_err("failed to prepend hardware inputs flag")?;
+        }
+        let header_length = data.len();
+        data.resize_with(header_length + frame.length, Default::default);
         self.file
-            .read_exact(&mut data)
+            .read_exact(&mut data[header_length..])
             .wrap_err("failed to read from recording file")?;
         Ok(Some(RecordingFrame {
             timing: frame.timing,
@@ -132,12 +163,17 @@ impl RecordingIndex {
     }
 }
 
-#[derive(Debug)]
-struct RecordingFrameMetadata {
-    timing: Timing,
-    offset: usize,
-    header_offset: usize,
-    length: usize,
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RecordingMetadata {
+    pub cycler_instance: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct RecordingFrameMetadata {
+    pub(crate) timing: Timing,
+    pub(crate) offset: usize,
+    pub(crate) header_offset: usize,
+    pub(crate) length: usize,
 }
 
 #[derive(Debug)]
@@ -146,12 +182,179 @@ pub struct RecordingFrame {
     pub data: Vec<u8>,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl RecordingFrame {
+    pub fn new(timestamp: SystemTime, duration: Duration, data: Vec<u8>) -> Self {
+        Self {
+            timing: Timing {
+                timestamp,
+                duration,
+            },
+            data,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Timing {
     pub timestamp: SystemTime,
     pub duration: Duration,
 }
 
+#[derive(Deserialize)]
+struct Footer {
+    metadata: RecordingMetadata,
+    frames: Vec<RecordingFrameMetadata>,
+}
+
+#[derive(Serialize)]
+struct FooterReference<'a> {
+    metadata: &'a RecordingMetadata,
+    frames: &'a [RecordingFrameMetadata],
+}
+
+pub(crate) fn write_file_header(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(&FILE_MAGIC)?;
+    writer.write_all(&RECORDING_FORMAT_VERSION.to_le_bytes())
+}
+
+pub(crate) fn write_footer(
+    writer: &mut impl Write,
+    footer_offset: u64,
+    metadata: &RecordingMetadata,
+    frames: &[RecordingFrameMetadata],
+) -> color_eyre::Result<()> {
+    serialize_into(&mut *writer, &FooterReference { metadata, frames })
+        .wrap_err("failed to serialize frame index")?;
+    writer
+        .write_all(&footer_offset.to_le_bytes())
+        .wrap_err("failed to write footer offset")?;
+    writer
+        .write_all(&FOOTER_MAGIC)
+        .wrap_err("failed to write footer magic")
+}
+
+/// Returns the format version or `None` for legacy recordings without file header
+fn read_file_header(file: &mut File, file_length: u64) -> color_eyre::Result<Option<u32>> {
+    if file_length < FILE_HEADER_LENGTH {
+        return Ok(None);
+    }
+    file.rewind().wrap_err("failed to rewind file")?;
+    let mut magic = [0; 8];
+    file.read_exact(&mut magic)
+        .wrap_err("failed to read file magic")?;
+    if magic != FILE_MAGIC {
+        return Ok(None);
+    }
+    let mut version = [0; 4];
+    file.read_exact(&mut version)
+        .wrap_err("failed to read format version")?;
+    Ok(Some(u32::from_le_bytes(version)))
+}
+
+fn read_footer(file: &mut File, file_length: u64) -> color_eyre::Result<Option<Footer>> {
+    if file_length < FILE_HEADER_LENGTH + TRAILER_LENGTH {
+        return Ok(None);
+    }
+    let trailer_offset = file_length - TRAILER_LENGTH;
+    file.seek(SeekFrom::Start(trailer_offset))
+        .wrap_err("failed to seek to trailer")?;
+    let mut footer_offset = [0; 8];
+    file.read_exact(&mut footer_offset)
+        .wrap_err("failed to read footer offset")?;
+    let footer_offset = u64::from_le_bytes(footer_offset);
+    let mut magic = [0; 8];
+    file.read_exact(&mut magic)
+        .wrap_err("failed to read footer magic")?;
+    if magic != FOOTER_MAGIC || footer_offset < FILE_HEADER_LENGTH || footer_offset > trailer_offset
+    {
+        return Ok(None);
+    }
+    file.seek(SeekFrom::Start(footer_offset))
+        .wrap_err("failed to seek to footer")?;
+    let footer = deserialize_from(io::BufReader::new(&mut *file))
+        .wrap_err("failed to deserialize frame index")?;
+    Ok(Some(footer))
+}
+
+/// Collects all complete frames between `start` and `file_length`
+///
+/// Returns the frames and the offset after the last complete frame.
+fn scan_frames(
+    file: &mut File,
+    start: u64,
+    file_length: u64,
+) -> color_eyre::Result<(Vec<RecordingFrameMetadata>, u64)> {
+    let mut frames = Vec::new();
+    file.seek(SeekFrom::Start(start))
+        .wrap_err("failed to seek to first frame")?;
+
+    let mut offset = start;
+    while offset < file_length {
+        let Some(timestamp) = end_of_file_error_as_option(deserialize_from(&mut *file))
+            .wrap_err("failed to deserialize timestamp")?
+        else {
+            eprintln!("unexpected end of file of recording file while deserializing timestamp");
+            break;
+        };
+        let Some(duration) = end_of_file_error_as_option(deserialize_from(&mut *file))
+            .wrap_err("failed to deserialize duration")?
+        else {
+            eprintln!("unexpected end of file of recording file while deserializing duration");
+            break;
+        };
+        let Some(length) = end_of_file_error_as_option(deserialize_from(&mut *file))
+            .wrap_err("failed to deserialize data length")?
+        else {
+            eprintln!("unexpected end of file of recording file while deserializing length");
+            break;
+        };
+        let header_length = file
+            .stream_position()
+            .wrap_err("failed to get stream position")?
+            - offset;
+        if offset + header_length + length as u64 > file_length {
+            eprintln!("unexpected end of file of recording file");
+            break;
+        }
+        file.seek(SeekFrom::Current(length as i64))
+            .wrap_err("failed to seek to end of data")?;
+        frames.push(RecordingFrameMetadata {
+            timing: Timing {
+                timestamp,
+                duration,
+            },
+            offset: offset.try_into().unwrap(),
+            header_offset: header_length.try_into().unwrap(),
+            length,
+        });
+        offset = file
+            .stream_position()
+            .wrap_err("failed to get stream position")?;
+    }
+
+    Ok((frames, offset))
+}
+
+/// Truncates incomplete trailing data and appends a frame index footer
+fn repair(
+    recording_file: &Path,
+    end_of_frames: u64,
+    metadata: &RecordingMetadata,
+    frames: &[RecordingFrameMetadata],
+) -> color_eyre::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(recording_file)
+        .wrap_err("failed to open recording file for writing")?;
+    file.set_len(end_of_frames)
+        .wrap_err("failed to truncate recording file")?;
+    file.seek(SeekFrom::End(0))
+        .wrap_err("failed to seek to end of file")?;
+    let mut writer = io::BufWriter::new(file);
+    write_footer(&mut writer, end_of_frames, metadata, frames)?;
+    writer.flush().wrap_err("failed to flush recording file")
+}
+
 fn end_of_file_error_as_option<T>(result: Result<T, Error>) -> Result<Option<T>, Error> {
     result.map(Some).or_else(|error| {
         if let bincode::ErrorKind::Io(ref error) = *error {
@@ -162,3 +365,127 @@ fn end_of_file_error_as_option<T>(result: Result<T, Error>) -> Result<Option<T>,
         Err(error)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use crate::RecordingWriter;
+
+    use super::*;
+
+    fn timestamp(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    fn write_recording(path: &Path, number_of_frames: u64) -> RecordingWriter {
+        let mut writer = RecordingWriter::create(path, "Control").unwrap();
+        for index in 0..number_of_frames {
+            writer
+                .write_frame(
+                    timestamp(index * 10),
+                    Duration::from_millis(12),
+                    &[index as u8; 3],
+                )
+                .unwrap();
+        }
+        writer
+    }
+
+    #[test]
+    fn finished_recording_is_loaded_from_footer() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("Control.bincode");
+        write_recording(&path, 5).finish().unwrap();
+
+        let mut index = RecordingIndex::read_from(&path).unwrap();
+        assert_eq!(index.number_of_frames(), 5);
+        assert_eq!(index.metadata().unwrap().cycler_instance, "Control");
+        assert!(index
+            .find_latest_frame_up_to(timestamp(0) - Duration::from_secs(1))
+            .unwrap()
+            .is_none());
+        let frame = index
+            .find_latest_frame_up_to(timestamp(25))
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.timing.timestamp, timestamp(20));
+        assert_eq!(frame.data, vec![2; 3]);
+        let frame = index
+            .find_latest_frame_up_to(timestamp(1000))
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.data, vec![4; 3]);
+    }
+
+    #[test]
+    fn truncated_recording_is_scanned_and_repaired() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("Control.bincode");
+        drop(write_recording(&path, 3));
+        let file_length = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(file_length - 1)
+            .unwrap();
+
+        let index = RecordingIndex::read_from(&path).unwrap();
+        assert_eq!(index.number_of_frames(), 2);
+
+        let mut file = File::open(&path).unwrap();
+        let file_length = file.seek(SeekFrom::End(0)).unwrap();
+        let footer = read_footer(&mut file, file_length).unwrap().unwrap();
+        assert_eq!(footer.frames.len(), 2);
+        assert_eq!(footer.metadata.cycler_instance, "Control");
+    }
+
+    #[test]
+    fn legacy_recording_without_header_is_scanned() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("Control.bincode");
+        let mut data = Vec::new();
+        for index in 0..4u64 {
+            serialize_into(&mut data, &timestamp(index)).unwrap();
+            serialize_into(&mut data, &Duration::from_millis(1)).unwrap();
+            serialize_into(&mut data, &2usize).unwrap();
+            data.extend_from_slice(&[index as u8; 2]);
+        }
+        std::fs::write(&path, data).unwrap();
+
+        let mut index = RecordingIndex::read_from(&path).unwrap();
+        assert_eq!(index.number_of_frames(), 4);
+        assert!(index.metadata().is_none());
+        let frame = index
+            .find_latest_frame_up_to(timestamp(2))
+            .unwrap()
+            .unwrap();
+        let mut expected_data = Vec::new();
+        serialize_into(&mut expected_data, &false).unwrap();
+        expected_data.extend_from_slice(&[2; 2]);
+        assert_eq!(frame.data, expected_data);
+    }
+
+    #[test]
+    fn version_1_frames_are_read_without_hardware_inputs() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("Control.bincode");
+        let mut data = Vec::new();
+        data.extend_from_slice(&FILE_MAGIC);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        serialize_into(&mut data, &timestamp(0)).unwrap();
+        serialize_into(&mut data, &Duration::from_millis(1)).unwrap();
+        serialize_into(&mut data, &2usize).unwrap();
+        data.extend_from_slice(&[7; 2]);
+        std::fs::write(&path, data).unwrap();
+
+        let mut index = RecordingIndex::read_from(&path).unwrap();
+        let frame = index.read_frame(0).unwrap().unwrap();
+
+        let mut frame_data = &frame.data[..];
+        let has_hardware_inputs: bool = deserialize_from(&mut frame_data).unwrap();
+        assert!(!has_hardware_inputs);
+        assert_eq!(frame_data, &[7; 2]);
+    }
+}
@@ -1,23 +1,210 @@
+use std::{
+    collections::VecDeque,
+    mem::take,
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::{eyre::WrapErr, Result};
+use serde::Deserialize;
+
+use crate::RecordingFrame;
+
+/// Keeps the recorded cycles around incidents instead of recording continuously
+///
+/// Both durations have to be finite and non-negative.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct EventRecording {
+    /// Length of the history which is buffered and written once an event is triggered
+    pub seconds_before: f32,
+    /// Duration after an event during which all cycles are written
+    pub seconds_after: f32,
+}
+
 pub struct RecordingTrigger {
     recording_interval: usize,
     counter: usize,
+    event_recording: Option<EventRecordingState>,
+}
+
+struct EventRecordingState {
+    before: Duration,
+    after: Duration,
+    buffer: VecDeque<RecordingFrame>,
+    last_handled_event: Option<SystemTime>,
+    record_until: Option<SystemTime>,
 }
 
 impl RecordingTrigger {
-    pub fn new(recording_interval: usize) -> Self {
-        Self {
+    pub fn new(recording_interval: usize, event_recording: Option<EventRecording>) -> Result<Self> {
+        let event_recording = event_recording
+            .map(|event_recording| -> Result<_> {
+                Ok(EventRecordingState {
+                    before: Duration::try_from_secs_f32(event_recording.seconds_before)
+                        .wrap_err("invalid event recording seconds_before")?,
+                    after: Duration::try_from_secs_f32(event_recording.seconds_after)
+                        .wrap_err("invalid event recording seconds_after")?,
+                    buffer: Default::default(),
+                    last_handled_event: None,
+                    record_until: None,
+                })
+            })
+            .transpose()?;
+        Ok(Self {
             recording_interval,
             counter: 0,
-        }
+            event_recording,
+        })
     }
 
     pub fn update(&mut self) {
-        if self.recording_interval != 0 {
-            self.counter = (self.counter + 1) % self.recording_interval;
+        let recording_interval = match self.event_recording {
+            Some(_) => self.recording_interval.max(1),
+            None => self.recording_interval,
+        };
+        if recording_interval != 0 {
+            self.counter = (self.counter + 1) % recording_interval;
         }
     }
 
+    /// Whether the current cycle should be serialized
+    ///
+    /// With event recording, cycles are serialized even if they might only end up in the buffer.
     pub fn should_record(&self) -> bool {
-        self.recording_interval != 0 && self.counter == 0
+        match self.event_recording {
+            Some(_) => self.counter == 0,
+            None => self.recording_interval != 0 && self.counter == 0,
+        }
+    }
+
+    /// Returns the frames which should be written to the recording
+    ///
+    /// Without event recording, this is the given frame. With event recording, the frame is
+    /// buffered unless it lies within [`EventRecording::seconds_after`] of the latest event. When
+    /// a new event is encountered, the buffered history is returned as well.
+    ///
+    /// The event timestamp only identifies the event. Its time is taken from the frame in which it
+    /// is first encountered, such that the history and the following cycles are measured with the
+    /// recording clock, even if the event was triggered with simulated time.
+    pub fn frames_to_write(
+        &mut self,
+        frame: RecordingFrame,
+        latest_event: Option<SystemTime>,
+    ) -> Vec<RecordingFrame> {
+        let Some(state) = &mut self.event_recording else {
+            return vec![frame];
+        };
+
+        let mut frames_to_write = Vec::new();
+        if latest_event.is_some() && latest_event != state.last_handled_event {
+            let event = frame.timing.timestamp;
+            state.last_handled_event = latest_event;
+            state.record_until = Some(event + state.after);
+            frames_to_write.extend(
+                take(&mut state.buffer)
+                    .into_iter()
+                    .filter(|buffered_frame| {
+                        buffered_frame.timing.timestamp + state.before >= event
+                    }),
+            );
+        }
+
+        let is_after_event = state
+            .record_until
+            .is_some_and(|record_until| frame.timing.timestamp <= record_until);
+        if is_after_event {
+            frames_to_write.push(frame);
+        } else {
+            let oldest_timestamp_to_keep = frame.timing.timestamp - state.before;
+            while state.buffer.front().is_some_and(|buffered_frame| {
+                buffered_frame.timing.timestamp < oldest_timestamp_to_keep
+            }) {
+                state.buffer.pop_front();
+            }
+            state.buffer.push_back(frame);
+        }
+        frames_to_write
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    fn frame(milliseconds: u64) -> RecordingFrame {
+        RecordingFrame::new(
+            UNIX_EPOCH + Duration::from_millis(milliseconds),
+            Duration::from_millis(1),
+            vec![],
+        )
+    }
+
+    fn timestamps(frames: &[RecordingFrame]) -> Vec<u64> {
+        frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .timing
+                    .timestamp
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn interval_recording_writes_every_serialized_frame() {
+        let mut trigger = RecordingTrigger::new(2, None).unwrap();
+        assert!(trigger.should_record());
+        trigger.update();
+        assert!(!trigger.should_record());
+        trigger.update();
+        assert!(trigger.should_record());
+        assert_eq!(timestamps(&trigger.frames_to_write(frame(0), None)), [0]);
+    }
+
+    #[test]
+    fn event_recording_flushes_history_and_following_frames() {
+        let mut trigger = RecordingTrigger::new(
+            0,
+            Some(EventRecording {
+                seconds_before: 0.1,
+                seconds_after: 0.05,
+            }),
+        )
+        .unwrap();
+        assert!(trigger.should_record());
+        for milliseconds in (0..=200).step_by(10) {
+            assert!(trigger
+                .frames_to_write(frame(milliseconds), None)
+                .is_empty());
+        }
+
+        let event = Some(UNIX_EPOCH + Duration::from_secs(1000));
+        assert_eq!(
+            timestamps(&trigger.frames_to_write(frame(210), event)),
+            [110, 120, 130, 140, 150, 160, 170, 180, 190, 200, 210]
+        );
+        assert_eq!(
+            timestamps(&trigger.frames_to_write(frame(260), event)),
+            [260]
+        );
+        assert!(trigger.frames_to_write(frame(270), event).is_empty());
+    }
+
+    #[test]
+    fn invalid_event_recording_durations_are_rejected() {
+        for (seconds_before, seconds_after) in [(-1.0, 1.0), (1.0, -1.0), (f32::NAN, 1.0)] {
+            assert!(RecordingTrigger::new(
+                0,
+                Some(EventRecording {
+                    seconds_before,
+                    seconds_after,
+                }),
+            )
+            .is_err());
+        }
     }
 }
@@ -0,0 +1,85 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, Write},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use color_eyre::eyre::WrapErr;
+
+use crate::recording_index::{
+    write_file_header, write_footer, RecordingFrameMetadata, RecordingMetadata,
+};
+
+pub struct RecordingWriter {
+    file: BufWriter<File>,
+    metadata: RecordingMetadata,
+    frames: Vec<RecordingFrameMetadata>,
+    offset: u64,
+}
+
+impl RecordingWriter {
+    pub fn create(
+        recording_file: impl AsRef<Path>,
+        cycler_instance: impl Into<String>,
+    ) -> color_eyre::Result<Self> {
+        let file = File::create(&recording_file)
+            .wrap_err_with(|| format!("failed to create {}", recording_file.as_ref().display()))?;
+        // TODO: possible optimization: buffer size
+        let mut file = BufWriter::new(file);
+        write_file_header(&mut file).wrap_err("failed to write file header")?;
+        let offset = file
+            .stream_position()
+            .wrap_err("failed to get stream position after file header")?;
+        Ok(Self {
+            file,
+            metadata: RecordingMetadata {
+                cycler_instance: cycler_instance.into(),
+            },
+            frames: Vec::new(),
+            offset,
+        })
+    }
+
+    pub fn write_frame(
+        &mut self,
+        timestamp: SystemTime,
+        duration: Duration,
+        data: &[u8],
+    ) -> color_eyre::Result<()> {
+        let mut recording_header = Vec::new();
+        bincode::serialize_into(&mut recording_header, &timestamp)
+            .wrap_err("failed to serialize timestamp")?;
+        bincode::serialize_into(&mut recording_header, &duration)
+            .wrap_err("failed to serialize duration")?;
+        bincode::serialize_into(&mut recording_header, &data.len())
+            .wrap_err("failed to serialize data length")?;
+        self.file
+            .write_all(recording_header.as_slice())
+            .wrap_err("failed to write frame header")?;
+        self.file
+            .write_all(data)
+            .wrap_err("failed to write frame data")?;
+        self.frames.push(RecordingFrameMetadata {
+            timing: crate::Timing {
+                timestamp,
+                duration,
+            },
+            offset: self.offset.try_into().unwrap(),
+            header_offset: recording_header.len(),
+            length: data.len(),
+        });
+        self.offset += (recording_header.len() + data.len()) as u64;
+        Ok(())
+    }
+
+    /// Appends the frame index footer and flushes the file.
+    ///
+    /// Recordings that are not finished (e.g. because the process crashed) are still readable, the
+    /// index is then reconstructed by scanning the file when it is opened the next time.
+    pub fn finish(mut self) -> color_eyre::Result<()> {
+        write_footer(&mut self.file, self.offset, &self.metadata, &self.frames)
+            .wrap_err("failed to write footer")?;
+        self.file.flush().wrap_err("failed to flush recording file")
+    }
+}
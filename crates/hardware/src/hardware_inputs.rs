@@ -0,0 +1,197 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use color_eyre::eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use types::{
+    audio::SpeakerRequest,
+    camera_position::CameraPosition,
+    hardware::{Ids, Paths},
+    joints::Joints,
+    led::Leds,
+    messages::{IncomingMessage, OutgoingMessage},
+    samples::Samples,
+    sensor_data::SensorData,
+    ycbcr422_image::YCbCr422Image,
+};
+
+use crate::{
+    ActuatorInterface, CameraInterface, IdInterface, MicrophoneInterface, NetworkInterface,
+    PathsInterface, RecordingInterface, SensorInterface, SpeakerInterface, TimeInterface,
+};
+
+/// Values read from the hardware during the setup nodes of one cycle
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct HardwareInputs {
+    pub camera_images: VecDeque<(CameraPosition, YCbCr422Image)>,
+    pub microphone_samples: VecDeque<Samples>,
+    pub network_messages: VecDeque<IncomingMessage>,
+    pub sensor_data: VecDeque<SensorData>,
+}
+
+#[derive(Default)]
+enum State {
+    #[default]
+    PassThrough,
+    Recording(HardwareInputs),
+    Replaying(HardwareInputs),
+}
+
+/// Hardware interface passed to setup nodes which either records all reads of the wrapped
+/// interface or serves previously recorded reads during replay
+///
+/// All other interfaces are delegated to the wrapped interface.
+pub struct HardwareInputsInterface<Inner> {
+    inner: Arc<Inner>,
+    state: Mutex<State>,
+}
+
+impl<Inner> HardwareInputsInterface<Inner> {
+    pub fn new(inner: Arc<Inner>) -> Self {
+        Self {
+            inner,
+            state: Default::default(),
+        }
+    }
+
+    pub fn start_recording(&self) {
+        *self.state.lock().unwrap() = State::Recording(Default::default());
+    }
+
+    /// Returns all inputs read since [`Self::start_recording`]
+    pub fn finish_recording(&self) -> HardwareInputs {
+        match std::mem::take(&mut *self.state.lock().unwrap()) {
+            State::Recording(inputs) => inputs,
+            _ => Default::default(),
+        }
+    }
+
+    pub fn start_replaying(&self, inputs: HardwareInputs) {
+        *self.state.lock().unwrap() = State::Replaying(inputs);
+    }
+
+    pub fn finish_replaying(&self) {
+        *self.state.lock().unwrap() = State::PassThrough;
+    }
+
+    fn read<Input: Clone>(
+        &self,
+        read_from_inner: impl FnOnce(&Inner) -> Result<Input>,
+        recorded_inputs: impl FnOnce(&mut HardwareInputs) -> &mut VecDeque<Input>,
+        name: &str,
+    ) -> Result<Input> {
+        let mut state = self.state.lock().unwrap();
+        match &mut *state {
+            State::PassThrough => read_from_inner(&self.inner),
+            State::Recording(inputs) => {
+                let input = read_from_inner(&self.inner)?;
+                recorded_inputs(inputs).push_back(input.clone());
+                Ok(input)
+            }
+            State::Replaying(inputs) => recorded_inputs(inputs)
+                .pop_front()
+                .ok_or_else(|| eyre!("no recorded {name} left to replay")),
+        }
+    }
+}
+
+impl<Inner: ActuatorInterface> ActuatorInterface for HardwareInputsInterface<Inner> {
+    fn write_to_actuators(
+        &self,
+        positions: Joints<f32>,
+        stiffnesses: Joints<f32>,
+        leds: Leds,
+    ) -> Result<()> {
+        self.inner.write_to_actuators(positions, stiffnesses, leds)
+    }
+}
+
+impl<Inner: CameraInterface> CameraInterface for HardwareInputsInterface<Inner> {
+    fn read_from_camera(&self, camera_position: CameraPosition) -> Result<YCbCr422Image> {
+        self.read(
+            |inner| Ok((camera_position, inner.read_from_camera(camera_position)?)),
+            |inputs| &mut inputs.camera_images,
+            "camera image",
+        )
+        .map(|(_camera_position, image)| image)
+    }
+}
+
+impl<Inner: IdInterface> IdInterface for HardwareInputsInterface<Inner> {
+    fn get_ids(&self) -> Ids {
+        self.inner.get_ids()
+    }
+}
+
+impl<Inner: MicrophoneInterface> MicrophoneInterface for HardwareInputsInterface<Inner> {
+    fn read_from_microphones(&self) -> Result<Samples> {
+        self.read(
+            |inner| inner.read_from_microphones(),
+            |inputs| &mut inputs.microphone_samples,
+            "microphone samples",
+        )
+    }
+}
+
+impl<Inner: NetworkInterface> NetworkInterface for HardwareInputsInterface<Inner> {
+    fn read_from_network(&self) -> Result<IncomingMessage> {
+        self.read(
+            |inner| inner.read_from_network(),
+            |inputs| &mut inputs.network_messages,
+            "network message",
+        )
+    }
+
+    fn write_to_network(&self, message: OutgoingMessage) -> Result<()> {
+        self.inner.write_to_network(message)
+    }
+}
+
+impl<Inner: PathsInterface> PathsInterface for HardwareInputsInterface<Inner> {
+    fn get_paths(&self) -> Paths {
+        self.inner.get_paths()
+    }
+}
+
+impl<Inner: RecordingInterface> RecordingInterface for HardwareInputsInterface<Inner> {
+    fn should_record(&self) -> bool {
+        self.inner.should_record()
+    }
+
+    fn set_whether_to_record(&self, enable: bool) {
+        self.inner.set_whether_to_record(enable)
+    }
+
+    fn trigger_event_recording(&self, timestamp: SystemTime) {
+        self.inner.trigger_event_recording(timestamp)
+    }
+
+    fn get_last_event_recording_trigger(&self) -> Option<SystemTime> {
+        self.inner.get_last_event_recording_trigger()
+    }
+}
+
+impl<Inner: SensorInterface> SensorInterface for HardwareInputsInterface<Inner> {
+    fn read_from_sensors(&self) -> Result<SensorData> {
+        self.read(
+            |inner| inner.read_from_sensors(),
+            |inputs| &mut inputs.sensor_data,
+            "sensor data",
+        )
+    }
+}
+
+impl<Inner: SpeakerInterface> SpeakerInterface for HardwareInputsInterface<Inner> {
+    fn write_to_speakers(&self, request: SpeakerRequest) {
+        self.inner.write_to_speakers(request)
+    }
+}
+
+impl<Inner: TimeInterface> TimeInterface for HardwareInputsInterface<Inner> {
+    fn get_now(&self) -> SystemTime {
+        self.inner.get_now()
+    }
+}
@@ -1,3 +1,5 @@
+mod hardware_inputs;
+
 use std::time::SystemTime;
 
 use color_eyre::eyre::Result;
@@ -13,6 +15,8 @@ use types::{
     ycbcr422_image::YCbCr422Image,
 };
 
+pub use hardware_inputs::{HardwareInputs, HardwareInputsInterface};
+
 pub trait ActuatorInterface {
     fn write_to_actuators(
         &self,
@@ -46,6 +50,8 @@ pub trait PathsInterface {
 pub trait RecordingInterface {
     fn should_record(&self) -> bool;
     fn set_whether_to_record(&self, enable: bool);
+    fn trigger_event_recording(&self, timestamp: SystemTime);
+    fn get_last_event_recording_trigger(&self) -> Option<SystemTime>;
 }
 
 pub trait SensorInterface {
@@ -18,9 +18,11 @@ pub fn collect_hulk_cyclers() -> Result<Cyclers, Error> {
                     "vision::feet_detection",
                     "vision::field_border_detection",
                     "vision::field_color_detection",
+                    "vision::goal_post_detection",
                     "vision::image_segmenter",
                     "vision::limb_projector",
                     "vision::line_detection",
+                    "vision::penalty_marker_detection",
                     "vision::perspective_grid_candidates_provider",
                     "vision::segment_filter",
                 ],
@@ -72,11 +74,13 @@ pub fn collect_hulk_cyclers() -> Result<Cyclers, Error> {
                     "control::orientation_filter",
                     "control::penalty_shot_direction_estimation",
                     "control::primary_state_filter",
+                    "control::recording_event_trigger",
                     "control::role_assignment",
                     "control::rule_obstacle_composer",
                     "control::sole_pressure_filter",
                     "control::sonar_filter",
                     "control::support_foot_estimation",
+                    "control::team_world_model",
                     "control::time_to_reach_kick_position",
                     "control::visual_referee_filter",
                     "control::whistle_filter",
@@ -62,6 +62,7 @@ pub struct HardwareInterface {
     camera_top: Camera,
     camera_bottom: Camera,
     enable_recording: AtomicBool,
+    last_event_recording_trigger: Mutex<Option<SystemTime>>,
     keep_running: CancellationToken,
 }
 
@@ -105,6 +106,7 @@ impl HardwareInterface {
             .wrap_err("failed to initialize bottom camera")?,
 
             enable_recording: AtomicBool::new(false),
+            last_event_recording_trigger: Mutex::new(None),
             keep_running,
         })
     }
@@ -179,6 +181,14 @@ impl RecordingInterface for HardwareInterface {
     fn set_whether_to_record(&self, enable: bool) {
         self.enable_recording.store(enable, Ordering::SeqCst)
     }
+
+    fn trigger_event_recording(&self, timestamp: SystemTime) {
+        *self.last_event_recording_trigger.lock() = Some(timestamp);
+    }
+
+    fn get_last_event_recording_trigger(&self) -> Option<SystemTime> {
+        *self.last_event_recording_trigger.lock()
+    }
 }
 
 impl SensorInterface for HardwareInterface {
@@ -86,11 +86,15 @@ fn main() -> Result<()> {
     run(
         Arc::new(hardware_interface),
         framework_parameters.communication_addresses,
+        framework_parameters.communication_read_only_key,
+        framework_parameters.communication_admin_key,
         framework_parameters.parameters_directory,
         arguments.log_path,
         ids.body_id,
         ids.head_id,
         keep_running,
         framework_parameters.recording_intervals,
+        framework_parameters.record_hardware_inputs,
+        framework_parameters.event_recordings,
     )
 }
@@ -0,0 +1,330 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::headless::{OutputSink, ReplayedOutput};
+
+/// One line of a golden file as written by the JSON Lines export of the headless mode
+#[derive(Debug, Deserialize)]
+pub struct GoldenOutput {
+    pub cycler_instance: String,
+    pub cycle: usize,
+    pub timestamp: f64,
+    pub path: String,
+    pub value: Value,
+}
+
+pub fn read_golden_outputs(golden_file: impl AsRef<Path>) -> Result<Vec<GoldenOutput>> {
+    let golden_file = golden_file.as_ref();
+    let file = File::open(golden_file)
+        .wrap_err_with(|| format!("failed to open {}", golden_file.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_line_number, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(line_number, line)| {
+            let line = line.wrap_err("failed to read golden file")?;
+            serde_json::from_str(&line).wrap_err_with(|| {
+                format!(
+                    "failed to parse line {} of {}",
+                    line_number + 1,
+                    golden_file.display()
+                )
+            })
+        })
+        .collect()
+}
+
+/// Maximum absolute deviation of numbers per field, e.g. `Control.main_outputs.ball_position.position`
+///
+/// The tolerance of the longest matching prefix applies to all nested fields. Fields without
+/// tolerance have to match exactly.
+#[derive(Debug, Default, Deserialize)]
+pub struct Tolerances(BTreeMap<String, f64>);
+
+impl Tolerances {
+    pub fn read_from(tolerances_file: impl AsRef<Path>) -> Result<Self> {
+        let tolerances_file = tolerances_file.as_ref();
+        let file = File::open(tolerances_file)
+            .wrap_err_with(|| format!("failed to open {}", tolerances_file.display()))?;
+        serde_json::from_reader(file)
+            .wrap_err_with(|| format!("failed to parse {}", tolerances_file.display()))
+    }
+
+    fn get(&self, path: &str) -> f64 {
+        self.0
+            .iter()
+            .filter(|(prefix, _tolerance)| {
+                path == prefix.as_str()
+                    || path
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|suffix| suffix.starts_with('.'))
+            })
+            .max_by_key(|(prefix, _tolerance)| prefix.len())
+            .map_or(0.0, |(_prefix, tolerance)| *tolerance)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Difference {
+    pub path: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+impl Display for Difference {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{}: expected {}, got {}",
+            self.path, self.expected, self.actual
+        )
+    }
+}
+
+/// Structurally compares two values and collects all differing leaves
+pub fn diff_values(
+    expected: &Value,
+    actual: &Value,
+    path: &str,
+    tolerances: &Tolerances,
+    differences: &mut Vec<Difference>,
+) {
+    match (expected, actual) {
+        (Value::Object(expected_fields), Value::Object(actual_fields)) => {
+            for (key, expected_field) in expected_fields {
+                let field_path = format!("{path}.{key}");
+                match actual_fields.get(key) {
+                    Some(actual_field) => {
+                        diff_values(
+                            expected_field,
+                            actual_field,
+                            &field_path,
+                            tolerances,
+                            differences,
+                        );
+                    }
+                    None => differences.push(Difference {
+                        path: field_path,
+                        expected: expected_field.clone(),
+                        actual: Value::Null,
+                    }),
+                }
+            }
+            for (key, actual_field) in actual_fields {
+                if !expected_fields.contains_key(key) {
+                    differences.push(Difference {
+                        path: format!("{path}.{key}"),
+                        expected: Value::Null,
+                        actual: actual_field.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(expected_items), Value::Array(actual_items))
+            if expected_items.len() == actual_items.len() =>
+        {
+            for (index, (expected_item, actual_item)) in
+                expected_items.iter().zip(actual_items).enumerate()
+            {
+                diff_values(
+                    expected_item,
+                    actual_item,
+                    &format!("{path}.{index}"),
+                    tolerances,
+                    differences,
+                );
+            }
+        }
+        (Value::Number(expected_number), Value::Number(actual_number)) => {
+            let (Some(expected_number), Some(actual_number)) =
+                (expected_number.as_f64(), actual_number.as_f64())
+            else {
+                return;
+            };
+            if (expected_number - actual_number).abs() > tolerances.get(path) {
+                differences.push(Difference {
+                    path: path.to_string(),
+                    expected: expected.clone(),
+                    actual: actual.clone(),
+                });
+            }
+        }
+        _ => {
+            if expected != actual {
+                differences.push(Difference {
+                    path: path.to_string(),
+                    expected: expected.clone(),
+                    actual: actual.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Compares replayed outputs against a golden file and fails at the first diverging cycle
+pub struct GoldenComparison {
+    golden_outputs: Vec<GoldenOutput>,
+    tolerances: Tolerances,
+    number_of_compared_outputs: usize,
+}
+
+impl GoldenComparison {
+    pub fn new(golden_outputs: Vec<GoldenOutput>, tolerances: Tolerances) -> Self {
+        Self {
+            golden_outputs,
+            tolerances,
+            number_of_compared_outputs: 0,
+        }
+    }
+
+    /// All `<cycler instance>.<path>` outputs contained in the golden file
+    pub fn outputs(&self) -> impl Iterator<Item = String> + '_ {
+        self.golden_outputs
+            .iter()
+            .map(|output| format!("{}.{}", output.cycler_instance, output.path))
+    }
+}
+
+impl OutputSink for GoldenComparison {
+    fn consume(&mut self, output: ReplayedOutput) -> Result<()> {
+        let Some(golden_output) = self.golden_outputs.get(self.number_of_compared_outputs) else {
+            bail!(
+                "replay produced more outputs than the {} golden outputs, first additional output is {}.{} in cycle {}",
+                self.golden_outputs.len(),
+                output.cycler_instance,
+                output.path,
+                output.cycle,
+            );
+        };
+        self.number_of_compared_outputs += 1;
+
+        if golden_output.cycler_instance != output.cycler_instance
+            || golden_output.path != output.path
+            || golden_output.cycle != output.cycle
+        {
+            bail!(
+                "replay diverged from golden outputs: expected {}.{} in cycle {}, got {}.{} in cycle {}",
+                golden_output.cycler_instance,
+                golden_output.path,
+                golden_output.cycle,
+                output.cycler_instance,
+                output.path,
+                output.cycle,
+            );
+        }
+
+        let mut differences = Vec::new();
+        diff_values(
+            &golden_output.value,
+            output.value,
+            &format!("{}.{}", output.cycler_instance, output.path),
+            &self.tolerances,
+            &mut differences,
+        );
+        if !differences.is_empty() {
+            let golden_timestamp = UNIX_EPOCH + Duration::from_secs_f64(golden_output.timestamp);
+            let differences: Vec<_> = differences
+                .iter()
+                .map(|difference| format!("  {difference}"))
+                .collect();
+            bail!(
+                "replay diverged from golden outputs in cycle {} of {} ({:?}):\n{}",
+                output.cycle,
+                output.cycler_instance,
+                golden_timestamp,
+                differences.join("\n"),
+            );
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let number_of_missing_outputs = self.golden_outputs.len() - self.number_of_compared_outputs;
+        if number_of_missing_outputs > 0 {
+            bail!("replay ended but {number_of_missing_outputs} golden outputs were not produced");
+        }
+        println!(
+            "{} outputs match the golden outputs",
+            self.number_of_compared_outputs
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn equal_values_have_no_differences() {
+        let value = json!({"position": [1.0, 2.0], "is_valid": true});
+        let mut differences = Vec::new();
+        diff_values(
+            &value,
+            &value,
+            "ball",
+            &Tolerances::default(),
+            &mut differences,
+        );
+        assert!(differences.is_empty());
+    }
+
+    #[test]
+    fn nested_differences_are_reported_with_path() {
+        let expected = json!({"position": [1.0, 2.0], "is_valid": true});
+        let actual = json!({"position": [1.0, 2.5], "is_valid": false});
+        let mut differences = Vec::new();
+        diff_values(
+            &expected,
+            &actual,
+            "ball",
+            &Tolerances::default(),
+            &mut differences,
+        );
+        assert_eq!(
+            differences,
+            vec![
+                Difference {
+                    path: "ball.is_valid".to_string(),
+                    expected: json!(true),
+                    actual: json!(false),
+                },
+                Difference {
+                    path: "ball.position.1".to_string(),
+                    expected: json!(2.0),
+                    actual: json!(2.5),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn longest_matching_tolerance_applies() {
+        let tolerances = Tolerances(BTreeMap::from([
+            ("ball".to_string(), 1.0),
+            ("ball.position".to_string(), 0.1),
+        ]));
+        let expected = json!({"position": [1.0], "age": 1.0});
+        let actual = json!({"position": [1.5], "age": 1.5});
+        let mut differences = Vec::new();
+        diff_values(&expected, &actual, "ball", &tolerances, &mut differences);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].path, "ball.position.0");
+        assert_eq!(tolerances.get("balloon"), 0.0);
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Result,
+};
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+
+use crate::{execution::Replayer, ReplayerHardwareInterface};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ExportFormat {
+    JsonLines,
+    Csv,
+}
+
+struct ExportedOutput {
+    cycler_instance: String,
+    path: String,
+}
+
+impl ExportedOutput {
+    /// Parses `<cycler instance>.<path>`, e.g. `Control.main_outputs.ball_position`
+    fn parse(output: &str) -> Result<Self> {
+        let (cycler_instance, path) = output.split_once('.').ok_or_else(|| {
+            eyre!("expected '.' in output path (e.g. 'Control.main_outputs.ball_position')")
+        })?;
+        if !path.starts_with("main_outputs.") && !path.starts_with("additional_outputs.") {
+            bail!("expected output path starting with 'main_outputs.' or 'additional_outputs.', got '{path}'");
+        }
+        Ok(Self {
+            cycler_instance: cycler_instance.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+pub struct ReplayedOutput<'value> {
+    pub cycler_instance: &'value str,
+    pub cycle: usize,
+    pub timestamp: SystemTime,
+    pub path: &'value str,
+    pub value: &'value Value,
+}
+
+pub trait OutputSink {
+    fn consume(&mut self, output: ReplayedOutput) -> Result<()>;
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// Collects the `(cycler instance, path)` pairs of `outputs` in a form accepted by [`replay_headless`]
+pub fn parse_outputs<'output>(
+    outputs: impl IntoIterator<Item = &'output str>,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut outputs_per_cycler_instance: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for output in outputs {
+        let output = ExportedOutput::parse(output)?;
+        let paths = outputs_per_cycler_instance
+            .entry(output.cycler_instance)
+            .or_default();
+        if !paths.contains(&output.path) {
+            paths.push(output.path);
+        }
+    }
+    Ok(outputs_per_cycler_instance)
+}
+
+/// Replays all frames of all cycler instances ordered by their timestamp as fast as possible
+///
+/// After each replayed frame, the selected outputs of the replayed cycler instance are passed to
+/// the sink.
+pub fn replay_headless(
+    replayer: &mut Replayer<ReplayerHardwareInterface>,
+    outputs_per_cycler_instance: &BTreeMap<String, Vec<String>>,
+    sink: &mut impl OutputSink,
+    keep_running: &CancellationToken,
+) -> Result<()> {
+    let cycler_instances: Vec<String> = replayer.get_recording_indices().into_keys().collect();
+    for (cycler_instance, paths) in outputs_per_cycler_instance {
+        if !cycler_instances.contains(cycler_instance) {
+            bail!(
+                "unknown cycler instance '{cycler_instance}', expected one of {cycler_instances:?}"
+            );
+        }
+        let additional_outputs: HashSet<String> = paths
+            .iter()
+            .filter(|path| path.starts_with("additional_outputs."))
+            .cloned()
+            .collect();
+        replayer
+            .subscribe_outputs(cycler_instance, additional_outputs)
+            .wrap_err_with(|| format!("failed to subscribe outputs of {cycler_instance}"))?;
+    }
+
+    let mut frames: Vec<(SystemTime, String, usize)> = replayer
+        .get_recording_indices()
+        .into_iter()
+        .flat_map(|(cycler_instance, index)| {
+            index
+                .iter()
+                .enumerate()
+                .map(|(frame_index, timing)| {
+                    (timing.timestamp, cycler_instance.clone(), frame_index)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    frames.sort_by_key(|(timestamp, _cycler_instance, _frame_index)| *timestamp);
+
+    for (_timestamp, cycler_instance, frame_index) in frames {
+        if keep_running.is_cancelled() {
+            break;
+        }
+        let frame = replayer
+            .get_recording_indices_mut()
+            .remove(&cycler_instance)
+            .expect("cycler instance should have a recording index")
+            .read_frame(frame_index)
+            .wrap_err("failed to read frame")?
+            .expect("frame index should be within recording");
+        replayer
+            .replay(&cycler_instance, frame.timing.timestamp, &frame.data)
+            .wrap_err("failed to replay frame")?;
+        let Some(paths) = outputs_per_cycler_instance.get(&cycler_instance) else {
+            continue;
+        };
+        for path in paths {
+            let value = replayer.get_output(&cycler_instance, path)?;
+            sink.consume(ReplayedOutput {
+                cycler_instance: &cycler_instance,
+                cycle: frame_index,
+                timestamp: frame.timing.timestamp,
+                path,
+                value: &value,
+            })?;
+        }
+    }
+    sink.finish()
+}
+
+pub struct OutputWriter<W> {
+    writer: W,
+    format: ExportFormat,
+}
+
+impl<W: Write> OutputWriter<W> {
+    pub fn new(mut writer: W, format: ExportFormat) -> Result<Self> {
+        if let ExportFormat::Csv = format {
+            writeln!(writer, "cycler_instance,cycle,timestamp,path,value")
+                .wrap_err("failed to write CSV header")?;
+        }
+        Ok(Self { writer, format })
+    }
+}
+
+impl<W: Write> OutputSink for OutputWriter<W> {
+    fn consume(&mut self, output: ReplayedOutput) -> Result<()> {
+        let ReplayedOutput {
+            cycler_instance,
+            cycle,
+            timestamp,
+            path,
+            value,
+        } = output;
+        let timestamp = timestamp
+            .duration_since(UNIX_EPOCH)
+            .expect("time ran backwards")
+            .as_secs_f64();
+        match self.format {
+            ExportFormat::JsonLines => {
+                serde_json::to_writer(
+                    &mut self.writer,
+                    &json!({
+                        "cycler_instance": cycler_instance,
+                        "cycle": cycle,
+                        "timestamp": timestamp,
+                        "path": path,
+                        "value": value,
+                    }),
+                )
+                .wrap_err("failed to write JSON line")?;
+                writeln!(self.writer).wrap_err("failed to write JSON line")
+            }
+            ExportFormat::Csv => writeln!(
+                self.writer,
+                "{cycler_instance},{cycle},{timestamp},{path},\"{}\"",
+                value.to_string().replace('"', "\"\"")
+            )
+            .wrap_err("failed to write CSV row"),
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer
+            .flush()
+            .wrap_err("failed to flush exported outputs")
+    }
+}
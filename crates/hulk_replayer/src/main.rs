@@ -1,4 +1,6 @@
 #![recursion_limit = "256"]
+mod golden;
+mod headless;
 mod replayer;
 mod user_interface;
 
@@ -97,6 +99,12 @@ impl RecordingInterface for ReplayerHardwareInterface {
     }
 
     fn set_whether_to_record(&self, _enable: bool) {}
+
+    fn trigger_event_recording(&self, _timestamp: SystemTime) {}
+
+    fn get_last_event_recording_trigger(&self) -> Option<SystemTime> {
+        None
+    }
 }
 
 impl SensorInterface for ReplayerHardwareInterface {
@@ -1,7 +1,13 @@
-use std::{env::args, fs::File, path::PathBuf, sync::Arc};
+use std::{
+    fs::File,
+    io::{stdout, BufWriter, Write},
+    path::PathBuf,
+    sync::Arc,
+};
 
+use clap::Parser;
 use color_eyre::{
-    eyre::{Report, WrapErr},
+    eyre::{bail, Report, WrapErr},
     Result,
 };
 use ctrlc::set_handler;
@@ -12,17 +18,41 @@ use serde_json::from_reader;
 use tokio_util::sync::CancellationToken;
 use types::hardware::Ids;
 
-use crate::{execution::Replayer, user_interface::ReplayerApplication, ReplayerHardwareInterface};
+use crate::{
+    execution::Replayer,
+    golden::{read_golden_outputs, GoldenComparison, Tolerances},
+    headless::{parse_outputs, replay_headless, ExportFormat, OutputWriter},
+    user_interface::ReplayerApplication,
+    ReplayerHardwareInterface,
+};
+
+#[derive(Parser)]
+struct Arguments {
+    /// Directory containing the recording files of all cycler instances
+    replay_path: PathBuf,
+    #[arg(default_value = "etc/parameters/framework.json")]
+    framework_parameters_path: PathBuf,
+    /// Replay all frames from start to end without user interface and export the selected outputs
+    #[arg(long)]
+    headless: bool,
+    /// Outputs to export in headless mode, e.g. `Control.main_outputs.ball_position`
+    #[arg(short, long = "output")]
+    outputs: Vec<String>,
+    /// File to export the outputs into, defaults to stdout
+    #[arg(long)]
+    export_path: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ExportFormat::JsonLines)]
+    export_format: ExportFormat,
+    /// Compare the outputs contained in this golden file (JSON Lines export) instead of exporting
+    #[arg(long, requires = "headless", conflicts_with_all = ["outputs", "export_path"])]
+    compare_with: Option<PathBuf>,
+    /// JSON object of maximum absolute deviations per field used when comparing
+    #[arg(long, requires = "compare_with")]
+    tolerances: Option<PathBuf>,
+}
 
 pub fn replayer() -> Result<()> {
-    let replay_path = PathBuf::from(
-        args()
-            .nth(1)
-            .expect("expected replay path as first parameter"),
-    );
-    let framework_parameters_path = args()
-        .nth(2)
-        .unwrap_or("etc/parameters/framework.json".to_string());
+    let arguments = Arguments::parse();
     let keep_running = CancellationToken::new();
     set_handler({
         let keep_running = keep_running.clone();
@@ -31,8 +61,8 @@ pub fn replayer() -> Result<()> {
         }
     })?;
 
-    let file =
-        File::open(framework_parameters_path).wrap_err("failed to open framework parameters")?;
+    let file = File::open(arguments.framework_parameters_path)
+        .wrap_err("failed to open framework parameters")?;
     let framework_parameters: FrameworkParameters =
         from_reader(file).wrap_err("failed to parse framework parameters")?;
 
@@ -45,17 +75,56 @@ pub fn replayer() -> Result<()> {
 
     let ids = hardware_interface.get_ids();
 
-    let replayer = Replayer::new(
+    // the headless mode subscribes outputs itself and does not serve any clients
+    let communication_addresses = if arguments.headless {
+        None
+    } else {
+        framework_parameters.communication_addresses
+    };
+
+    let mut replayer = Replayer::new(
         Arc::new(hardware_interface),
-        framework_parameters.communication_addresses,
-        replay_path.clone(),
+        communication_addresses,
+        arguments.replay_path.clone(),
         ids.body_id,
         ids.head_id,
-        keep_running,
-        replay_path,
+        keep_running.clone(),
+        arguments.replay_path,
     )
     .wrap_err("failed to create replayer")?;
 
+    if let Some(golden_file) = arguments.compare_with {
+        let golden_outputs = read_golden_outputs(golden_file)?;
+        let tolerances = match arguments.tolerances {
+            Some(tolerances_file) => Tolerances::read_from(tolerances_file)?,
+            None => Tolerances::default(),
+        };
+        let mut comparison = GoldenComparison::new(golden_outputs, tolerances);
+        let outputs = comparison.outputs().collect::<Vec<_>>();
+        let outputs = parse_outputs(outputs.iter().map(String::as_str))?;
+        let result = replay_headless(&mut replayer, &outputs, &mut comparison, &keep_running);
+        keep_running.cancel();
+        return result;
+    }
+
+    if arguments.headless {
+        if arguments.outputs.is_empty() {
+            bail!("headless mode requires at least one --output or --compare-with");
+        }
+        let outputs = parse_outputs(arguments.outputs.iter().map(String::as_str))?;
+        let writer: Box<dyn Write> = match arguments.export_path {
+            Some(export_path) => Box::new(BufWriter::new(
+                File::create(&export_path)
+                    .wrap_err_with(|| format!("failed to create {}", export_path.display()))?,
+            )),
+            None => Box::new(BufWriter::new(stdout().lock())),
+        };
+        let mut writer = OutputWriter::new(writer, arguments.export_format)?;
+        let result = replay_headless(&mut replayer, &outputs, &mut writer, &keep_running);
+        keep_running.cancel();
+        return result;
+    }
+
     run_native(
         "Replayer",
         Default::default(),
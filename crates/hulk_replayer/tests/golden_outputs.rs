@@ -0,0 +1,67 @@
+use std::{
+    fs::read_dir,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn repository_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .ancestors()
+        .nth(2)
+        .expect("crate should be located in <repository>/crates/")
+        .to_path_buf()
+}
+
+/// Replays every test case in `tests/replay/<case>/` and compares against its golden outputs
+///
+/// A test case consists of a `recording/` directory (as downloaded from the robot), a
+/// `golden.jsonl` file (JSON Lines export of the headless replayer) and optionally a
+/// `tolerances.json` file. Without a `tests/replay/` directory there is nothing to compare.
+#[test]
+fn replayed_outputs_match_golden_outputs() {
+    let repository_root = repository_root();
+    let test_cases_directory = repository_root.join("tests/replay");
+    if !test_cases_directory.exists() {
+        return;
+    }
+    let test_cases: Vec<_> = read_dir(test_cases_directory)
+        .expect("failed to read test cases in tests/replay/")
+        .map(|test_case| test_case.expect("failed to read test case").path())
+        .filter(|test_case| test_case.is_dir())
+        .collect();
+    let mut failed_test_cases = Vec::new();
+    for test_case in test_cases {
+        for fixture in ["recording", "golden.jsonl"] {
+            assert!(
+                test_case.join(fixture).exists(),
+                "{fixture} is missing in {}",
+                test_case.display()
+            );
+        }
+        let mut command = Command::new(env!("CARGO_BIN_EXE_hulk_replayer"));
+        command
+            .current_dir(&repository_root)
+            .arg(test_case.join("recording"))
+            .arg(repository_root.join("etc/parameters/framework.json"))
+            .arg("--headless")
+            .arg("--compare-with")
+            .arg(test_case.join("golden.jsonl"));
+        let tolerances = test_case.join("tolerances.json");
+        if tolerances.exists() {
+            command.arg("--tolerances").arg(tolerances);
+        }
+        let output = command.output().expect("failed to execute replayer");
+        if !output.status.success() {
+            eprintln!(
+                "{}:\n{}",
+                test_case.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            failed_test_cases.push(test_case);
+        }
+    }
+    assert!(
+        failed_test_cases.is_empty(),
+        "golden outputs differ in {failed_test_cases:?}"
+    );
+}
@@ -15,6 +15,7 @@ use hardware::{
     ActuatorInterface, CameraInterface, IdInterface, MicrophoneInterface, NetworkInterface,
     PathsInterface, RecordingInterface, SensorInterface, SpeakerInterface, TimeInterface,
 };
+use parking_lot::Mutex;
 use serde::Deserialize;
 use spl_network::endpoint::{Endpoint, Ports};
 use tokio::{
@@ -66,6 +67,7 @@ pub struct HardwareInterface {
     spl_network_endpoint: Endpoint,
     async_runtime: Runtime,
     enable_recording: AtomicBool,
+    last_event_recording_trigger: Mutex<Option<SystemTime>>,
     keep_running: CancellationToken,
     simulator_audio_synchronization: Barrier,
 }
@@ -95,6 +97,7 @@ impl HardwareInterface {
                 .wrap_err("failed to initialize SPL network")?,
             async_runtime: runtime,
             enable_recording: AtomicBool::new(false),
+            last_event_recording_trigger: Mutex::new(None),
             keep_running,
             simulator_audio_synchronization: Barrier::new(2),
         })
@@ -358,6 +361,14 @@ impl RecordingInterface for HardwareInterface {
     fn set_whether_to_record(&self, enable: bool) {
         self.enable_recording.store(enable, Ordering::SeqCst)
     }
+
+    fn trigger_event_recording(&self, timestamp: SystemTime) {
+        *self.last_event_recording_trigger.lock() = Some(timestamp);
+    }
+
+    fn get_last_event_recording_trigger(&self) -> Option<SystemTime> {
+        *self.last_event_recording_trigger.lock()
+    }
 }
 
 impl SensorInterface for HardwareInterface {
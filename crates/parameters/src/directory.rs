@@ -52,60 +52,34 @@ pub async fn deserialize<Parameters>(
 where
     Parameters: DeserializeOwned,
 {
-    let default_file_path = parameters_root_path.as_ref().join("default.json");
-    let mut parameters = read_from_file(default_file_path)
-        .await
-        .map_err(DirectoryError::DefaultParametersNotGet)?;
-
-    let location_directory = parameters_root_path
-        .as_ref()
-        .join(location_directory_from_head_id(head_id));
-
-    let location_default_file_path = location_directory.join("default.json");
-    if location_default_file_path.exists() {
-        let location_default_parameters = read_from_file(location_default_file_path)
-            .await
-            .map_err(DirectoryError::DefaultParametersOfLocationNotGet)?;
-        merge_json(&mut parameters, &location_default_parameters);
-    }
-
-    let body_file_path = parameters_root_path
-        .as_ref()
-        .join(format!("body.{}.json", body_id));
-    if body_file_path.exists() {
-        let body_parameters = read_from_file(body_file_path)
-            .await
-            .map_err(DirectoryError::BodyParametersNotGet)?;
-        merge_json(&mut parameters, &body_parameters);
-    }
-
-    let head_file_path = parameters_root_path
-        .as_ref()
-        .join(format!("head.{}.json", head_id));
-    if head_file_path.exists() {
-        let head_parameters = read_from_file(head_file_path)
-            .await
-            .map_err(DirectoryError::HeadParametersNotGet)?;
-        merge_json(&mut parameters, &head_parameters);
-    }
-
-    let location_body_file_path = location_directory.join(format!("body.{}.json", body_id));
-    if location_body_file_path.exists() {
-        let location_body_parameters = read_from_file(location_body_file_path)
-            .await
-            .map_err(DirectoryError::BodyParametersOfLocationNotGet)?;
-        merge_json(&mut parameters, &location_body_parameters);
+    let mut parameters = Value::Object(Default::default());
+    for (_scope, layer) in deserialize_layers(parameters_root_path, body_id, head_id).await? {
+        merge_json(&mut parameters, &layer);
     }
+    from_value(parameters).map_err(DirectoryError::JsonValueNotConvertedToParameters)
+}
 
-    let location_head_file_path = location_directory.join(format!("head.{}.json", head_id));
-    if location_head_file_path.exists() {
-        let location_head_parameters = read_from_file(location_head_file_path)
+/// Reads the parameter files of all scopes in the order they are merged
+///
+/// Only the default parameters are mandatory, scopes without a file are left out.
+pub async fn deserialize_layers(
+    parameters_root_path: impl AsRef<Path>,
+    body_id: &str,
+    head_id: &str,
+) -> Result<Vec<(Scope, Value)>, DirectoryError> {
+    let mut layers = Vec::new();
+    for scope in Scope::MERGE_ORDER {
+        let file_path = file_path_from_scope(scope, &parameters_root_path, body_id, head_id);
+        let is_default = scope.location == Location::All && scope.id == Id::All;
+        if !is_default && !file_path.exists() {
+            continue;
+        }
+        let layer = read_from_file(file_path)
             .await
-            .map_err(DirectoryError::HeadParametersOfLocationNotGet)?;
-        merge_json(&mut parameters, &location_head_parameters);
+            .map_err(|error| layer_not_read(scope, error))?;
+        layers.push((scope, layer));
     }
-
-    from_value(parameters).map_err(DirectoryError::JsonValueNotConvertedToParameters)
+    Ok(layers)
 }
 
 pub async fn serialize<Parameters>(
@@ -158,6 +132,36 @@ pub struct Scope {
     pub id: Id,
 }
 
+impl Scope {
+    /// Scopes in the order their files are merged, later scopes override earlier ones
+    pub const MERGE_ORDER: [Scope; 6] = [
+        Scope {
+            location: Location::All,
+            id: Id::All,
+        },
+        Scope {
+            location: Location::Current,
+            id: Id::All,
+        },
+        Scope {
+            location: Location::All,
+            id: Id::Body,
+        },
+        Scope {
+            location: Location::All,
+            id: Id::Head,
+        },
+        Scope {
+            location: Location::Current,
+            id: Id::Body,
+        },
+        Scope {
+            location: Location::Current,
+            id: Id::Head,
+        },
+    ];
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Location {
     All,
@@ -190,6 +194,17 @@ fn file_path_from_scope(
     }
 }
 
+fn layer_not_read(scope: Scope, error: SerializationError) -> DirectoryError {
+    match (scope.location, scope.id) {
+        (Location::All, Id::All) => DirectoryError::DefaultParametersNotGet(error),
+        (Location::Current, Id::All) => DirectoryError::DefaultParametersOfLocationNotGet(error),
+        (Location::All, Id::Body) => DirectoryError::BodyParametersNotGet(error),
+        (Location::All, Id::Head) => DirectoryError::HeadParametersNotGet(error),
+        (Location::Current, Id::Body) => DirectoryError::BodyParametersOfLocationNotGet(error),
+        (Location::Current, Id::Head) => DirectoryError::HeadParametersOfLocationNotGet(error),
+    }
+}
+
 fn location_directory_from_head_id(head_id: &str) -> &'static str {
     let webots_id_found = head_id.starts_with("webots");
     let behavior_simulator_id_found = head_id.starts_with("behavior_simulator");
@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     env::current_dir,
     ffi::OsStr,
     fmt::Display,
@@ -281,6 +281,7 @@ impl Repository {
     pub async fn set_recording_intervals(
         &self,
         recording_intervals: HashMap<String, usize>,
+        record_hardware_inputs: HashSet<String>,
     ) -> Result<()> {
         let file_contents = read_to_string(self.root.join("etc/parameters/framework.json"))
             .await
@@ -290,6 +291,8 @@ impl Repository {
 
         hardware_json["recording_intervals"] = to_value(recording_intervals)
             .wrap_err("failed to convert recording intervals to JSON")?;
+        hardware_json["record_hardware_inputs"] = to_value(record_hardware_inputs)
+            .wrap_err("failed to convert cycler instances recording hardware inputs to JSON")?;
         {
             let file_contents = to_string_pretty(&hardware_json)
                 .wrap_err("failed to serialize framework.json")?
@@ -1,7 +1,8 @@
 pub trait EncodeJpeg {
     const DEFAULT_QUALITY: u8;
     type Error;
-    fn encode_as_jpeg(&self, quality: u8) -> Result<Vec<u8>, Self::Error>;
+    /// Encodes the image with its width and height divided by `downscale_factor`
+    fn encode_as_jpeg(&self, quality: u8, downscale_factor: u32) -> Result<Vec<u8>, Self::Error>;
 }
 
 pub trait DecodeJpeg
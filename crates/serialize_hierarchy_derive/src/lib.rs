@@ -48,17 +48,26 @@ fn process_input(mut input: DeriveInput) -> TokenStream {
     let (jpeg_serialization, jpeg_exists_getter, jpeg_field_chain) = if contains_as_jpeg {
         (
             quote! {
-                "jpeg" => self
-                    .encode_as_jpeg(Self::DEFAULT_QUALITY)
-                    .map_err(|error| serialize_hierarchy::Error::SerializationFailed(serde::ser::Error::custom(error)))?
-                    .serialize(serializer)
-                    .map_err(serialize_hierarchy::Error::SerializationFailed),
+                "jpeg" | "jpeg_half" | "jpeg_quarter" => {
+                    let downscale_factor = match path {
+                        "jpeg_half" => 2,
+                        "jpeg_quarter" => 4,
+                        _ => 1,
+                    };
+                    self
+                        .encode_as_jpeg(Self::DEFAULT_QUALITY, downscale_factor)
+                        .map_err(|error| serialize_hierarchy::Error::SerializationFailed(serde::ser::Error::custom(error)))?
+                        .serialize(serializer)
+                        .map_err(serialize_hierarchy::Error::SerializationFailed)
+                }
             },
             quote! {
-                "jpeg" => true,
+                "jpeg" | "jpeg_half" | "jpeg_quarter" => true,
             },
             quote! {
                 fields.insert(format!("{prefix}jpeg"));
+                fields.insert(format!("{prefix}jpeg_half"));
+                fields.insert(format!("{prefix}jpeg_quarter"));
             },
         )
     } else {
@@ -9,7 +9,7 @@ use std::{
 };
 
 use coordinate_systems::Field;
-use linear_algebra::{Point2, Pose2};
+use linear_algebra::{point, Point2, Pose2};
 use serde::{Deserialize, Serialize};
 
 pub use game_controller_return_message::GameControllerReturnMessage;
@@ -20,13 +20,49 @@ pub use game_controller_state_message::{
 use serialize_hierarchy::SerializeHierarchy;
 pub use visual_referee_message::{VisualRefereeDecision, VisualRefereeMessage};
 
+/// Number of obstacles a robot shares with its teammates in each message
+pub const MAXIMUM_NUMBER_OF_SHARED_OBSTACLES: usize = 8;
+
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
 pub struct HulkMessage {
     pub player_number: PlayerNumber,
     pub fallen: bool,
     pub pose: Pose2<Field>,
+    /// Standard deviation of the position in `pose` in meters
+    pub pose_standard_deviation: f32,
     pub ball_position: Option<BallPosition<Field>>,
+    /// The sender did not see `ball_position` itself but relays the team ball
+    pub ball_is_relayed: bool,
     pub time_to_reach_kick_position: Option<Duration>,
+    pub obstacles: [Option<SharedObstacle>; MAXIMUM_NUMBER_OF_SHARED_OBSTACLES],
+}
+
+/// Obstacle position in the field frame with centimeter resolution to fit into the message budget
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SharedObstacle {
+    x_in_centimeters: i16,
+    y_in_centimeters: i16,
+}
+
+impl SharedObstacle {
+    pub fn new(position: Point2<Field>) -> Self {
+        let to_centimeters = |meters: f32| {
+            (meters * 100.0)
+                .round()
+                .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+        };
+        Self {
+            x_in_centimeters: to_centimeters(position.x()),
+            y_in_centimeters: to_centimeters(position.y()),
+        }
+    }
+
+    pub fn position(&self) -> Point2<Field> {
+        point![
+            self.x_in_centimeters as f32 / 100.0,
+            self.y_in_centimeters as f32 / 100.0
+        ]
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
@@ -38,7 +74,18 @@ pub struct BallPosition<Frame> {
 pub const HULKS_TEAM_NUMBER: u8 = 24;
 
 #[derive(
-    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize, SerializeHierarchy,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    SerializeHierarchy,
 )]
 pub enum PlayerNumber {
     One,
@@ -71,9 +118,11 @@ impl Display for PlayerNumber {
 mod tests {
     use std::time::Duration;
 
-    use linear_algebra::{Point, Pose2};
+    use linear_algebra::{point, Point, Pose2};
 
-    use crate::{BallPosition, HulkMessage, PlayerNumber};
+    use crate::{
+        BallPosition, HulkMessage, PlayerNumber, SharedObstacle, MAXIMUM_NUMBER_OF_SHARED_OBSTACLES,
+    };
 
     #[test]
     fn maximum_hulk_message_size() {
@@ -81,12 +130,22 @@ mod tests {
             player_number: PlayerNumber::Seven,
             fallen: false,
             pose: Pose2::default(),
+            pose_standard_deviation: f32::MAX,
             ball_position: Some(BallPosition {
                 position: Point::origin(),
                 age: Duration::MAX,
             }),
+            ball_is_relayed: true,
             time_to_reach_kick_position: Some(Duration::MAX),
+            obstacles: [Some(SharedObstacle::default()); MAXIMUM_NUMBER_OF_SHARED_OBSTACLES],
         };
         assert!(bincode::serialize(&test_message).unwrap().len() <= 128)
     }
+
+    #[test]
+    fn shared_obstacles_keep_centimeter_resolution() {
+        let obstacle = SharedObstacle::new(point![-4.512, 2.996]);
+
+        assert_eq!(obstacle.position(), point![-4.51, 3.0]);
+    }
 }
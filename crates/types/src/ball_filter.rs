@@ -3,7 +3,7 @@ use std::time::SystemTime;
 use serde::{Deserialize, Serialize};
 
 use coordinate_systems::Ground;
-use linear_algebra::{vector, Point};
+use linear_algebra::{vector, Point, Vector2};
 use serialize_hierarchy::SerializeHierarchy;
 
 use crate::{
@@ -41,10 +41,16 @@ impl Hypothesis {
         configuration: &BallFilterParameters,
     ) -> BallPosition<Ground> {
         let selected_state = self.selected_state(configuration);
+        // the velocity of the resting model is only noise
+        let velocity = if self.is_resting(configuration) {
+            Vector2::zeros()
+        } else {
+            vector![selected_state.mean.z, selected_state.mean.w]
+        };
 
         BallPosition {
             position: Point::from(selected_state.mean.xy()),
-            velocity: vector![selected_state.mean.z, selected_state.mean.w],
+            velocity,
             last_seen: self.last_update,
         }
     }
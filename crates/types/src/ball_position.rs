@@ -1,4 +1,4 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 use serialize_hierarchy::SerializeHierarchy;
@@ -21,3 +21,100 @@ impl<Frame> Default for BallPosition<Frame> {
         }
     }
 }
+
+/// Predictions of a ball rolling with constant deceleration due to rolling friction
+///
+/// Without a positive deceleration the motion is unknown, the ball is then predicted to rest at its
+/// current position.
+impl<Frame> BallPosition<Frame> {
+    pub fn time_to_rest(&self, deceleration: f32) -> Duration {
+        if !is_valid_deceleration(deceleration) {
+            return Duration::ZERO;
+        }
+        Duration::try_from_secs_f32(self.velocity.norm() / deceleration).unwrap_or_default()
+    }
+
+    pub fn position_after(&self, duration: Duration, deceleration: f32) -> Point2<Frame> {
+        let speed = self.velocity.norm();
+        if speed == 0.0 || !is_valid_deceleration(deceleration) {
+            return self.position;
+        }
+        let time = duration.min(self.time_to_rest(deceleration)).as_secs_f32();
+        let travelled_distance = speed * time - 0.5 * deceleration * time.powi(2);
+        self.position + self.velocity * (travelled_distance / speed)
+    }
+
+    pub fn rest_position(&self, deceleration: f32) -> Point2<Frame> {
+        if !is_valid_deceleration(deceleration) {
+            return self.position;
+        }
+        self.position + self.velocity * (self.velocity.norm() / (2.0 * deceleration))
+    }
+
+    /// Positions until the ball comes to rest, sampled every `time_step`, including the rest position
+    pub fn predicted_trajectory(
+        &self,
+        deceleration: f32,
+        time_step: Duration,
+    ) -> Vec<Point2<Frame>> {
+        let time_to_rest = self.time_to_rest(deceleration);
+        let mut trajectory = Vec::new();
+        let mut time = Duration::ZERO;
+        while time < time_to_rest && !time_step.is_zero() {
+            trajectory.push(self.position_after(time, deceleration));
+            time += time_step;
+        }
+        trajectory.push(self.rest_position(deceleration));
+        trajectory
+    }
+}
+
+pub fn is_valid_deceleration(deceleration: f32) -> bool {
+    deceleration.is_finite() && deceleration > 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use coordinate_systems::Ground;
+    use linear_algebra::{point, vector};
+
+    use super::*;
+
+    fn rolling_ball() -> BallPosition<Ground> {
+        BallPosition {
+            position: point![1.0, 0.0],
+            velocity: vector![2.0, 0.0],
+            last_seen: UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn ball_comes_to_rest_after_rolling_distance() {
+        let ball = rolling_ball();
+
+        assert_eq!(ball.time_to_rest(0.5), Duration::from_secs(4));
+        assert_eq!(ball.rest_position(0.5), point![5.0, 0.0]);
+        assert_eq!(
+            ball.position_after(Duration::from_secs(10), 0.5),
+            point![5.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn ball_without_valid_deceleration_rests_at_its_position() {
+        let ball = rolling_ball();
+
+        for deceleration in [0.0, -0.5, f32::NAN, f32::INFINITY] {
+            assert_eq!(ball.time_to_rest(deceleration), Duration::ZERO);
+            assert_eq!(ball.rest_position(deceleration), ball.position);
+            assert_eq!(
+                ball.position_after(Duration::from_secs(1), deceleration),
+                ball.position
+            );
+            assert_eq!(
+                ball.predicted_trajectory(deceleration, Duration::from_millis(100)),
+                vec![ball.position]
+            );
+        }
+    }
+}
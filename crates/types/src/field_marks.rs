@@ -419,3 +419,43 @@ pub fn field_marks_from_field_dimensions(field_dimensions: &FieldDimensions) ->
         },
     ]
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct PointMark {
+    pub kind: PointMarkKind,
+    pub position: Point2<Field>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PointMarkKind {
+    GoalPost,
+    PenaltyMarker,
+    CenterCircle,
+}
+
+pub fn point_marks_from_field_dimensions(field_dimensions: &FieldDimensions) -> Vec<PointMark> {
+    let penalty_marker_x = field_dimensions.length / 2.0 - field_dimensions.penalty_marker_distance;
+    goal_post_positions(field_dimensions)
+        .into_iter()
+        .map(|position| PointMark {
+            kind: PointMarkKind::GoalPost,
+            position,
+        })
+        .chain([-penalty_marker_x, penalty_marker_x].map(|x| PointMark {
+            kind: PointMarkKind::PenaltyMarker,
+            position: point![x, 0.0],
+        }))
+        .chain([PointMark {
+            kind: PointMarkKind::CenterCircle,
+            position: Point2::origin(),
+        }])
+        .collect()
+}
+
+/// Centers of the four goal posts, the posts stand on the outer edge of the goal line
+pub fn goal_post_positions(field_dimensions: &FieldDimensions) -> [Point2<Field>; 4] {
+    let radius = field_dimensions.goal_post_diameter / 2.0;
+    let x = field_dimensions.length / 2.0 + radius - field_dimensions.line_width / 2.0;
+    let y = field_dimensions.goal_inner_width / 2.0 + radius;
+    [point![-x, -y], point![-x, y], point![x, -y], point![x, y]]
+}
@@ -41,16 +41,28 @@ impl EncodeJpeg for GrayscaleImage {
     const DEFAULT_QUALITY: u8 = 40;
     type Error = ImageError;
 
-    fn encode_as_jpeg(&self, quality: u8) -> Result<Vec<u8>, Self::Error> {
-        let gray_image = ImageBuffer::<Luma<u8>, &[u8]>::from_raw(
-            self.width,
-            self.height,
-            self.buffer.as_slice(),
-        )
-        .unwrap();
+    fn encode_as_jpeg(&self, quality: u8, downscale_factor: u32) -> Result<Vec<u8>, Self::Error> {
         let mut jpeg_image_buffer = vec![];
         let mut encoder = JpegEncoder::new_with_quality(&mut jpeg_image_buffer, quality);
-        encoder.encode_image(&gray_image)?;
+        if downscale_factor > 1 {
+            let gray_image = ImageBuffer::<Luma<u8>, _>::from_fn(
+                self.width / downscale_factor,
+                self.height / downscale_factor,
+                |x, y| {
+                    let index = y * downscale_factor * self.width + x * downscale_factor;
+                    Luma([self.buffer[index as usize]])
+                },
+            );
+            encoder.encode_image(&gray_image)?;
+        } else {
+            let gray_image = ImageBuffer::<Luma<u8>, &[u8]>::from_raw(
+                self.width,
+                self.height,
+                self.buffer.as_slice(),
+            )
+            .unwrap();
+            encoder.encode_image(&gray_image)?;
+        }
         Ok(jpeg_image_buffer)
     }
 }
@@ -68,6 +68,7 @@ pub mod sonar_values;
 pub mod step_adjustment;
 pub mod step_plan;
 pub mod support_foot;
+pub mod team_ball;
 pub mod walk_command;
 pub mod whistle;
 pub mod world_state;
@@ -1,4 +1,6 @@
-use nalgebra::{vector, Matrix3};
+use std::f32::consts::PI;
+
+use nalgebra::{vector, Matrix3, Rotation2};
 use serde::{Deserialize, Serialize};
 
 use coordinate_systems::{Field, Ground};
@@ -37,4 +39,69 @@ impl ScoredPose {
             score,
         }
     }
+
+    /// Pose rotated by 180 degrees around the field center
+    ///
+    /// The field is point symmetric, both poses explain the same field mark measurements.
+    pub fn mirrored(&self) -> Self {
+        let mean = self.state.mean;
+        Self {
+            state: MultivariateNormalDistribution {
+                mean: vector![-mean.x, -mean.y, Rotation2::new(mean.z + PI).angle()],
+                covariance: self.state.covariance,
+            },
+            score: self.score,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
+pub struct SymmetryCheck {
+    /// Number of observations agreeing with the best hypothesis
+    pub consistent_votes: usize,
+    /// Number of observations agreeing with the mirrored best hypothesis
+    pub mirrored_votes: usize,
+    /// Decaying sum of the vote differences, the best hypothesis is mirrored at the threshold
+    pub evidence: f32,
+    pub decision: SymmetryDecision,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, SerializeHierarchy,
+)]
+pub enum SymmetryDecision {
+    #[default]
+    Keep,
+    Mirror,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, SerializeHierarchy,
+)]
+pub enum LocalizationBackend {
+    /// Multiple hypotheses, each tracked by an unscented Kalman filter
+    #[default]
+    Hypotheses,
+    /// Monte Carlo localization
+    ParticleFilter,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, SerializeHierarchy)]
+pub struct Particle {
+    pub pose: Pose2<Field>,
+    pub weight: f32,
+}
+
+impl Particle {
+    /// Pose rotated by 180 degrees around the field center
+    pub fn mirrored(&self) -> Self {
+        let position = self.pose.position();
+        Self {
+            pose: Pose2::new(
+                linear_algebra::vector![-position.x(), -position.y()],
+                Rotation2::new(self.pose.angle() + PI).angle(),
+            ),
+            weight: self.weight,
+        }
+    }
 }
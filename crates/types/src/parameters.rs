@@ -266,6 +266,78 @@ pub struct SplNetworkParameters {
     pub striker_trusts_team_ball: Duration,
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
+pub struct ParticleFilterParameters {
+    /// Seed of the random number generator, fixed to make replays reproducible
+    pub seed: u64,
+    pub resampling: KldSamplingParameters,
+    /// Particles are resampled once their effective sample size drops below this fraction
+    pub minimum_effective_sample_size_ratio: f32,
+    /// Standard deviation of the distance between measured lines and their field marks
+    pub line_standard_deviation: f32,
+    /// Standard deviation of the distance between measured points and their point marks
+    pub point_standard_deviation: f32,
+    /// Distances to field marks are clamped to this to limit the influence of outliers
+    pub maximum_field_mark_distance: f32,
+    /// Smoothing factor of the long term average measurement likelihood
+    pub slow_average_factor: f32,
+    /// Smoothing factor of the short term average measurement likelihood
+    pub fast_average_factor: f32,
+    /// Standard deviation of x, y and angle of particles sampled around reset poses
+    pub reset_standard_deviation: Vector3<f32>,
+    /// Radius around the densest region of particles used for the pose estimate
+    pub estimate_radius: f32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
+pub struct KldSamplingParameters {
+    pub minimum_number_of_particles: usize,
+    pub maximum_number_of_particles: usize,
+    pub translation_bin_size: f32,
+    pub rotation_bin_size: f32,
+    /// Maximum Kullback-Leibler divergence between the sampled and the true distribution
+    pub error: f32,
+    /// Upper standard normal quantile of the probability that the error bound holds
+    pub quantile: f32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
+pub struct SymmetryResolutionParameters {
+    pub enable: bool,
+    /// Own ball observations and teammate messages older than this are not compared
+    pub observation_timeout: Duration,
+    /// Minimum distance by which the mirrored pose has to explain an observation better for a vote
+    pub distance_margin: f32,
+    /// Distance into the opponent half a robot may stand during set
+    pub own_half_margin: f32,
+    /// Factor for the score of hypotheses and the weight of particles that are rather explained by
+    /// their mirrored pose
+    pub mirrored_hypothesis_score_factor: f32,
+    /// Factor the evidence decays with per cycle
+    pub evidence_decay: f32,
+    /// Evidence at which the best hypothesis is mirrored
+    pub mirror_threshold: f32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
+pub struct TeamWorldModelParameters {
+    pub ball_observation_timeout: Duration,
+    /// Standard deviation along the line of sight per meter of ball distance
+    pub ball_distance_noise_factor: f32,
+    /// Standard deviation perpendicular to the line of sight per meter of ball distance
+    pub ball_bearing_noise_factor: f32,
+    /// Growth of the standard deviation per second of observation age in meters
+    pub ball_age_noise: f32,
+    /// Mahalanobis distance up to which two ball observations are considered consistent
+    pub ball_consistency_threshold: f32,
+    /// Obstacles shared by teammates closer than this to the receiving robot are the robot itself
+    pub shared_obstacle_self_distance: f32,
+    /// Obstacles closer than this to the position of a teammate are the teammate and not shared
+    pub teammate_obstacle_distance: f32,
+    /// Duration after which the position of a teammate from its last message is not used anymore
+    pub teammate_position_timeout: Duration,
+}
+
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
 pub enum MedianModeParameters {
     #[default]
@@ -309,8 +381,12 @@ pub struct BallFilterParameters {
     pub visible_validity_exponential_decay_factor: f32,
     pub hidden_validity_exponential_decay_factor: f32,
     pub validity_discard_threshold: f32,
-    pub velocity_decay_factor: f32,
+    /// Constant deceleration of a rolling ball in m/s²
+    ///
+    /// Balls are predicted to rest at their position if it is not positive.
+    pub rolling_friction_deceleration: f32,
     pub resting_ball_velocity_threshold: f32,
+    pub predicted_trajectory_time_step: Duration,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize, SerializeHierarchy)]
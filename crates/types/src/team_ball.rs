@@ -0,0 +1,31 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use coordinate_systems::Field;
+use linear_algebra::{Point2, Vector2};
+use nalgebra::Matrix2;
+use serde::{Deserialize, Serialize};
+use serialize_hierarchy::SerializeHierarchy;
+
+/// Ball position fused from the observations of all robots of the team
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, SerializeHierarchy)]
+pub struct TeamBall {
+    pub position: Point2<Field>,
+    /// Mean of the velocities of the observations, teammates do not share ball velocities
+    pub velocity: Vector2<Field>,
+    #[serialize_hierarchy(leaf)]
+    pub covariance: Matrix2<f32>,
+    pub last_seen: SystemTime,
+    pub number_of_observations: usize,
+}
+
+impl Default for TeamBall {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            velocity: Default::default(),
+            covariance: Default::default(),
+            last_seen: UNIX_EPOCH,
+            number_of_observations: 0,
+        }
+    }
+}
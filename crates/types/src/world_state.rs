@@ -1,4 +1,4 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
@@ -31,17 +31,30 @@ pub struct BallState {
     pub ball_in_ground: Point2<Ground>,
     pub ball_in_field: Point2<Field>,
     pub ball_in_ground_velocity: Vector2<Ground>,
+    /// Where the ball comes to rest when rolling with constant deceleration
+    pub ball_in_ground_rest_position: Point2<Ground>,
+    /// Predicted crossing of the own goal line, if the ball reaches it before coming to rest
+    pub own_goal_line_crossing: Option<GoalLineCrossing>,
     pub last_seen_ball: SystemTime,
     pub penalty_shot_direction: Option<PenaltyShotDirection>,
     pub field_side: Side,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, SerializeHierarchy)]
+pub struct GoalLineCrossing {
+    pub position: Point2<Field>,
+    pub time_to_crossing: Duration,
+}
+
 impl BallState {
     pub fn new_at_center(ground_to_field: Isometry2<Ground, Field>) -> Self {
+        let ball_in_ground = ground_to_field.inverse() * Point2::origin();
         Self {
             ball_in_field: Point2::origin(),
-            ball_in_ground: ground_to_field.inverse() * Point2::origin(),
+            ball_in_ground,
             ball_in_ground_velocity: Vector2::zeros(),
+            ball_in_ground_rest_position: ball_in_ground,
+            own_goal_line_crossing: None,
             last_seen_ball: UNIX_EPOCH,
             penalty_shot_direction: Default::default(),
             field_side: Side::Left,
@@ -108,8 +108,12 @@ impl EncodeJpeg for YCbCr422Image {
     const DEFAULT_QUALITY: u8 = 40;
     type Error = ImageError;
 
-    fn encode_as_jpeg(&self, quality: u8) -> Result<Vec<u8>, Self::Error> {
-        let rgb_image: RgbImage = self.into();
+    fn encode_as_jpeg(&self, quality: u8, downscale_factor: u32) -> Result<Vec<u8>, Self::Error> {
+        let rgb_image = if downscale_factor > 1 {
+            self.downscaled_rgb_image(downscale_factor)
+        } else {
+            self.into()
+        };
         let mut jpeg_buffer = vec![];
         let mut encoder = JpegEncoder::new_with_quality(&mut jpeg_buffer, quality);
         encoder.encode_image(&rgb_image)?;
@@ -222,6 +226,18 @@ impl YCbCr422Image {
         self.width_422 * 2
     }
 
+    /// Converts every `downscale_factor`-th pixel in both directions to RGB
+    fn downscaled_rgb_image(&self, downscale_factor: u32) -> RgbImage {
+        RgbImage::from_fn(
+            self.width() / downscale_factor,
+            self.height / downscale_factor,
+            |x, y| {
+                let color: Rgb = self.at(x * downscale_factor, y * downscale_factor).into();
+                image::Rgb([color.r, color.g, color.b])
+            },
+        )
+    }
+
     pub fn height(&self) -> u32 {
         self.height
     }
@@ -0,0 +1,180 @@
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use context_attribute::context;
+use coordinate_systems::Ground;
+use filtering::mean_clustering::MeanClustering;
+use framework::{AdditionalOutput, MainOutput};
+use linear_algebra::{distance, point, Point2};
+use projection::{camera_matrix::CameraMatrix, Projection};
+use types::{
+    color::Intensity,
+    detected_feet::CountedCluster,
+    field_border::FieldBorder,
+    image_segments::{ImageSegments, ScanLine},
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct GoalPostDetection {}
+
+#[context]
+pub struct CreationContext {}
+
+#[context]
+pub struct CycleContext {
+    candidates_in_ground:
+        AdditionalOutput<Vec<Point2<Ground>>, "goal_post_detection.candidates_in_ground">,
+
+    enable: Parameter<bool, "goal_post_detection.$cycler_instance.enable">,
+    maximum_cluster_distance:
+        Parameter<f32, "goal_post_detection.$cycler_instance.maximum_cluster_distance">,
+    maximum_post_width: Parameter<f32, "goal_post_detection.$cycler_instance.maximum_post_width">,
+    minimum_luminance: Parameter<u8, "goal_post_detection.$cycler_instance.minimum_luminance">,
+    minimum_samples_per_post:
+        Parameter<usize, "goal_post_detection.$cycler_instance.minimum_samples_per_post">,
+
+    camera_matrix: RequiredInput<Option<CameraMatrix>, "camera_matrix?">,
+    field_border: RequiredInput<Option<FieldBorder>, "field_border?">,
+    image_segments: Input<ImageSegments, "image_segments">,
+}
+
+#[context]
+#[derive(Default)]
+pub struct MainOutputs {
+    pub detected_goal_posts: MainOutput<Vec<Point2<Ground>>>,
+}
+
+impl GoalPostDetection {
+    pub fn new(_context: CreationContext) -> Result<Self> {
+        Ok(Self {})
+    }
+
+    pub fn cycle(&mut self, mut context: CycleContext) -> Result<MainOutputs> {
+        if !context.enable || context.field_border.border_lines.is_empty() {
+            return Ok(MainOutputs::default());
+        }
+
+        let candidates: Vec<_> = context
+            .image_segments
+            .scan_grid
+            .vertical_scan_lines
+            .iter()
+            .filter_map(|scan_line| {
+                find_candidate(
+                    scan_line,
+                    context.field_border,
+                    context.camera_matrix,
+                    *context.minimum_luminance,
+                )
+            })
+            .collect();
+        context
+            .candidates_in_ground
+            .fill_if_subscribed(|| candidates.clone());
+
+        let detected_goal_posts = group_candidates(
+            &candidates,
+            *context.maximum_cluster_distance,
+            *context.maximum_post_width,
+            *context.minimum_samples_per_post,
+        );
+        Ok(MainOutputs {
+            detected_goal_posts: detected_goal_posts.into(),
+        })
+    }
+}
+
+/// Foot point of a bright object which covers the field border on this scan line
+///
+/// Goal posts stand on the goal line and reach far above the field border. The
+/// consecutive non-field segments containing the border crossing therefore start
+/// outside and end inside the field. Their end is the foot point of the post.
+fn find_candidate(
+    scan_line: &ScanLine,
+    field_border: &FieldBorder,
+    camera_matrix: &CameraMatrix,
+    minimum_luminance: u8,
+) -> Option<Point2<Ground>> {
+    let x = scan_line.position as f32;
+    let mut run_start = None;
+    for segment in &scan_line.segments {
+        let is_bright =
+            segment.field_color == Intensity::Low && segment.color.y >= minimum_luminance;
+        if !is_bright {
+            run_start = None;
+            continue;
+        }
+        let run_start = *run_start.get_or_insert(segment.start);
+        let starts_outside_field = !field_border.is_inside_field(point![x, run_start as f32]);
+        let ends_inside_field = field_border.is_inside_field(point![x, segment.end as f32]);
+        if starts_outside_field && ends_inside_field {
+            return camera_matrix
+                .pixel_to_ground(point![x, segment.end as f32])
+                .ok();
+        }
+    }
+    None
+}
+
+/// Groups candidates of neighboring scan lines and keeps groups as narrow as a goal post
+///
+/// Robots covering the field border produce wider groups and are rejected.
+fn group_candidates(
+    candidates: &[Point2<Ground>],
+    maximum_cluster_distance: f32,
+    maximum_post_width: f32,
+    minimum_samples_per_post: usize,
+) -> Vec<Point2<Ground>> {
+    let mut groups: Vec<Vec<Point2<Ground>>> = Vec::new();
+    for &candidate in candidates {
+        match groups.last_mut() {
+            Some(group)
+                if distance(*group.last().unwrap(), candidate) < maximum_cluster_distance =>
+            {
+                group.push(candidate)
+            }
+            _ => groups.push(vec![candidate]),
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|group| {
+            group.len() >= minimum_samples_per_post
+                && distance(group[0], *group.last().unwrap()) <= maximum_post_width
+        })
+        .map(|group| {
+            let mut cluster = CountedCluster {
+                mean: group[0],
+                samples: 1,
+            };
+            for &candidate in &group[1..] {
+                cluster.push(candidate);
+            }
+            cluster.mean
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn narrow_groups_are_goal_posts_and_wide_groups_are_rejected() {
+        let candidates = vec![
+            point![4.0, 1.0],
+            point![4.0, 1.02],
+            point![4.0, 1.04],
+            point![3.0, -1.0],
+            point![3.0, -1.1],
+            point![3.0, -1.2],
+            point![3.0, -1.3],
+            point![5.0, 0.0],
+        ];
+
+        let goal_posts = group_candidates(&candidates, 0.15, 0.15, 2);
+
+        assert_eq!(goal_posts.len(), 1);
+        assert!(distance(goal_posts[0], point![4.0, 1.02]) < 1e-5);
+    }
+}
@@ -0,0 +1,192 @@
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use context_attribute::context;
+use coordinate_systems::Ground;
+use filtering::mean_clustering::MeanClustering;
+use framework::{AdditionalOutput, MainOutput};
+use linear_algebra::{distance, point, Point2};
+use projection::{camera_matrix::CameraMatrix, Projection};
+use types::{
+    ball::Ball,
+    color::Intensity,
+    detected_feet::CountedCluster,
+    filtered_segments::FilteredSegments,
+    image_segments::{EdgeType, Segment},
+    line_data::LineData,
+};
+
+#[derive(Deserialize, Serialize)]
+pub struct PenaltyMarkerDetection {}
+
+#[context]
+pub struct CreationContext {}
+
+#[context]
+pub struct CycleContext {
+    candidates_in_ground:
+        AdditionalOutput<Vec<Point2<Ground>>, "penalty_marker_detection.candidates_in_ground">,
+
+    enable: Parameter<bool, "penalty_marker_detection.$cycler_instance.enable">,
+    maximum_cluster_distance:
+        Parameter<f32, "penalty_marker_detection.$cycler_instance.maximum_cluster_distance">,
+    maximum_projected_segment_length: Parameter<
+        f32,
+        "penalty_marker_detection.$cycler_instance.maximum_projected_segment_length",
+    >,
+    minimum_distance_to_lines:
+        Parameter<f32, "penalty_marker_detection.$cycler_instance.minimum_distance_to_lines">,
+    minimum_samples_per_cluster:
+        Parameter<usize, "penalty_marker_detection.$cycler_instance.minimum_samples_per_cluster">,
+
+    balls: RequiredInput<Option<Vec<Ball>>, "balls?">,
+    camera_matrix: RequiredInput<Option<CameraMatrix>, "camera_matrix?">,
+    filtered_segments: Input<FilteredSegments, "filtered_segments">,
+    line_data: RequiredInput<Option<LineData>, "line_data?">,
+}
+
+#[context]
+#[derive(Default)]
+pub struct MainOutputs {
+    pub detected_penalty_markers: MainOutput<Vec<Point2<Ground>>>,
+}
+
+impl PenaltyMarkerDetection {
+    pub fn new(_context: CreationContext) -> Result<Self> {
+        Ok(Self {})
+    }
+
+    pub fn cycle(&mut self, mut context: CycleContext) -> Result<MainOutputs> {
+        if !context.enable {
+            return Ok(MainOutputs::default());
+        }
+
+        let candidates = extract_candidates(
+            context.filtered_segments,
+            context.camera_matrix,
+            context.balls,
+            context.line_data,
+            *context.maximum_projected_segment_length,
+            *context.minimum_distance_to_lines,
+        );
+        context
+            .candidates_in_ground
+            .fill_if_subscribed(|| candidates.clone());
+
+        let detected_penalty_markers =
+            cluster_candidates(candidates, *context.maximum_cluster_distance)
+                .into_iter()
+                .filter(|cluster| cluster.samples >= *context.minimum_samples_per_cluster)
+                .map(|cluster| cluster.mean)
+                .collect();
+        Ok(MainOutputs {
+            detected_penalty_markers: detected_penalty_markers.into(),
+        })
+    }
+}
+
+/// Centers of short bright segments which are neither part of a line nor of a ball
+///
+/// A penalty marker crosses only a few scan lines, each with a segment about as long as the marker.
+fn extract_candidates(
+    filtered_segments: &FilteredSegments,
+    camera_matrix: &CameraMatrix,
+    balls: &[Ball],
+    line_data: &LineData,
+    maximum_projected_segment_length: f32,
+    minimum_distance_to_lines: f32,
+) -> Vec<Point2<Ground>> {
+    let minimum_squared_distance_to_lines = minimum_distance_to_lines.powi(2);
+    filtered_segments
+        .scan_grid
+        .vertical_scan_lines
+        .iter()
+        .flat_map(|scan_line| {
+            scan_line
+                .segments
+                .iter()
+                .map(move |segment| (scan_line.position, segment))
+        })
+        .filter(|(scan_line_position, segment)| {
+            is_candidate_segment(segment)
+                && !balls.iter().any(|ball| {
+                    ball.image_location
+                        .contains(point![*scan_line_position as f32, segment.center() as f32])
+                })
+        })
+        .filter_map(|(scan_line_position, segment)| {
+            let start = camera_matrix
+                .pixel_to_ground(point![scan_line_position as f32, segment.start as f32])
+                .ok()?;
+            let end = camera_matrix
+                .pixel_to_ground(point![scan_line_position as f32, segment.end as f32])
+                .ok()?;
+            if distance(start, end) > maximum_projected_segment_length {
+                return None;
+            }
+            camera_matrix
+                .pixel_to_ground(point![scan_line_position as f32, segment.center() as f32])
+                .ok()
+        })
+        .filter(|candidate| {
+            line_data.lines.iter().all(|line| {
+                line.squared_distance_to_segment(*candidate) > minimum_squared_distance_to_lines
+            })
+        })
+        .collect()
+}
+
+fn is_candidate_segment(segment: &Segment) -> bool {
+    segment.start_edge_type == EdgeType::Rising
+        && segment.end_edge_type == EdgeType::Falling
+        && segment.field_color == Intensity::Low
+}
+
+fn cluster_candidates(
+    candidates: Vec<Point2<Ground>>,
+    maximum_cluster_distance: f32,
+) -> Vec<CountedCluster> {
+    let mut clusters: Vec<CountedCluster> = Vec::new();
+    for candidate in candidates {
+        let nearest_cluster = clusters
+            .iter_mut()
+            .map(|cluster| {
+                let distance = distance(cluster.mean, candidate);
+                (cluster, distance)
+            })
+            .filter(|(_, distance)| *distance < maximum_cluster_distance)
+            .min_by(|(_, left_distance), (_, right_distance)| {
+                left_distance.total_cmp(right_distance)
+            });
+        match nearest_cluster {
+            Some((cluster, _)) => cluster.push(candidate),
+            None => clusters.push(CountedCluster {
+                mean: candidate,
+                samples: 1,
+            }),
+        }
+    }
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_candidates_form_one_cluster() {
+        let candidates = vec![
+            point![1.0, 0.0],
+            point![1.02, 0.01],
+            point![0.98, -0.01],
+            point![2.0, 1.0],
+        ];
+
+        let clusters = cluster_candidates(candidates, 0.1);
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].samples, 3);
+        assert!(distance(clusters[0].mean, point![1.0, 0.0]) < 1e-5);
+        assert_eq!(clusters[1].samples, 1);
+    }
+}
@@ -16,11 +16,16 @@ spawn_robot(7)
 local game_end_time = 15000
 local goal_scored = false
 
+expect_within(game_end_time, function() return goal_scored end, "No goal was scored!")
+
 function on_goal()
     print("Goal scored, resetting ball!")
     print("Ball: " .. inspect(state.ball))
     print("Ball was at x: " .. state.ball.position[1] .. " y: " .. state.ball.position[2])
     state.ball = nil
+    if not goal_scored then
+        record_metric("first_goal_cycle", state.cycle_count)
+    end
     goal_scored = true
     game_end_time = state.cycle_count + 200
 end
@@ -56,9 +61,6 @@ function on_cycle()
     end
 
     if state.cycle_count == game_end_time then
-        if not goal_scored then
-            error("No goal was scored!")
-        end
         state.finished = true
     end
 end
@@ -0,0 +1,63 @@
+local inspect = require 'inspect'
+
+function spawn_robot(number)
+    table.insert(state.robots, create_robot(number))
+end
+
+spawn_robot(1)
+spawn_robot(2)
+spawn_robot(3)
+spawn_robot(4)
+spawn_robot(5)
+spawn_robot(6)
+spawn_robot(7)
+
+table.insert(state.opponents, create_opponent("Striker", { 1.0, 1.5 }))
+table.insert(state.opponents, create_opponent("Striker", { 1.0, -1.5 }))
+table.insert(state.opponents, create_opponent("Goalkeeper", { 4.3, 0.0 }))
+
+local game_end_time = 15000
+local goals = { Hulks = 0, Opponent = 0 }
+
+function on_goal(scoring_team)
+    print("Goal scored by " .. scoring_team .. ", resetting ball!")
+    print("Ball: " .. inspect(state.ball))
+    goals[scoring_team] = goals[scoring_team] + 1
+    record_metric("goals_hulks", goals.Hulks)
+    record_metric("goals_opponent", goals.Opponent)
+    state.ball = nil
+end
+
+function on_cycle()
+    if state.ball == nil and state.cycle_count % 1000 == 0 then
+        state.ball = {
+            position = { 0.0, 0.0 },
+            velocity = { 0.0, 0.0 },
+        }
+    end
+
+    if state.cycle_count == 100 then
+        state.filtered_game_controller_state.game_state = {
+            Ready = {
+                kicking_team = "Hulks",
+            }
+        }
+    end
+
+    if state.cycle_count == 1600 then
+        state.filtered_game_controller_state.game_state = "Set"
+    end
+
+    if state.cycle_count == 1700 then
+        state.filtered_game_controller_state.game_state = {
+            Playing = {
+                ball_is_free = true,
+                kick_off = true
+            }
+        }
+    end
+
+    if state.cycle_count == game_end_time then
+        state.finished = true
+    end
+end
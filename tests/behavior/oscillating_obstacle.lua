@@ -4,6 +4,8 @@ end
 
 spawn_robot(7)
 
+state.seed = 42
+
 state.filtered_game_controller_state.game_state = {
   Playing = {
     ball_is_free = true,
@@ -15,6 +15,31 @@ spawn_robot(7)
 
 local game_end_time = 15000.0
 
+local function has_replacement_keeper()
+    for number = 2, 7 do
+        if role(number) == "ReplacementKeeper" then
+            return true
+        end
+    end
+    return false
+end
+
+local function expect_replacement_keeper(cycles)
+    expect_within(
+        cycles,
+        has_replacement_keeper,
+        "No replacement keeper while the keeper is penalized since cycle " .. state.cycle_count
+    )
+end
+
+local function expect_keeper_to_return(cycles)
+    expect_within(
+        cycles,
+        function() return role(1) == "Keeper" and not has_replacement_keeper() end,
+        "Keeper did not take over again after being unpenalized at cycle " .. state.cycle_count
+    )
+end
+
 function on_goal()
     print("Goal scored, resetting ball!")
     print("Ball: " .. inspect(state.ball))
@@ -54,6 +79,7 @@ function on_cycle()
     end
 
     if state.cycle_count == 1750 then
+        expect_replacement_keeper(400)
         penalize(1);
         state.filtered_game_controller_state.penalties.one = {
             Manual = {
@@ -67,6 +93,7 @@ function on_cycle()
     end
 
     if state.cycle_count == 2150 then
+        expect_keeper_to_return(1000)
         unpenalize(1);
         state.filtered_game_controller_state.penalties.one = nil;
     end
@@ -79,6 +106,7 @@ function on_cycle()
     end
 
     if state.cycle_count == 3600 then
+        expect_replacement_keeper(300)
         penalize(1);
         state.filtered_game_controller_state.penalties.one = {
             Manual = {
@@ -92,6 +120,7 @@ function on_cycle()
     end
 
     if state.cycle_count == 3900 then
+        expect_keeper_to_return(1000)
         unpenalize(1);
         state.filtered_game_controller_state.penalties.one = nil;
     end
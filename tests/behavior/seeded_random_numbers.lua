@@ -0,0 +1,8 @@
+record_metric("top_level_random_number", math.random())
+
+state.seed = 7
+
+function on_cycle()
+    record_metric("callback_random_number", math.random())
+    state.finished = true
+end
@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use coordinate_systems::Field;
+use linear_algebra::{point, vector, Point2};
+
+use crate::state::Ball;
+
+const BALL_RADIUS: f32 = 0.05;
+/// Constant deceleration of a rolling ball on the carpet
+const ROLLING_DECELERATION: f32 = 0.5;
+const GOAL_POST_RADIUS: f32 = 0.05;
+pub const GOAL_LINE_X: f32 = 4.5;
+const GOAL_POST_Y: f32 = 0.8;
+const GOAL_POSTS: [(f32, f32); 4] = [
+    (GOAL_LINE_X, GOAL_POST_Y),
+    (GOAL_LINE_X, -GOAL_POST_Y),
+    (-GOAL_LINE_X, GOAL_POST_Y),
+    (-GOAL_LINE_X, -GOAL_POST_Y),
+];
+/// Half of the opening between the inner sides of the goal posts
+pub const GOAL_HALF_WIDTH: f32 = GOAL_POST_Y - GOAL_POST_RADIUS;
+/// Field length and width including the border strip
+const HALF_BORDER_LENGTH: f32 = 5.2;
+const HALF_BORDER_WIDTH: f32 = 3.7;
+const GOAL_POST_RESTITUTION: f32 = 0.6;
+const BORDER_RESTITUTION: f32 = 0.5;
+const FOOTPRINT_RESTITUTION: f32 = 0.3;
+pub const ROBOT_FOOTPRINT_RADIUS: f32 = 0.1;
+
+/// Area covered by the feet of a robot which the ball bounces off
+pub struct Footprint {
+    pub position: Point2<Field>,
+    pub radius: f32,
+}
+
+/// Advances the ball by one time step
+///
+/// The ball rolls with constant deceleration and bounces off goal posts, the field border and
+/// robot footprints.
+pub fn step_ball(ball: &mut Ball, footprints: &[Footprint], time_step: Duration) {
+    let time_step = time_step.as_secs_f32();
+    ball.position += ball.velocity * time_step;
+
+    let speed = ball.velocity.norm();
+    ball.velocity = if speed > ROLLING_DECELERATION * time_step {
+        ball.velocity * ((speed - ROLLING_DECELERATION * time_step) / speed)
+    } else {
+        vector![0.0, 0.0]
+    };
+
+    for (x, y) in GOAL_POSTS {
+        collide_with_circle(ball, point![x, y], GOAL_POST_RADIUS, GOAL_POST_RESTITUTION);
+    }
+    for footprint in footprints {
+        collide_with_circle(
+            ball,
+            footprint.position,
+            footprint.radius,
+            FOOTPRINT_RESTITUTION,
+        );
+    }
+    collide_with_border(ball);
+}
+
+/// Whether the ball has crossed one of the goal lines between the goal posts
+pub fn is_in_goal(ball: &Ball) -> bool {
+    ball.position.x().abs() > GOAL_LINE_X && ball.position.y().abs() < GOAL_HALF_WIDTH
+}
+
+fn collide_with_circle(ball: &mut Ball, center: Point2<Field>, radius: f32, restitution: f32) {
+    let offset = ball.position - center;
+    let minimum_distance = radius + BALL_RADIUS;
+    let distance = offset.norm();
+    if distance >= minimum_distance || distance < f32::EPSILON {
+        return;
+    }
+
+    let normal = offset / distance;
+    ball.position = center + normal * minimum_distance;
+    let normal_velocity = ball.velocity.dot(normal);
+    if normal_velocity < 0.0 {
+        ball.velocity -= normal * ((1.0 + restitution) * normal_velocity);
+    }
+}
+
+fn collide_with_border(ball: &mut Ball) {
+    let maximum_x = HALF_BORDER_LENGTH - BALL_RADIUS;
+    let maximum_y = HALF_BORDER_WIDTH - BALL_RADIUS;
+    let mut position = ball.position;
+    let mut velocity = ball.velocity;
+    if position.x().abs() > maximum_x {
+        position = point![maximum_x.copysign(position.x()), position.y()];
+        velocity = vector![-velocity.x() * BORDER_RESTITUTION, velocity.y()];
+    }
+    if position.y().abs() > maximum_y {
+        position = point![position.x(), maximum_y.copysign(position.y())];
+        velocity = vector![velocity.x(), -velocity.y() * BORDER_RESTITUTION];
+    }
+    ball.position = position;
+    ball.velocity = velocity;
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use linear_algebra::Vector2;
+
+    use super::*;
+
+    fn ball(position: Point2<Field>, velocity: Vector2<Field>) -> Ball {
+        Ball { position, velocity }
+    }
+
+    #[test]
+    fn rolling_ball_decelerates_constantly() {
+        let mut ball = ball(point![0.0, 0.0], vector![1.0, 0.0]);
+        step_ball(&mut ball, &[], Duration::from_millis(100));
+
+        assert_relative_eq!(ball.position, point![0.1, 0.0]);
+        assert_relative_eq!(ball.velocity, vector![0.95, 0.0]);
+    }
+
+    #[test]
+    fn slow_ball_comes_to_rest() {
+        let mut ball = ball(point![0.0, 0.0], vector![0.0, 0.02]);
+        step_ball(&mut ball, &[], Duration::from_millis(100));
+        assert_relative_eq!(ball.velocity, vector![0.0, 0.0]);
+
+        let rest_position = ball.position;
+        step_ball(&mut ball, &[], Duration::from_millis(100));
+        assert_relative_eq!(ball.position, rest_position);
+        assert_relative_eq!(ball.velocity, vector![0.0, 0.0]);
+    }
+
+    #[test]
+    fn ball_bounces_off_field_border() {
+        let mut ball = ball(point![0.0, 3.6], vector![0.0, 2.0]);
+        step_ball(&mut ball, &[], Duration::from_millis(100));
+
+        assert_relative_eq!(ball.position, point![0.0, 3.65]);
+        assert_relative_eq!(ball.velocity, vector![0.0, -0.975]);
+    }
+
+    #[test]
+    fn ball_bounces_off_footprint() {
+        let footprint = Footprint {
+            position: point![1.0, 0.0],
+            radius: ROBOT_FOOTPRINT_RADIUS,
+        };
+        let mut ball = ball(point![0.8, 0.0], vector![1.0, 0.0]);
+        step_ball(&mut ball, &[footprint], Duration::from_millis(100));
+
+        assert_relative_eq!(ball.position, point![0.85, 0.0]);
+        assert_relative_eq!(ball.velocity, vector![-0.285, 0.0], epsilon = 1e-6);
+    }
+
+    #[test]
+    fn only_balls_between_the_goal_posts_are_in_goal() {
+        let in_goal = |x, y| is_in_goal(&ball(point![x, y], vector![0.0, 0.0]));
+
+        assert!(in_goal(4.6, 0.5));
+        assert!(in_goal(-4.6, -0.5));
+        assert!(!in_goal(4.6, -1.5));
+        assert!(!in_goal(-4.6, -2.0));
+        assert!(!in_goal(4.4, 0.0));
+    }
+}
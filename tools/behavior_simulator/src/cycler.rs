@@ -9,6 +9,7 @@ use control::{
     motion::look_around::LookAround,
     role_assignment::{self, RoleAssignment},
     rule_obstacle_composer::RuleObstacleComposer,
+    team_world_model::{self, TeamWorldModel},
     time_to_reach_kick_position::{self, TimeToReachKickPosition},
     world_state_composer::{self, WorldStateComposer},
 };
@@ -43,6 +44,7 @@ pub struct BehaviorCycler {
     look_around: LookAround,
     role_assignment: RoleAssignment,
     rule_obstacle_composer: RuleObstacleComposer,
+    team_world_model: TeamWorldModel,
     world_state_composer: WorldStateComposer,
     time_to_reach_kick_position: TimeToReachKickPosition,
 }
@@ -76,6 +78,8 @@ impl BehaviorCycler {
             control::rule_obstacle_composer::CreationContext {},
         )
         .wrap_err("failed to create node `RuleObstacleComposer`")?;
+        let team_world_model = TeamWorldModel::new(team_world_model::CreationContext {})
+            .wrap_err("failed to create node `TeamWorldModel`")?;
         let world_state_composer =
             WorldStateComposer::new(world_state_composer::CreationContext::new())
                 .wrap_err("failed to create node `WorldStateComposer`")?;
@@ -91,6 +95,7 @@ impl BehaviorCycler {
             look_around,
             role_assignment,
             rule_obstacle_composer,
+            team_world_model,
             world_state_composer,
         })
     }
@@ -140,12 +145,17 @@ impl BehaviorCycler {
                         .as_ref(),
                     &own_database.main_outputs.primary_state,
                     own_database.main_outputs.ground_to_field.as_ref(),
+                    own_database
+                        .main_outputs
+                        .ground_to_field_standard_deviation
+                        .as_ref(),
                     &own_database.main_outputs.cycle_time,
                     PerceptionInput {
-                        persistent: incoming_messages,
+                        persistent: incoming_messages.clone(),
                         temporary: Default::default(),
                     },
                     &mut cycler_state.time_to_reach_kick_position,
+                    &mut cycler_state.shared_obstacles,
                     &parameters.field_dimensions,
                     parameters.role_assignment.forced_role.as_ref(),
                     &parameters
@@ -155,6 +165,7 @@ impl BehaviorCycler {
                     &parameters.behavior.optional_roles,
                     &parameters.player_number,
                     &parameters.spl_network,
+                    &parameters.team_world_model,
                     &self.hardware_interface,
                 ))
                 .wrap_err("failed to execute cycle of node `RoleAssignment`")?;
@@ -163,6 +174,30 @@ impl BehaviorCycler {
                 main_outputs.network_robot_obstacles.value;
             own_database.main_outputs.role = main_outputs.role.value;
         }
+        {
+            let main_outputs = self
+                .team_world_model
+                .cycle(team_world_model::CycleContext::new(
+                    own_database.main_outputs.ball_position.as_ref(),
+                    &own_database.main_outputs.cycle_time,
+                    own_database.main_outputs.ground_to_field.as_ref(),
+                    own_database
+                        .main_outputs
+                        .ground_to_field_standard_deviation
+                        .as_ref(),
+                    &own_database.main_outputs.obstacles,
+                    PerceptionInput {
+                        persistent: incoming_messages,
+                        temporary: Default::default(),
+                    },
+                    &parameters.player_number,
+                    &parameters.team_world_model,
+                    &mut cycler_state.shared_obstacles,
+                ))
+                .wrap_err("failed to execute cycle of node `TeamWorldModel`")?;
+            own_database.main_outputs.team_ball_hypothesis =
+                main_outputs.team_ball_hypothesis.value;
+        }
         {
             let main_outputs = self
                 .ball_state_composer
@@ -171,13 +206,14 @@ impl BehaviorCycler {
                     own_database.main_outputs.ball_position.as_ref(),
                     own_database.main_outputs.penalty_shot_direction.as_ref(),
                     own_database.main_outputs.ground_to_field.as_ref(),
-                    own_database.main_outputs.team_ball.as_ref(),
+                    own_database.main_outputs.team_ball_hypothesis.as_ref(),
                     &own_database.main_outputs.primary_state,
                     own_database
                         .main_outputs
                         .filtered_game_controller_state
                         .as_ref(),
                     &parameters.field_dimensions,
+                    &parameters.ball_filter.rolling_friction_deceleration,
                 ))
                 .wrap_err("failed to execute cycle of node `BallStateComposer`")?;
             own_database.main_outputs.ball_state = main_outputs.ball_state.value;
@@ -1,15 +1,25 @@
 use std::{
     mem::take,
     sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use color_eyre::Result;
-use hardware::{NetworkInterface, RecordingInterface};
+use hardware::{NetworkInterface, RecordingInterface, TimeInterface};
 use types::messages::{IncomingMessage, OutgoingMessage};
 
-#[derive(Default)]
 pub struct Interfake {
     messages: Arc<Mutex<Vec<OutgoingMessage>>>,
+    now: Mutex<SystemTime>,
+}
+
+impl Default for Interfake {
+    fn default() -> Self {
+        Self {
+            messages: Default::default(),
+            now: Mutex::new(UNIX_EPOCH),
+        }
+    }
 }
 
 impl NetworkInterface for Interfake {
@@ -29,9 +39,26 @@ impl RecordingInterface for Interfake {
     }
 
     fn set_whether_to_record(&self, _enable: bool) {}
+
+    fn trigger_event_recording(&self, _timestamp: SystemTime) {}
+
+    fn get_last_event_recording_trigger(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+impl TimeInterface for Interfake {
+    fn get_now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
 }
 
 impl Interfake {
+    /// Sets the simulated time returned by [`TimeInterface::get_now`]
+    pub fn set_now(&self, now: SystemTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
     pub fn take_outgoing_messages(&self) -> Vec<OutgoingMessage> {
         take(&mut self.messages.lock().unwrap())
     }
@@ -1,11 +1,14 @@
 use hardware::{NetworkInterface, RecordingInterface, TimeInterface};
 
+pub mod ball_physics;
 pub mod cycler;
 pub mod interfake;
+pub mod opponent;
 pub mod robot;
 pub mod server;
 pub mod simulator;
 pub mod state;
+pub mod sweep;
 
 include!(concat!(env!("OUT_DIR"), "/generated_code.rs"));
 
@@ -1,18 +1,32 @@
-use std::{io::stdout, path::PathBuf, time::Instant};
+use std::{
+    fs::File,
+    io::{stdout, BufWriter},
+    path::PathBuf,
+    thread::available_parallelism,
+    time::Instant,
+};
 
 use chrono::Local;
 use clap::Parser;
-use color_eyre::{eyre::Context, install, Result};
+use color_eyre::{
+    eyre::{bail, Context},
+    install, Result,
+};
 use fern::{Dispatch, InitError};
 use log::LevelFilter;
 use tokio_util::sync::CancellationToken;
 
-use behavior_simulator::{server, simulator::Simulator};
+use behavior_simulator::{
+    server,
+    simulator::Simulator,
+    sweep::{run_sweep, write_summary, SummaryFormat, SweepConfiguration},
+};
 
 #[derive(Parser)]
 enum Arguments {
     Run(RunArguments),
     Serve(ServeArguments),
+    Sweep(SweepArguments),
 }
 
 #[derive(Parser)]
@@ -27,6 +41,27 @@ struct ServeArguments {
     scenario_file: PathBuf,
 }
 
+#[derive(Parser)]
+struct SweepArguments {
+    scenario_file: PathBuf,
+    /// JSON object mapping parameter paths to lists of values or `{"minimum": ..., "maximum": ...}` ranges
+    sweep_file: PathBuf,
+    /// Run this many random samples instead of all combinations of the listed values
+    #[arg(long)]
+    samples: Option<usize>,
+    /// Seed used to draw the random samples
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Number of simulations running in parallel, defaults to the number of CPUs
+    #[arg(short, long)]
+    jobs: Option<usize>,
+    /// File to write the summary of all runs into
+    #[arg(short, long)]
+    output: PathBuf,
+    #[arg(long, value_enum, default_value_t = SummaryFormat::Csv)]
+    format: SummaryFormat,
+}
+
 fn setup_logger(is_verbose: bool) -> Result<(), InitError> {
     Dispatch::new()
         .format(|out, message, record| {
@@ -56,6 +91,7 @@ fn main() -> Result<()> {
     match arguments {
         Arguments::Run(arguments) => run(arguments),
         Arguments::Serve(arguments) => serve(arguments),
+        Arguments::Sweep(arguments) => sweep(arguments),
     }
 }
 
@@ -87,3 +123,39 @@ fn serve(arguments: ServeArguments) -> Result<()> {
         arguments.scenario_file,
     )
 }
+
+fn sweep(arguments: SweepArguments) -> Result<()> {
+    let configuration = SweepConfiguration::read_from(&arguments.sweep_file)?;
+    let runs = match arguments.samples {
+        Some(samples) => configuration.sample(samples, arguments.seed)?,
+        None => configuration.grid()?,
+    };
+    let number_of_jobs = match arguments.jobs {
+        Some(jobs) => jobs,
+        None => available_parallelism()
+            .wrap_err("failed to determine number of CPUs")?
+            .get(),
+    };
+
+    let start = Instant::now();
+    let results = run_sweep(&arguments.scenario_file, runs, number_of_jobs);
+    let duration = Instant::now() - start;
+    println!(
+        "Took {:.2} seconds for {} runs",
+        duration.as_secs_f32(),
+        results.len()
+    );
+
+    let file = File::create(&arguments.output)
+        .wrap_err_with(|| format!("failed to create {}", arguments.output.display()))?;
+    write_summary(BufWriter::new(file), &results, arguments.format)?;
+
+    let number_of_failed_runs = results
+        .iter()
+        .filter(|result| result.error.is_some())
+        .count();
+    if number_of_failed_runs > 0 {
+        bail!("{number_of_failed_runs} of {} runs failed", results.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::{Field, Ground};
+use linear_algebra::{distance, point, Isometry2, Point2};
+use types::obstacles::Obstacle;
+
+use crate::{
+    ball_physics::{GOAL_HALF_WIDTH, GOAL_LINE_X},
+    state::Ball,
+};
+
+const WALKING_SPEED: f32 = 0.3;
+const KICK_DISTANCE: f32 = 0.2;
+const KICK_SPEED: f32 = 2.5;
+const KICK_COOLDOWN: Duration = Duration::from_secs(1);
+const ROBOT_RADIUS: f32 = 0.2;
+/// Goal line of the goal defended by the opponents, our robots play toward positive x
+const OPPONENT_GOAL_LINE: f32 = GOAL_LINE_X;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum OpponentPolicy {
+    /// Walks to the ball and kicks it toward our goal
+    Striker,
+    /// Stays on its goal line between ball and goal and clears the ball when it comes close
+    Goalkeeper,
+}
+
+/// Scripted robot of the opposing team which our robots perceive as obstacle
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Opponent {
+    pub policy: OpponentPolicy,
+    pub position: Point2<Field>,
+    #[serde(default)]
+    pub last_kick_time: Duration,
+}
+
+impl Opponent {
+    pub fn new(policy: OpponentPolicy, position: Point2<Field>) -> Self {
+        Self {
+            policy,
+            position,
+            last_kick_time: Duration::ZERO,
+        }
+    }
+
+    pub fn step(&mut self, ball: Option<&mut Ball>, time_elapsed: Duration, time_step: Duration) {
+        let Some(ball) = ball else {
+            return;
+        };
+
+        let target = match self.policy {
+            OpponentPolicy::Striker => ball.position,
+            OpponentPolicy::Goalkeeper => point![
+                OPPONENT_GOAL_LINE - ROBOT_RADIUS,
+                ball.position.y().clamp(-GOAL_HALF_WIDTH, GOAL_HALF_WIDTH)
+            ],
+        };
+        self.position +=
+            (target - self.position).cap_magnitude(WALKING_SPEED * time_step.as_secs_f32());
+
+        let can_kick = time_elapsed.saturating_sub(self.last_kick_time) > KICK_COOLDOWN;
+        if can_kick && distance(self.position, ball.position) < KICK_DISTANCE {
+            let our_goal_center = point![-OPPONENT_GOAL_LINE, 0.0];
+            ball.velocity = (our_goal_center - ball.position).normalize() * KICK_SPEED;
+            self.last_kick_time = time_elapsed;
+        }
+    }
+
+    pub fn obstacle(&self, ground_to_field: Isometry2<Ground, Field>) -> Obstacle {
+        Obstacle::robot(
+            ground_to_field.inverse() * self.position,
+            ROBOT_RADIUS,
+            ROBOT_RADIUS,
+        )
+    }
+}
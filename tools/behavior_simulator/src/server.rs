@@ -6,6 +6,7 @@ use std::{
 
 use crate::{
     cycler::Database,
+    opponent::Opponent,
     robot::to_player_number,
     simulator::{Frame, Simulator},
     state::Ball,
@@ -29,6 +30,7 @@ struct Parameters {
 struct MainOutputs {
     frame_count: usize,
     ball: Option<Ball>,
+    opponents: Vec<Opponent>,
     databases: Players<Option<Database>>,
 }
 
@@ -69,6 +71,7 @@ async fn timeline_server(
             outputs.main_outputs.frame_count = frames.len();
             let frame = &frames[parameters.selected_frame];
             outputs.main_outputs.ball = frame.ball.clone();
+            outputs.main_outputs.opponents = frame.opponents.clone();
             outputs.main_outputs.databases = frame.robots.clone();
         }
         outputs_changed.notify_waiters();
@@ -94,6 +97,7 @@ pub fn run(
     let parameter_slots = 3; // 2 for communication writer + 1 reader for timeline_server
     let communication_server = communication::server::Runtime::<Parameters>::start(
         addresses,
+        communication::server::AccessKeys::default(),
         "tools/behavior_simulator",
         "behavior_simulator".to_string(),
         "behavior_simulator".to_string(),
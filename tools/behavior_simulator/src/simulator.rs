@@ -1,11 +1,15 @@
-use std::{fs::read_to_string, path::Path, sync::Arc, time::Duration};
+use std::{collections::BTreeMap, fs::read_to_string, mem::take, path::Path, sync::Arc};
 
 use color_eyre::{
-    eyre::{eyre, WrapErr},
+    eyre::{bail, eyre, WrapErr},
     Result,
 };
-use mlua::{Error as LuaError, Function, Lua, LuaSerdeExt, SerializeOptions, Value};
+use mlua::{
+    Error as LuaError, Function, Lua, LuaSerdeExt, RegistryKey, SerializeOptions, ToLuaMulti, Value,
+};
 use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
+use serialize_hierarchy::SerializeHierarchy;
 
 use coordinate_systems::Field;
 use linear_algebra::{Isometry2, Point2, Vector2};
@@ -13,40 +17,92 @@ use types::{obstacles::Obstacle, players::Players};
 
 use crate::{
     cycler::Database,
+    opponent::{Opponent, OpponentPolicy},
     robot::to_player_number,
     robot::Robot,
     state::Ball,
     state::{Event, LuaRobot, State},
+    structs::Parameters,
 };
 
 const SERIALIZE_OPTIONS: SerializeOptions = SerializeOptions::new().serialize_none_to_null(false);
 
 pub struct Frame {
     pub ball: Option<Ball>,
+    pub opponents: Vec<Opponent>,
     pub robots: Players<Option<Database>>,
 }
 
+pub struct ExpectationFailure {
+    pub cycle: usize,
+    pub message: String,
+}
+
+struct PendingExpectation {
+    deadline: usize,
+    predicate: RegistryKey,
+    message: String,
+}
+
+#[derive(Default)]
+struct Expectations {
+    pending: Vec<PendingExpectation>,
+    failures: Vec<ExpectationFailure>,
+}
+
 pub struct Simulator {
     pub state: Arc<Mutex<State>>,
     pub frames: Vec<Frame>,
     lua: Lua,
+    expectations: Arc<Mutex<Expectations>>,
+    metrics: Arc<Mutex<BTreeMap<String, f64>>>,
 }
 
 impl Simulator {
     pub fn try_new() -> Result<Self> {
+        Self::try_new_with_parameter_overrides(Default::default())
+    }
+
+    /// Creates a simulator whose robots use the given values instead of their loaded parameters
+    ///
+    /// The keys are parameter paths, e.g. `behavior.role_positions.defender_y_offset`.
+    pub fn try_new_with_parameter_overrides(
+        parameter_overrides: BTreeMap<String, JsonValue>,
+    ) -> Result<Self> {
+        for path in parameter_overrides.keys() {
+            if !Parameters::exists(path) {
+                bail!("unknown parameter path '{path}'");
+            }
+        }
         let state = Arc::new(Mutex::new(State::default()));
 
         let lua = Lua::new();
         let create_robot = lua
-            .create_function(|lua, player_number: usize| {
+            .create_function(move |lua, player_number: usize| {
                 let player_number = to_player_number(player_number).map_err(LuaError::external)?;
-                let robot = Robot::try_new(player_number).map_err(LuaError::external)?;
+                let mut robot = Robot::try_new(player_number).map_err(LuaError::external)?;
+                for (path, value) in &parameter_overrides {
+                    robot
+                        .parameters
+                        .deserialize_path(path, value.clone())
+                        .map_err(LuaError::external)?;
+                }
                 Ok(lua.to_value(&LuaRobot::new(&robot)))
             })
             .wrap_err("failed to create function create_robot")?;
         lua.globals()
             .set("create_robot", create_robot)
             .wrap_err("failed to insert create_robot")?;
+        let create_opponent = lua
+            .create_function(|lua, (policy, position): (Value, Value)| {
+                let policy: OpponentPolicy = lua.from_value(policy)?;
+                let position = lua.from_value(position)?;
+                lua.to_value(&Opponent::new(policy, position))
+            })
+            .wrap_err("failed to create function create_opponent")?;
+        lua.globals()
+            .set("create_opponent", create_opponent)
+            .wrap_err("failed to insert create_opponent")?;
         let error = lua
             .create_function(|_lua, message: String| -> Result<(), LuaError> {
                 Err(LuaError::external(message))
@@ -56,13 +112,109 @@ impl Simulator {
             .set("error", error)
             .wrap_err("failed to insert create_robot")?;
 
+        let expectations = Arc::new(Mutex::new(Expectations::default()));
+        let expect = lua
+            .create_function({
+                let state = state.clone();
+                let expectations = expectations.clone();
+                move |_lua, (condition, message): (bool, String)| {
+                    if !condition {
+                        let cycle = state.lock().cycle_count;
+                        expectations
+                            .lock()
+                            .failures
+                            .push(ExpectationFailure { cycle, message });
+                    }
+                    Ok(())
+                }
+            })
+            .wrap_err("failed to create function expect")?;
+        lua.globals()
+            .set("expect", expect)
+            .wrap_err("failed to insert expect")?;
+        let expect_within = lua
+            .create_function({
+                let state = state.clone();
+                let expectations = expectations.clone();
+                move |lua, (cycles, predicate, message): (usize, Function, String)| {
+                    let deadline = state.lock().cycle_count + cycles;
+                    let predicate = lua.create_registry_value(predicate)?;
+                    expectations.lock().pending.push(PendingExpectation {
+                        deadline,
+                        predicate,
+                        message,
+                    });
+                    Ok(())
+                }
+            })
+            .wrap_err("failed to create function expect_within")?;
+        lua.globals()
+            .set("expect_within", expect_within)
+            .wrap_err("failed to insert expect_within")?;
+        let fail = lua
+            .create_function({
+                let state = state.clone();
+                let expectations = expectations.clone();
+                move |_lua, message: String| {
+                    let cycle = state.lock().cycle_count;
+                    expectations
+                        .lock()
+                        .failures
+                        .push(ExpectationFailure { cycle, message });
+                    Ok(())
+                }
+            })
+            .wrap_err("failed to create function fail")?;
+        lua.globals()
+            .set("fail", fail)
+            .wrap_err("failed to insert fail")?;
+
+        let role = lua
+            .create_function({
+                let state = state.clone();
+                move |_lua, player_number: usize| {
+                    let player_number =
+                        to_player_number(player_number).map_err(LuaError::external)?;
+                    Ok(state
+                        .lock()
+                        .robots
+                        .get(&player_number)
+                        .map(|robot| format!("{:?}", robot.database.main_outputs.role)))
+                }
+            })
+            .wrap_err("failed to create function role")?;
+        lua.globals()
+            .set("role", role)
+            .wrap_err("failed to insert role")?;
+
+        let metrics = Arc::new(Mutex::new(BTreeMap::new()));
+        let record_metric = lua
+            .create_function({
+                let metrics = metrics.clone();
+                move |_lua, (name, value): (String, f64)| {
+                    metrics.lock().insert(name, value);
+                    Ok(())
+                }
+            })
+            .wrap_err("failed to create function record_metric")?;
+        lua.globals()
+            .set("record_metric", record_metric)
+            .wrap_err("failed to insert record_metric")?;
+
         Ok(Self {
             state,
             lua,
             frames: Vec::new(),
+            expectations,
+            metrics,
         })
     }
 
+    /// Latest values of all metrics recorded by the scenario via `record_metric(name, value)`
+    pub fn metrics(&self) -> BTreeMap<String, f64> {
+        self.metrics.lock().clone()
+    }
+
     pub fn execute_script(&mut self, file_name: impl AsRef<Path>) -> Result<()> {
         self.serialze_state()?;
 
@@ -75,11 +227,25 @@ impl Simulator {
                 .to_str()
                 .ok_or_else(|| eyre!("filename is not valid unicode"))?,
         )?;
+        // seed before the top level code of the script runs, it may already draw random numbers
+        let seed = self.state.lock().seed;
+        self.seed_lua_random_number_generator()?;
         script
             .exec()
             .wrap_err("failed to execute scenario script")?;
 
-        self.deserialize_state()
+        self.deserialize_state()?;
+        if self.state.lock().seed != seed {
+            self.seed_lua_random_number_generator()?;
+        }
+        Ok(())
+    }
+
+    fn seed_lua_random_number_generator(&self) -> Result<()> {
+        self.lua
+            .load("math.randomseed(state.seed)")
+            .exec()
+            .wrap_err("failed to seed lua random number generator")
     }
 
     pub fn run(&mut self) -> Result<()> {
@@ -93,6 +259,7 @@ impl Simulator {
             }
             self.frames.push(Frame {
                 robots,
+                opponents: state.opponents.clone(),
                 ball: state.ball.clone(),
             });
 
@@ -101,13 +268,79 @@ impl Simulator {
             }
         }
 
+        self.report_expectation_failures()
+    }
+
+    /// Fails if any expectation failed or was not satisfied before the simulation finished
+    fn report_expectation_failures(&self) -> Result<()> {
+        let cycle = self.state.lock().cycle_count;
+        let mut expectations = self.expectations.lock();
+        for expectation in take(&mut expectations.pending) {
+            self.lua
+                .remove_registry_value(expectation.predicate)
+                .wrap_err("failed to remove expectation predicate")?;
+            expectations.failures.push(ExpectationFailure {
+                cycle,
+                message: format!(
+                    "{} (not satisfied until the simulation finished)",
+                    expectation.message
+                ),
+            });
+        }
+        if expectations.failures.is_empty() {
+            return Ok(());
+        }
+        let failures: Vec<_> = expectations
+            .failures
+            .iter()
+            .map(|failure| format!("  cycle {}: {}", failure.cycle, failure.message))
+            .collect();
+        bail!(
+            "{} expectations failed:\n{}",
+            failures.len(),
+            failures.join("\n")
+        )
+    }
+
+    fn check_pending_expectations(&self) -> Result<()> {
+        let cycle = self.state.lock().cycle_count;
+        let pending = take(&mut self.expectations.lock().pending);
+        let mut still_pending = Vec::new();
+        let mut failures = Vec::new();
+        for expectation in pending {
+            let predicate: Function = self
+                .lua
+                .registry_value(&expectation.predicate)
+                .wrap_err("failed to retrieve expectation predicate")?;
+            let is_satisfied: bool = predicate
+                .call(())
+                .wrap_err_with(|| format!("failed to evaluate '{}'", expectation.message))?;
+            if is_satisfied {
+                self.lua
+                    .remove_registry_value(expectation.predicate)
+                    .wrap_err("failed to remove expectation predicate")?;
+            } else if cycle >= expectation.deadline {
+                failures.push(ExpectationFailure {
+                    cycle,
+                    message: expectation.message,
+                });
+                self.lua
+                    .remove_registry_value(expectation.predicate)
+                    .wrap_err("failed to remove expectation predicate")?;
+            } else {
+                still_pending.push(expectation);
+            }
+        }
+        let mut expectations = self.expectations.lock();
+        expectations.pending.extend(still_pending);
+        expectations.failures.extend(failures);
         Ok(())
     }
 
     pub fn cycle(&mut self) -> Result<()> {
         let events = {
             let mut state = self.state.lock();
-            state.cycle(Duration::from_millis(12))?
+            state.cycle()?
         };
 
         self.serialze_state()?;
@@ -226,20 +459,27 @@ impl Simulator {
 
             for event in events {
                 match event {
-                    Event::Cycle => self.execute_event_callback("on_cycle")?,
-                    Event::Goal => self.execute_event_callback("on_goal")?,
+                    Event::Cycle => self.execute_event_callback("on_cycle", ())?,
+                    Event::Goal { scoring_team } => {
+                        self.execute_event_callback("on_goal", format!("{scoring_team:?}"))?
+                    }
                 }
             }
 
             Ok(())
         })?;
+        self.check_pending_expectations()?;
 
         self.deserialize_state()
     }
 
-    fn execute_event_callback(&self, name: &str) -> Result<(), LuaError> {
-        if let Ok(on_goal) = self.lua.globals().get::<_, Function>(name) {
-            on_goal.call(())?;
+    fn execute_event_callback<'lua>(
+        &'lua self,
+        name: &str,
+        arguments: impl ToLuaMulti<'lua>,
+    ) -> Result<(), LuaError> {
+        if let Ok(callback) = self.lua.globals().get::<_, Function>(name) {
+            callback.call(arguments)?;
         }
 
         Ok(())
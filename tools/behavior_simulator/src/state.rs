@@ -1,8 +1,8 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::BTreeMap,
     f32::consts::FRAC_PI_4,
     mem::take,
-    time::{Duration, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use color_eyre::Result;
@@ -26,14 +26,19 @@ use types::{
 };
 
 use crate::{
+    ball_physics::{is_in_goal, step_ball, Footprint, ROBOT_FOOTPRINT_RADIUS},
     cycler::Database,
+    opponent::Opponent,
     robot::Robot,
     structs::{control::AdditionalOutputs, Parameters},
 };
 
+/// Maximum distance between robot and ball at which kicks hit the ball
+const KICK_REACH: f32 = 0.35;
+
 pub enum Event {
     Cycle,
-    Goal,
+    Goal { scoring_team: Team },
 }
 
 #[derive(Default, Clone, Deserialize, Serialize, SerializeHierarchy)]
@@ -43,9 +48,14 @@ pub struct Ball {
 }
 
 pub struct State {
+    pub start_time: SystemTime,
+    pub time_step: Duration,
     pub time_elapsed: Duration,
     pub cycle_count: usize,
-    pub robots: HashMap<PlayerNumber, Robot>,
+    /// Seed of the random number generator of the scenario script, the simulation itself is deterministic
+    pub seed: u64,
+    pub robots: BTreeMap<PlayerNumber, Robot>,
+    pub opponents: Vec<Opponent>,
     pub ball: Option<Ball>,
     pub messages: Vec<(PlayerNumber, HulkMessage)>,
     pub finished: bool,
@@ -53,12 +63,14 @@ pub struct State {
 }
 
 impl State {
-    pub fn cycle(&mut self, time_step: Duration) -> Result<Vec<Event>> {
-        let now = UNIX_EPOCH + self.time_elapsed;
+    pub fn cycle(&mut self) -> Result<Vec<Event>> {
+        let time_step = self.time_step;
+        let now = self.start_time + self.time_elapsed;
 
         let mut events = vec![Event::Cycle];
 
         self.move_robots(time_step);
+        self.move_opponents(time_step);
         self.cycle_robots(now)?;
         events.extend(self.move_ball(time_step));
 
@@ -135,9 +147,11 @@ impl State {
                             Side::Right => -1.0,
                         };
 
-                        // TODO: Check if ball is even in range
-                        // let kick_location = ground_to_field * ();
-                        if (self.time_elapsed - robot.last_kick_time).as_secs_f32() > 1.0 {
+                        let ball_in_ground = ground_to_field.inverse() * ball.position;
+                        let is_in_reach = ball_in_ground.coords().norm() < KICK_REACH;
+                        if is_in_reach
+                            && (self.time_elapsed - robot.last_kick_time).as_secs_f32() > 1.0
+                        {
                             let direction = match kick {
                                 KickVariant::Forward => vector![1.0, 0.0],
                                 KickVariant::Turn => vector![0.707, 0.707 * side],
@@ -179,6 +193,12 @@ impl State {
         }
     }
 
+    fn move_opponents(&mut self, time_step: Duration) {
+        for opponent in &mut self.opponents {
+            opponent.step(self.ball.as_mut(), self.time_elapsed, time_step);
+        }
+    }
+
     fn cycle_robots(&mut self, now: std::time::SystemTime) -> Result<()> {
         let incoming_messages = take(&mut self.messages);
 
@@ -198,6 +218,7 @@ impl State {
             )]);
 
             robot.database.main_outputs.cycle_time.start_time = now;
+            robot.interface.set_now(now);
 
             let ground_to_field = robot
                 .database
@@ -243,7 +264,20 @@ impl State {
             };
             robot.database.main_outputs.filtered_game_controller_state =
                 Some(self.filtered_game_controller_state);
+
+            // opponents are only visible during the cycle, the remaining obstacles are scripted
+            let number_of_scripted_obstacles = robot.database.main_outputs.obstacles.len();
+            robot.database.main_outputs.obstacles.extend(
+                self.opponents
+                    .iter()
+                    .map(|opponent| opponent.obstacle(ground_to_field)),
+            );
             robot.cycle(messages_with_time)?;
+            robot
+                .database
+                .main_outputs
+                .obstacles
+                .truncate(number_of_scripted_obstacles);
 
             for message in robot.interface.take_outgoing_messages() {
                 if let OutgoingMessage::Spl(message) = message {
@@ -258,13 +292,29 @@ impl State {
     }
 
     fn move_ball(&mut self, time_step: Duration) -> Vec<Event> {
+        let footprints: Vec<_> = self
+            .robots
+            .values()
+            .filter_map(|robot| robot.database.main_outputs.ground_to_field)
+            .map(|ground_to_field| ground_to_field.translation())
+            .chain(self.opponents.iter().map(|opponent| opponent.position))
+            .map(|position| Footprint {
+                position,
+                radius: ROBOT_FOOTPRINT_RADIUS,
+            })
+            .collect();
+
         let mut events = Vec::new();
         if let Some(ball) = self.ball.as_mut() {
-            ball.position += ball.velocity * time_step.as_secs_f32();
-            ball.velocity *= 0.98;
+            step_ball(ball, &footprints, time_step);
 
-            if ball.position.x().abs() > 4.5 && ball.position.y() < 0.75 {
-                events.push(Event::Goal);
+            if is_in_goal(ball) {
+                let scoring_team = if ball.position.x() > 0.0 {
+                    Team::Hulks
+                } else {
+                    Team::Opponent
+                };
+                events.push(Event::Goal { scoring_team });
             }
         }
         events
@@ -272,11 +322,15 @@ impl State {
 
     pub fn get_lua_state(&self) -> LuaState {
         LuaState {
+            start_time: self.start_time,
+            time_step: self.time_step,
             time_elapsed: self.time_elapsed.as_secs_f32(),
             cycle_count: self.cycle_count,
+            seed: self.seed,
             // TODO: Expose robot data to lua again
             // robots: self.robots.iter().map(LuaRobot::new).collect(),
             robots: Default::default(),
+            opponents: self.opponents.clone(),
             ball: self.ball.clone(),
             messages: self.messages.clone(),
 
@@ -288,7 +342,11 @@ impl State {
 
     pub fn load_lua_state(&mut self, lua_state: LuaState) -> Result<()> {
         self.ball = lua_state.ball;
+        self.opponents = lua_state.opponents;
+        self.start_time = lua_state.start_time;
+        self.time_step = lua_state.time_step;
         self.cycle_count = lua_state.cycle_count;
+        self.seed = lua_state.seed;
         for lua_robot in lua_state.robots {
             let mut robot = Robot::try_new(lua_robot.parameters.player_number)
                 .expect("Creating dummy robot should never fail");
@@ -307,7 +365,7 @@ impl State {
 
 impl Default for State {
     fn default() -> Self {
-        let robots = HashMap::new();
+        let robots = BTreeMap::new();
         let filtered_game_controller_state = FilteredGameControllerState {
             game_state: FilteredGameState::Initial,
             opponent_game_state: FilteredGameState::Initial,
@@ -328,9 +386,13 @@ impl Default for State {
         };
 
         Self {
+            start_time: UNIX_EPOCH,
+            time_step: Duration::from_millis(12),
             time_elapsed: Duration::ZERO,
             cycle_count: 0,
+            seed: 0,
             robots,
+            opponents: Vec::new(),
             ball: None,
             messages: Vec::new(),
             finished: false,
@@ -341,9 +403,13 @@ impl Default for State {
 
 #[derive(Deserialize, Serialize)]
 pub struct LuaState {
+    pub start_time: SystemTime,
+    pub time_step: Duration,
     pub time_elapsed: f32,
     pub cycle_count: usize,
+    pub seed: u64,
     pub robots: Vec<LuaRobot>,
+    pub opponents: Vec<Opponent>,
     pub ball: Option<Ball>,
     pub messages: Vec<(PlayerNumber, HulkMessage)>,
     pub finished: bool,
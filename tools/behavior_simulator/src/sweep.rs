@@ -0,0 +1,264 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::scope,
+};
+
+use clap::ValueEnum;
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::simulator::Simulator;
+
+/// Parameter paths mapped to the values which are swept
+///
+/// ```json
+/// {
+///   "role_assignment.keeper_replacementkeeper_switch_time.secs": [8, 10, 12],
+///   "behavior.role_positions.defender_y_offset": { "minimum": 0.5, "maximum": 1.5 }
+/// }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct SweepConfiguration(BTreeMap<String, ParameterValues>);
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterValues {
+    List(Vec<Value>),
+    Range { minimum: f64, maximum: f64 },
+}
+
+impl SweepConfiguration {
+    pub fn read_from(sweep_file: impl AsRef<Path>) -> Result<Self> {
+        let sweep_file = sweep_file.as_ref();
+        let file = File::open(sweep_file)
+            .wrap_err_with(|| format!("failed to open {}", sweep_file.display()))?;
+        serde_json::from_reader(file)
+            .wrap_err_with(|| format!("failed to parse {}", sweep_file.display()))
+    }
+
+    /// All combinations of the listed values
+    pub fn grid(&self) -> Result<Vec<BTreeMap<String, Value>>> {
+        self.ensure_lists_are_not_empty()?;
+        let mut runs = vec![BTreeMap::new()];
+        for (path, values) in &self.0 {
+            let ParameterValues::List(values) = values else {
+                bail!("range of '{path}' can only be sampled randomly, use --samples");
+            };
+            runs = runs
+                .into_iter()
+                .flat_map(|run| {
+                    values.iter().map(move |value| {
+                        let mut run = run.clone();
+                        run.insert(path.clone(), value.clone());
+                        run
+                    })
+                })
+                .collect();
+        }
+        Ok(runs)
+    }
+
+    /// Randomly chosen values of the lists and uniformly sampled values of the ranges
+    pub fn sample(
+        &self,
+        number_of_samples: usize,
+        seed: u64,
+    ) -> Result<Vec<BTreeMap<String, Value>>> {
+        self.ensure_lists_are_not_empty()?;
+        let mut random_number_generator = StdRng::seed_from_u64(seed);
+        Ok((0..number_of_samples)
+            .map(|_| {
+                self.0
+                    .iter()
+                    .map(|(path, values)| {
+                        let value = match values {
+                            ParameterValues::List(values) => values
+                                .choose(&mut random_number_generator)
+                                .expect("lists are not empty")
+                                .clone(),
+                            ParameterValues::Range { minimum, maximum } => {
+                                Value::from(random_number_generator.gen_range(*minimum..=*maximum))
+                            }
+                        };
+                        (path.clone(), value)
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn ensure_lists_are_not_empty(&self) -> Result<()> {
+        for (path, values) in &self.0 {
+            if matches!(values, ParameterValues::List(values) if values.is_empty()) {
+                bail!("list of '{path}' is empty, there are no values to sweep");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunResult {
+    pub parameters: BTreeMap<String, Value>,
+    pub metrics: BTreeMap<String, f64>,
+    /// Error of the run including failed expectations, `None` if it passed
+    pub error: Option<String>,
+}
+
+/// Runs the scenario once per entry of `runs` on `number_of_jobs` threads
+///
+/// Results are returned in the order of `runs`.
+pub fn run_sweep(
+    scenario_file: &Path,
+    runs: Vec<BTreeMap<String, Value>>,
+    number_of_jobs: usize,
+) -> Vec<RunResult> {
+    let next_run = AtomicUsize::new(0);
+    let results = Mutex::new(BTreeMap::new());
+    scope(|scope| {
+        for _ in 0..number_of_jobs.max(1) {
+            scope.spawn(|| loop {
+                let index = next_run.fetch_add(1, Ordering::SeqCst);
+                let Some(parameters) = runs.get(index) else {
+                    break;
+                };
+                let result = run_once(scenario_file, parameters.clone());
+                println!(
+                    "Run {}/{} {}",
+                    index + 1,
+                    runs.len(),
+                    if result.error.is_none() {
+                        "passed"
+                    } else {
+                        "failed"
+                    }
+                );
+                results.lock().insert(index, result);
+            });
+        }
+    });
+    results.into_inner().into_values().collect()
+}
+
+fn run_once(scenario_file: &Path, parameters: BTreeMap<String, Value>) -> RunResult {
+    let mut metrics = BTreeMap::new();
+    let result = (|| {
+        let mut simulator = Simulator::try_new_with_parameter_overrides(parameters.clone())?;
+        simulator.execute_script(scenario_file)?;
+        let result = simulator.run();
+        metrics = simulator.metrics();
+        result
+    })();
+    RunResult {
+        parameters,
+        metrics,
+        error: result.err().map(|error| format!("{error:#}")),
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SummaryFormat {
+    Csv,
+    Json,
+}
+
+pub fn write_summary(
+    mut writer: impl Write,
+    results: &[RunResult],
+    format: SummaryFormat,
+) -> Result<()> {
+    match format {
+        SummaryFormat::Csv => {
+            let parameters: BTreeSet<_> = results
+                .iter()
+                .flat_map(|result| result.parameters.keys())
+                .collect();
+            let metrics: BTreeSet<_> = results
+                .iter()
+                .flat_map(|result| result.metrics.keys())
+                .collect();
+
+            let header: Vec<_> = ["run"]
+                .into_iter()
+                .chain(parameters.iter().map(|path| path.as_str()))
+                .chain(metrics.iter().map(|name| name.as_str()))
+                .chain(["error"])
+                .map(quote)
+                .collect();
+            writeln!(writer, "{}", header.join(",")).wrap_err("failed to write CSV header")?;
+
+            for (index, result) in results.iter().enumerate() {
+                let row: Vec<_> = [index.to_string()]
+                    .into_iter()
+                    .chain(parameters.iter().map(|path| {
+                        result
+                            .parameters
+                            .get(*path)
+                            .map(|value| quote(&value.to_string()))
+                            .unwrap_or_default()
+                    }))
+                    .chain(metrics.iter().map(|name| {
+                        result
+                            .metrics
+                            .get(*name)
+                            .map(f64::to_string)
+                            .unwrap_or_default()
+                    }))
+                    .chain([result.error.as_deref().map(quote).unwrap_or_default()])
+                    .collect();
+                writeln!(writer, "{}", row.join(",")).wrap_err("failed to write CSV row")?;
+            }
+        }
+        SummaryFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, results)
+                .wrap_err("failed to write JSON summary")?;
+            writeln!(writer).wrap_err("failed to write JSON summary")?;
+        }
+    }
+    writer.flush().wrap_err("failed to flush summary")
+}
+
+fn quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling_an_empty_list_names_the_parameter() {
+        let configuration: SweepConfiguration = serde_json::from_str(
+            r#"{ "a.b": [1, 2], "c.d": [], "e.f": { "minimum": 0.0, "maximum": 1.0 } }"#,
+        )
+        .unwrap();
+
+        let error = configuration.sample(3, 0).unwrap_err();
+
+        assert!(error.to_string().contains("'c.d'"));
+    }
+
+    #[test]
+    fn samples_contain_every_parameter() {
+        let configuration: SweepConfiguration =
+            serde_json::from_str(r#"{ "a.b": [1, 2], "e.f": { "minimum": 0.0, "maximum": 1.0 } }"#)
+                .unwrap();
+
+        let samples = configuration.sample(3, 0).unwrap();
+
+        assert_eq!(samples.len(), 3);
+        assert!(samples
+            .iter()
+            .all(|sample| sample.contains_key("a.b") && sample.contains_key("e.f")));
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use color_eyre::Result;
+
+use behavior_simulator::simulator::Simulator;
+
+fn run_scenario(path: impl AsRef<Path>) -> Result<()> {
+    let mut simulator = Simulator::try_new()?;
+    simulator.execute_script(path)?;
+    simulator.run()
+}
+
+fn failure_report(path: impl AsRef<Path>) -> String {
+    run_scenario(path)
+        .expect_err("scenario with failed expectations should fail")
+        .to_string()
+}
+
+#[test]
+fn failing_expect_fails_the_run() {
+    let report = failure_report("tests/expectations/failing_expect.lua");
+    assert!(report.contains("1 expectations failed"), "{report}");
+    assert!(report.contains("arithmetic is broken"), "{report}");
+}
+
+#[test]
+fn fail_fails_the_run() {
+    let report = failure_report("tests/expectations/failing_fail.lua");
+    assert!(report.contains("scenario failed on purpose"), "{report}");
+}
+
+#[test]
+fn unsatisfied_expect_within_fails_the_run() {
+    let report = failure_report("tests/expectations/unsatisfied_expect_within.lua");
+    assert!(report.contains("predicate never holds"), "{report}");
+}
+
+#[test]
+fn satisfied_expectations_pass_the_run() -> Result<()> {
+    run_scenario("tests/expectations/satisfied_expectations.lua")
+}
@@ -0,0 +1,4 @@
+function on_cycle()
+    expect(1 + 1 == 3, "arithmetic is broken")
+    state.finished = true
+end
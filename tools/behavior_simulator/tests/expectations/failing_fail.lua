@@ -0,0 +1,4 @@
+function on_cycle()
+    fail("scenario failed on purpose")
+    state.finished = true
+end
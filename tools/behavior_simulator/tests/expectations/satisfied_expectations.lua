@@ -0,0 +1,11 @@
+local cycles = 0
+
+expect_within(5, function() return cycles >= 3 end, "predicate never holds")
+
+function on_cycle()
+    cycles = cycles + 1
+    expect(cycles > 0, "cycles are counted")
+    if cycles == 10 then
+        state.finished = true
+    end
+end
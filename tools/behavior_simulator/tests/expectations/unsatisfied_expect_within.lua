@@ -0,0 +1,10 @@
+local cycles = 0
+
+expect_within(5, function() return false end, "predicate never holds")
+
+function on_cycle()
+    cycles = cycles + 1
+    if cycles == 10 then
+        state.finished = true
+    end
+end
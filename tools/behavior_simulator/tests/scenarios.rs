@@ -1,4 +1,4 @@
-use std::{path::Path, time::Instant};
+use std::{collections::BTreeMap, path::Path, time::Instant};
 
 use color_eyre::{eyre::Context, Result};
 
@@ -15,6 +15,64 @@ fn test_scenario(path: impl AsRef<Path>) -> Result<()> {
 
     Ok(())
 }
+
+fn trace_scenario(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    let mut simulator = Simulator::try_new()?;
+    simulator.execute_script(path)?;
+    simulator.run().wrap_err("failed to run simulation")?;
+
+    let mut trace = Vec::new();
+    for frame in &simulator.frames {
+        bincode::serialize_into(&mut trace, &frame.ball)?;
+        for database in [
+            &frame.robots.one,
+            &frame.robots.two,
+            &frame.robots.three,
+            &frame.robots.four,
+            &frame.robots.five,
+            &frame.robots.six,
+            &frame.robots.seven,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            bincode::serialize_into(&mut trace, &database.main_outputs.ground_to_field)?;
+            bincode::serialize_into(&mut trace, &database.main_outputs.motion_command)?;
+            bincode::serialize_into(&mut trace, &database.main_outputs.role)?;
+        }
+    }
+    Ok(trace)
+}
+
+#[test]
+fn test_simulation_is_deterministic() -> Result<()> {
+    let first_trace = trace_scenario("../../tests/behavior/oscillating_obstacle.lua")?;
+    let second_trace = trace_scenario("../../tests/behavior/oscillating_obstacle.lua")?;
+    assert!(
+        first_trace == second_trace,
+        "traces of identical runs differ"
+    );
+    Ok(())
+}
+
+fn random_numbers_of_scenario(path: impl AsRef<Path>) -> Result<BTreeMap<String, f64>> {
+    let mut simulator = Simulator::try_new()?;
+    simulator.execute_script(path)?;
+    simulator.run().wrap_err("failed to run simulation")?;
+    Ok(simulator.metrics())
+}
+
+#[test]
+fn test_lua_random_numbers_are_reproducible() -> Result<()> {
+    let first_random_numbers =
+        random_numbers_of_scenario("../../tests/behavior/seeded_random_numbers.lua")?;
+    let second_random_numbers =
+        random_numbers_of_scenario("../../tests/behavior/seeded_random_numbers.lua")?;
+    assert_eq!(first_random_numbers.len(), 2);
+    assert_eq!(first_random_numbers, second_random_numbers);
+    Ok(())
+}
+
 #[test]
 fn test_ball_intercept() -> Result<()> {
     test_scenario("../../tests/behavior/ball_intercept.lua")
@@ -55,6 +113,11 @@ fn test_hulks_vs_ghosts() -> Result<()> {
     test_scenario("../../tests/behavior/hulks_vs_ghosts.lua")
 }
 
+#[test]
+fn test_hulks_vs_opponents() -> Result<()> {
+    test_scenario("../../tests/behavior/hulks_vs_opponents.lua")
+}
+
 #[test]
 fn test_ingamepenalty_attacking() -> Result<()> {
     test_scenario("../../tests/behavior/ingamepenalty_attacking.lua")
@@ -1,23 +1,118 @@
-use std::str::FromStr;
+use std::{
+    io::stdout,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 
-use clap::Parser;
-use color_eyre::{eyre::bail, Result};
+use clap::{Parser, Subcommand};
+use color_eyre::Result;
 use communication::{
     client::{Communication, CyclerOutput, SubscriberMessage},
-    messages::Format,
+    messages::{Format, SubscriptionOptions},
 };
 use log::{error, info};
+use output::{OutputFormat, Row, RowWriter};
+use selection::{Predicate, Selector};
+use serde_json::Value;
+use tokio::{select, spawn, sync::mpsc, time::sleep};
 
-use crate::logging::setup_logger;
+use crate::{
+    logging::setup_logger,
+    recording::{playback, record},
+};
 
 mod logging;
+mod output;
+mod recording;
+mod selection;
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct CommandlineArguments {
-    #[clap(short, long, default_value = "localhost")]
-    address: String,
-    path: String,
+    #[clap(subcommand)]
+    command: Option<Command>,
+    /// Robots to subscribe to, may be given multiple times
+    #[clap(short, long = "address", default_value = "localhost")]
+    addresses: Vec<String>,
+    /// Outputs to subscribe to (e.g. 'Control.main.ball_position')
+    #[clap(required = true)]
+    paths: Vec<String>,
+    /// Pre-shared key if the robots require authentication
+    #[clap(long)]
+    access_key: Option<String>,
+    /// Maximum number of values per second
+    #[clap(long, value_parser = parse_maximum_rate)]
+    maximum_rate: Option<f32>,
+    /// Only receive every n-th cycle
+    #[clap(long)]
+    decimation: Option<usize>,
+    /// Only receive values which differ from the previous one
+    #[clap(long)]
+    on_change: bool,
+    /// Fields of the values to print (e.g. '.position[0]'), may be given multiple times
+    #[clap(short, long)]
+    select: Vec<Selector>,
+    /// Only print values matching the condition (e.g. '.confidence > 0.5')
+    #[clap(short, long)]
+    filter: Option<Predicate>,
+    #[clap(long, value_enum, default_value_t)]
+    format: OutputFormat,
+    /// Stop after this many seconds
+    #[clap(long, value_parser = parse_seconds)]
+    until: Option<Duration>,
+    /// Stop after this many printed values
+    #[clap(long)]
+    count: Option<usize>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Record all updates of outputs from one or more robots into a JSON Lines file
+    Record {
+        /// Robots to record from, may be given multiple times
+        #[clap(short, long = "address", default_value = "localhost")]
+        addresses: Vec<String>,
+        /// Pre-shared key if the robots require authentication
+        #[clap(long)]
+        access_key: Option<String>,
+        /// File the updates are written to
+        #[clap(short, long)]
+        output: PathBuf,
+        /// Outputs to record (e.g. 'Control.main.ball_position')
+        #[clap(required = true)]
+        paths: Vec<String>,
+    },
+    /// Serve a recording like a live robot, e.g. for Twix
+    Playback {
+        recording: PathBuf,
+        /// Robot of the recording to play back, defaults to the first recorded robot
+        #[clap(long)]
+        robot: Option<String>,
+        /// Address the playback server listens on
+        #[clap(long, default_value = "localhost:1337")]
+        listen: String,
+    },
+}
+
+fn parse_maximum_rate(argument: &str) -> Result<f32, String> {
+    let maximum_rate: f32 = argument.parse().map_err(|error| format!("{error}"))?;
+    SubscriptionOptions::default()
+        .with_maximum_rate(maximum_rate)
+        .map_err(|error| error.to_string())?;
+    Ok(maximum_rate)
+}
+
+fn parse_seconds(argument: &str) -> Result<Duration, String> {
+    let seconds: f32 = argument.parse().map_err(|error| format!("{error}"))?;
+    Duration::try_from_secs_f32(seconds).map_err(|error| format!("{error}"))
 }
 
 #[tokio::main]
@@ -25,21 +120,117 @@ async fn main() -> Result<()> {
     setup_logger()?;
 
     let arguments = CommandlineArguments::parse();
-    let output_to_subscribe = CyclerOutput::from_str(&arguments.path)?;
-    let communication = Communication::new(Some(format!("ws://{}:1337", arguments.address)), true);
-    let (_uuid, mut receiver) = communication
-        .subscribe_output(output_to_subscribe, Format::Textual)
-        .await;
-    while let Some(message) = receiver.recv().await {
-        match message {
-            SubscriberMessage::Update { value } => println!("{value:#}"),
-            SubscriberMessage::SubscriptionSuccess => info!("Successfully subscribed"),
-            SubscriberMessage::SubscriptionFailure { info } => {
-                error!("Failed to subscribe: {info:?}");
-                break;
+    match arguments.command {
+        Some(Command::Record {
+            addresses,
+            access_key,
+            output,
+            paths,
+        }) => return record(addresses, access_key, &output, paths).await,
+        Some(Command::Playback {
+            recording,
+            robot,
+            listen,
+        }) => return playback(&recording, robot, listen).await,
+        None => {}
+    }
+    let options = SubscriptionOptions {
+        minimum_interval: None,
+        decimation: arguments.decimation,
+        on_change_only: arguments.on_change,
+    };
+    let options = match arguments.maximum_rate {
+        Some(maximum_rate) => options.with_maximum_rate(maximum_rate)?,
+        None => options,
+    };
+    let outputs = arguments
+        .paths
+        .iter()
+        .map(|path| Ok((path.clone(), CyclerOutput::from_str(path)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let (value_sender, mut value_receiver) = mpsc::channel(100);
+    let mut communications = Vec::new();
+    for robot in arguments.addresses {
+        let communication = Communication::new(Some(format!("ws://{robot}:1337")), false);
+        communication.set_access_key(arguments.access_key.clone());
+        communication.set_connect(true).await;
+        for (path, output) in &outputs {
+            let (_uuid, mut receiver) = communication
+                .subscribe_output(output.clone(), Format::Textual, options)
+                .await;
+            let value_sender = value_sender.clone();
+            let robot = robot.clone();
+            let path = path.clone();
+            spawn(async move {
+                while let Some(message) = receiver.recv().await {
+                    match message {
+                        SubscriberMessage::Update { value } => {
+                            if value_sender
+                                .send((robot.clone(), path.clone(), value))
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                        SubscriberMessage::SubscriptionSuccess => {
+                            info!("{robot}: successfully subscribed to {path}")
+                        }
+                        SubscriberMessage::SubscriptionFailure { info } => {
+                            error!("{robot}: failed to subscribe to {path}: {info:?}");
+                            break;
+                        }
+                        SubscriberMessage::UpdateBinary { .. } => {
+                            error!("{robot}: cannot print binary data of {path}");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+        communications.push(communication);
+    }
+    drop(value_sender);
+
+    let deadline = sleep(arguments.until.unwrap_or(Duration::MAX));
+    tokio::pin!(deadline);
+    let mut writer = RowWriter::new(stdout(), arguments.format);
+    let mut number_of_printed_values = 0;
+    while arguments.count != Some(number_of_printed_values) {
+        let (robot, path, value) = select! {
+            received = value_receiver.recv() => match received {
+                Some(received) => received,
+                None => break,
+            },
+            _ = &mut deadline => break,
+        };
+        if let Some(filter) = &arguments.filter {
+            if !filter.matches(&value) {
+                continue;
             }
-            SubscriberMessage::UpdateBinary { .. } => bail!("Cannot print binary data"),
         }
+        let columns = if arguments.select.is_empty() {
+            vec![("value".to_string(), value)]
+        } else {
+            arguments
+                .select
+                .iter()
+                .map(|selector| {
+                    (
+                        selector.to_string(),
+                        selector.select(&value).cloned().unwrap_or(Value::Null),
+                    )
+                })
+                .collect()
+        };
+        writer.write(Row {
+            timestamp: SystemTime::now(),
+            robot,
+            path,
+            columns,
+        })?;
+        number_of_printed_values += 1;
     }
     Ok(())
 }
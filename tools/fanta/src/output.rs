@@ -0,0 +1,158 @@
+use std::{
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use color_eyre::{eyre::WrapErr, Result};
+use serde_json::{Map, Value};
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Robot and path followed by the pretty-printed JSON of each value
+    #[default]
+    Pretty,
+    /// One JSON object per line with robot and timestamp
+    Jsonl,
+    /// Comma-separated values with a header line
+    Csv,
+}
+
+/// One received value after selection
+pub struct Row {
+    pub timestamp: SystemTime,
+    pub robot: String,
+    pub path: String,
+    /// Selected values, named by their selector or `value` if nothing is selected
+    pub columns: Vec<(String, Value)>,
+}
+
+pub struct RowWriter<W> {
+    writer: W,
+    format: OutputFormat,
+    header_written: bool,
+}
+
+impl<W: Write> RowWriter<W> {
+    pub fn new(writer: W, format: OutputFormat) -> Self {
+        Self {
+            writer,
+            format,
+            header_written: false,
+        }
+    }
+
+    pub fn write(&mut self, row: Row) -> Result<()> {
+        let timestamp = row
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        match self.format {
+            OutputFormat::Pretty => {
+                writeln!(self.writer, "{} {}:", row.robot, row.path)?;
+                for (name, value) in &row.columns {
+                    writeln!(self.writer, "{name} = {value:#}")?;
+                }
+            }
+            OutputFormat::Jsonl => {
+                let mut object = Map::new();
+                object.insert("timestamp".to_string(), timestamp.into());
+                object.insert("robot".to_string(), row.robot.into());
+                object.insert("path".to_string(), row.path.into());
+                object.extend(row.columns);
+                writeln!(self.writer, "{}", Value::Object(object))?;
+            }
+            OutputFormat::Csv => {
+                if !self.header_written {
+                    let header: Vec<_> = ["timestamp", "robot", "path"]
+                        .into_iter()
+                        .chain(row.columns.iter().map(|(name, _value)| name.as_str()))
+                        .map(escape_csv)
+                        .collect();
+                    writeln!(self.writer, "{}", header.join(","))?;
+                    self.header_written = true;
+                }
+                let cells: Vec<_> = [timestamp.to_string(), row.robot, row.path]
+                    .into_iter()
+                    .chain(row.columns.into_iter().map(|(_name, value)| match value {
+                        Value::String(string) => string,
+                        Value::Null => String::new(),
+                        value => value.to_string(),
+                    }))
+                    .map(|cell| escape_csv(&cell))
+                    .collect();
+                writeln!(self.writer, "{}", cells.join(","))?;
+            }
+        }
+        self.writer.flush().wrap_err("failed to flush output")
+    }
+}
+
+fn escape_csv(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn row() -> Row {
+        Row {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1500),
+            robot: "10.1.24.42".to_string(),
+            path: "Control.main.ball_position".to_string(),
+            columns: vec![
+                (".kind".to_string(), json!("a, \"b\"")),
+                (".position[0]".to_string(), json!(1.5)),
+                (".missing".to_string(), Value::Null),
+            ],
+        }
+    }
+
+    fn written(format: OutputFormat, rows: Vec<Row>) -> String {
+        let mut output = Vec::new();
+        let mut writer = RowWriter::new(&mut output, format);
+        for row in rows {
+            writer.write(row).unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn csv_cells_are_escaped() {
+        assert_eq!(escape_csv("plain"), "plain");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("a \"b\""), "\"a \"\"b\"\"\"");
+        assert_eq!(escape_csv("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn csv_has_one_header_line() {
+        assert_eq!(
+            written(OutputFormat::Csv, vec![row(), row()]),
+            "timestamp,robot,path,.kind,.position[0],.missing\n\
+             1.5,10.1.24.42,Control.main.ball_position,\"a, \"\"b\"\"\",1.5,\n\
+             1.5,10.1.24.42,Control.main.ball_position,\"a, \"\"b\"\"\",1.5,\n"
+        );
+    }
+
+    #[test]
+    fn pretty_output_contains_robot_and_path() {
+        assert_eq!(
+            written(OutputFormat::Pretty, vec![row()]),
+            "10.1.24.42 Control.main.ball_position:\n\
+             .kind = \"a, \\\"b\\\"\"\n\
+             .position[0] = 1.5\n\
+             .missing = null\n"
+        );
+    }
+}
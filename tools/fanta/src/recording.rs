@@ -0,0 +1,80 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    str::FromStr,
+};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use communication::{
+    client::{read_subscription_records, record_subscriptions, CyclerOutput},
+    playback::serve_playback,
+};
+use log::{error, info};
+use tokio::{signal::ctrl_c, spawn};
+use tokio_util::sync::CancellationToken;
+
+pub async fn record(
+    addresses: Vec<String>,
+    access_key: Option<String>,
+    recording: &Path,
+    paths: Vec<String>,
+) -> Result<()> {
+    let outputs = paths
+        .iter()
+        .map(|path| CyclerOutput::from_str(path))
+        .collect::<Result<Vec<_>>>()?;
+    let robots = addresses
+        .iter()
+        .map(|address| format!("ws://{address}:1337"))
+        .collect();
+    let writer = BufWriter::new(
+        File::create(recording)
+            .wrap_err_with(|| format!("failed to create {}", recording.display()))?,
+    );
+    let keep_running = cancel_on_ctrl_c();
+    info!("Recording to {}, stop with Ctrl+C", recording.display());
+    record_subscriptions(robots, outputs, access_key, writer, keep_running).await
+}
+
+pub async fn playback(recording: &Path, robot: Option<String>, listen: String) -> Result<()> {
+    let reader = BufReader::new(
+        File::open(recording)
+            .wrap_err_with(|| format!("failed to open {}", recording.display()))?,
+    );
+    let records = read_subscription_records(reader)?;
+    let robot = match robot {
+        Some(address) => format!("ws://{address}:1337"),
+        None => records
+            .first()
+            .ok_or_else(|| eyre!("{} does not contain any records", recording.display()))?
+            .robot
+            .clone(),
+    };
+    let records: Vec<_> = records
+        .into_iter()
+        .filter(|record| record.robot == robot)
+        .collect();
+    info!(
+        "Playing back {} records of {robot} on {listen}",
+        records.len()
+    );
+    serve_playback(listen, records, cancel_on_ctrl_c()).await
+}
+
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let keep_running = CancellationToken::new();
+    {
+        let keep_running = keep_running.clone();
+        spawn(async move {
+            if let Err(error) = ctrl_c().await {
+                error!("failed to wait for Ctrl+C: {error}");
+            }
+            keep_running.cancel();
+        });
+    }
+    keep_running
+}
@@ -0,0 +1,235 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use color_eyre::{
+    eyre::{bail, eyre, WrapErr},
+    Report, Result,
+};
+use serde_json::{from_str, Value};
+
+/// jq-like path into a value, e.g. `.position[0]` or `.obstacles[2].kind`
+///
+/// `.` selects the whole value.
+#[derive(Clone, Debug)]
+pub struct Selector {
+    text: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+impl Selector {
+    pub fn select<'value>(&self, value: &'value Value) -> Option<&'value Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |value, segment| match segment {
+                Segment::Field(name) => value.get(name),
+                Segment::Index(index) => value.get(index),
+            })
+    }
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for Selector {
+    type Err = Report;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let Some(mut rest) = text.strip_prefix('.') else {
+            bail!("selector '{text}' has to start with '.'");
+        };
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(index_and_rest) = rest.strip_prefix('[') {
+                let (index, remainder) = index_and_rest
+                    .split_once(']')
+                    .ok_or_else(|| eyre!("missing ']' in selector '{text}'"))?;
+                let index = index
+                    .parse()
+                    .wrap_err_with(|| format!("invalid index '{index}' in selector '{text}'"))?;
+                segments.push(Segment::Index(index));
+                rest = remainder;
+            } else {
+                let rest_without_dot = rest.strip_prefix('.').unwrap_or(rest);
+                let end = rest_without_dot
+                    .find(['.', '['])
+                    .unwrap_or(rest_without_dot.len());
+                let (name, remainder) = rest_without_dot.split_at(end);
+                if name.is_empty() {
+                    bail!("empty field name in selector '{text}'");
+                }
+                segments.push(Segment::Field(name.to_string()));
+                rest = remainder;
+            }
+        }
+        Ok(Self {
+            text: text.to_string(),
+            segments,
+        })
+    }
+}
+
+/// Condition on a value, e.g. `.confidence > 0.5`, `.kind == "Robot"`, or just `.is_kicking`
+///
+/// Without comparison, the selected value has to be `true`. Values which do not contain the
+/// selected field never pass.
+#[derive(Clone, Debug)]
+pub struct Predicate {
+    selector: Selector,
+    comparison: Option<(Comparison, Value)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Predicate {
+    pub fn matches(&self, value: &Value) -> bool {
+        let Some(selected) = self.selector.select(value) else {
+            return false;
+        };
+        let Some((comparison, operand)) = &self.comparison else {
+            return selected == &Value::Bool(true);
+        };
+        match comparison {
+            Comparison::Equal => selected == operand,
+            Comparison::NotEqual => selected != operand,
+            Comparison::Less => compare(selected, operand) == Some(Ordering::Less),
+            Comparison::LessOrEqual => matches!(
+                compare(selected, operand),
+                Some(Ordering::Less | Ordering::Equal)
+            ),
+            Comparison::Greater => compare(selected, operand) == Some(Ordering::Greater),
+            Comparison::GreaterOrEqual => matches!(
+                compare(selected, operand),
+                Some(Ordering::Greater | Ordering::Equal)
+            ),
+        }
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = Report;
+
+    fn from_str(text: &str) -> Result<Self> {
+        // longer operators first, otherwise `<=` would be parsed as `<`
+        const OPERATORS: [(&str, Comparison); 6] = [
+            ("==", Comparison::Equal),
+            ("!=", Comparison::NotEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ];
+        // the selector is tokenized first, such that operators within the operand are kept
+        let text_without_whitespace = text.trim_start();
+        let selector_length = text_without_whitespace
+            .find(|character: char| {
+                !(character.is_alphanumeric() || matches!(character, '_' | '.' | '[' | ']'))
+            })
+            .unwrap_or(text_without_whitespace.len());
+        let (selector, rest) = text_without_whitespace.split_at(selector_length);
+        let selector = selector.parse()?;
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Ok(Self {
+                selector,
+                comparison: None,
+            });
+        }
+        let (comparison, operand) = OPERATORS
+            .iter()
+            .find_map(|(operator, comparison)| Some((*comparison, rest.strip_prefix(operator)?)))
+            .ok_or_else(|| eyre!("expected comparison operator before '{rest}' in '{text}'"))?;
+        let operand = from_str(operand.trim())
+            .wrap_err_with(|| format!("operand in '{text}' is not a JSON value"))?;
+        Ok(Self {
+            selector,
+            comparison: Some((comparison, operand)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn selectors_select_fields_and_indices() {
+        let value = json!({"position": [1.0, 2.0], "obstacles": [{"kind": "Robot"}]});
+
+        let whole: Selector = ".".parse().unwrap();
+        assert_eq!(whole.select(&value), Some(&value));
+        let position: Selector = ".position[1]".parse().unwrap();
+        assert_eq!(position.select(&value), Some(&json!(2.0)));
+        let kind: Selector = ".obstacles[0].kind".parse().unwrap();
+        assert_eq!(kind.select(&value), Some(&json!("Robot")));
+        let missing: Selector = ".obstacles[1].kind".parse().unwrap();
+        assert_eq!(missing.select(&value), None);
+    }
+
+    #[test]
+    fn invalid_selectors_are_rejected() {
+        for text in ["position", ".position[", ".position[a]", ".position..x"] {
+            assert!(text.parse::<Selector>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn predicates_compare_selected_values() {
+        let value = json!({"confidence": 0.7, "kind": "Robot", "is_kicking": true});
+
+        let cases = [
+            (".confidence > 0.5", true),
+            (".confidence<=0.5", false),
+            (".confidence >= 0.7", true),
+            (".kind == \"Robot\"", true),
+            (".kind != \"Robot\"", false),
+            (".kind < \"Z\"", true),
+            (".is_kicking", true),
+            (".missing", false),
+            (".missing != 1", false),
+        ];
+        for (text, expected) in cases {
+            let predicate: Predicate = text.parse().unwrap();
+            assert_eq!(predicate.matches(&value), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn operators_within_the_operand_are_kept() {
+        let predicate: Predicate = r#".kind != "a==b""#.parse().unwrap();
+        assert!(!predicate.matches(&json!({"kind": "a==b"})));
+        assert!(predicate.matches(&json!({"kind": "a"})));
+    }
+
+    #[test]
+    fn invalid_predicates_are_rejected() {
+        for text in [".kind = 1", ".kind == Robot", "kind == 1", ".kind 1"] {
+            assert!(text.parse::<Predicate>().is_err(), "{text}");
+        }
+    }
+}
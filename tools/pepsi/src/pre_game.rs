@@ -41,6 +41,9 @@ pub struct Arguments {
     /// and one out of every 30 in VisionTop. Set to 0 or don't specify to disable recording for a cycler.
     #[arg(long, value_delimiter=',', value_parser = parse_key_value::<String, usize>, default_value = "Control=1,VisionTop=30,VisionBottom=30")]
     pub recording_intervals: Vec<(String, usize)>,
+    /// Cycler instances which additionally record hardware inputs, e.g. VisionTop,SplNetwork
+    #[arg(long, value_delimiter = ',')]
+    pub record_hardware_inputs: Vec<String>,
     /// The location to use for parameters
     pub location: String,
     /// The network to connect the wireless device to (None disconnects from anything)
@@ -67,6 +70,7 @@ pub async fn pre_game(arguments: Arguments, repository: &Repository) -> Result<(
     recording(
         RecordingArguments {
             recording_intervals: arguments.recording_intervals,
+            record_hardware_inputs: arguments.record_hardware_inputs,
         },
         repository,
     )
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, error::Error};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
 
 use clap::Args;
 use color_eyre::{eyre::WrapErr, Result};
@@ -11,11 +14,18 @@ pub struct Arguments {
     /// and one out of every 30 in VisionTop. Set to 0 or don't specify to disable recording for a cycler.
     #[arg(value_delimiter=',', value_parser = parse_key_value::<String, usize>)]
     pub recording_intervals: Vec<(String, usize)>,
+    /// Cycler instances which additionally record hardware inputs (camera, microphones, network,
+    /// sensors), e.g. VisionTop,SplNetwork to re-execute their setup nodes during replay
+    #[arg(long, value_delimiter = ',')]
+    pub record_hardware_inputs: Vec<String>,
 }
 
 pub async fn recording(arguments: Arguments, repository: &Repository) -> Result<()> {
     repository
-        .set_recording_intervals(HashMap::from_iter(arguments.recording_intervals))
+        .set_recording_intervals(
+            HashMap::from_iter(arguments.recording_intervals),
+            HashSet::from_iter(arguments.record_hardware_inputs),
+        )
         .await
         .wrap_err("failed to set recording enablement")
 }
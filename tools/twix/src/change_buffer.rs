@@ -2,7 +2,7 @@ use std::collections::VecDeque;
 
 use communication::{
     client::{Communication, CyclerOutput, SubscriberMessage},
-    messages::Format,
+    messages::{Format, SubscriptionOptions},
 };
 use log::error;
 use serde_json::Value;
@@ -122,7 +122,7 @@ impl ChangeBuffer {
         let (command_sender, command_receiver) = mpsc::channel(10);
         spawn(async move {
             let (uuid, receiver) = communication
-                .subscribe_output(output, Format::Textual)
+                .subscribe_output(output, Format::Textual, SubscriptionOptions::default())
                 .await;
 
             change_buffer(receiver, command_receiver).await;
@@ -1,4 +1,9 @@
-use communication::client::{Communication, CyclerOutput, SubscriberMessage};
+use std::{collections::VecDeque, sync::Arc, time::SystemTime};
+
+use communication::{
+    client::{Communication, CyclerOutput, SubscriberMessage},
+    messages::{Format, SubscriptionOptions},
+};
 use log::error;
 use tokio::{
     select, spawn,
@@ -8,6 +13,11 @@ use tokio::{
     },
 };
 
+use crate::timeline::{Timeline, HISTORY_DURATION};
+
+/// Upper bound of the memory used by the images of one buffer, older images are dropped first
+const MAXIMUM_BUFFERED_BYTES: usize = 256 * 1024 * 1024;
+
 #[derive(Debug)]
 enum Message {
     GetLatest {
@@ -23,13 +33,21 @@ pub struct ImageBuffer {
 }
 
 impl ImageBuffer {
-    pub fn new(communication: Communication, output: CyclerOutput) -> Self {
+    pub fn new(
+        communication: Communication,
+        output: CyclerOutput,
+        timeline: Arc<Timeline>,
+    ) -> Self {
         let (command_sender, command_receiver) = mpsc::channel(10);
         spawn(async move {
             let (uuid, receiver) = communication
-                .subscribe_output(output.clone(), communication::messages::Format::Binary)
+                .subscribe_output(
+                    output.clone(),
+                    Format::Binary,
+                    SubscriptionOptions::default(),
+                )
                 .await;
-            image_buffer(receiver, command_receiver).await;
+            image_buffer(receiver, command_receiver, timeline).await;
             communication.unsubscribe_output(uuid).await;
         });
         Self {
@@ -55,11 +73,15 @@ impl ImageBuffer {
     }
 }
 
+/// Buffers the images of the last [`HISTORY_DURATION`], newest first
+///
+/// While paused, new images are ignored such that the history before the pause is kept.
 async fn image_buffer(
     mut subscriber_receiver: mpsc::Receiver<SubscriberMessage>,
     mut command_receiver: mpsc::Receiver<Message>,
+    timeline: Arc<Timeline>,
 ) {
-    let mut image_data: Option<Result<Vec<u8>, String>> = None;
+    let mut image_data: Option<Result<VecDeque<(SystemTime, Vec<u8>)>, String>> = None;
     let mut update_listeners: Vec<mpsc::Sender<()>> = Vec::new();
     loop {
         select! {
@@ -68,7 +90,10 @@ async fn image_buffer(
                     Some(message) => {
                         match message {
                             SubscriberMessage::UpdateBinary{data: new_data} => {
-                                image_data = Some(Ok(new_data));
+                                if timeline.is_paused() {
+                                    continue;
+                                }
+                                add_image(&mut image_data, new_data);
                                 update_listeners.retain(|listener| {
                                     if let Err(TrySendError::Closed(_)) = listener.try_send(()) {
                                             return false;
@@ -93,8 +118,15 @@ async fn image_buffer(
                 match maybe_command {
                     Some(command) => match command {
                         Message::GetLatest{response_sender} => {
+                            let selected_time = timeline.selected_time();
                             let response = match &image_data {
-                                Some(Ok(values)) => Ok(values.clone()),
+                                Some(Ok(images)) => images
+                                    .iter()
+                                    .find(|(time, _)| {
+                                        !selected_time.is_some_and(|selected_time| *time > selected_time)
+                                    })
+                                    .map(|(_, data)| data.clone())
+                                    .ok_or_else(|| "No image at selected time".to_string()),
                                 Some(Err(error)) => Err(error.clone()),
                                 None => Err("No response yet".to_string()),
                             };
@@ -110,3 +142,29 @@ async fn image_buffer(
         }
     }
 }
+
+fn add_image(
+    image_data: &mut Option<Result<VecDeque<(SystemTime, Vec<u8>)>, String>>,
+    data: Vec<u8>,
+) {
+    let now = SystemTime::now();
+    match image_data {
+        Some(Ok(images)) => {
+            images.push_front((now, data));
+            while images.len() > 1
+                && images.back().is_some_and(|(time, _)| {
+                    now.duration_since(*time).unwrap_or_default() > HISTORY_DURATION
+                })
+            {
+                images.pop_back();
+            }
+            let mut buffered_bytes: usize = images.iter().map(|(_, data)| data.len()).sum();
+            while images.len() > 1 && buffered_bytes > MAXIMUM_BUFFERED_BYTES {
+                if let Some((_, data)) = images.pop_back() {
+                    buffered_bytes -= data.len();
+                }
+            }
+        }
+        _ => *image_data = Some(Ok(VecDeque::from([(now, data)]))),
+    }
+}
@@ -1,12 +1,14 @@
 use std::{
     fmt::{self, Display, Formatter},
     net::IpAddr,
+    path::PathBuf,
     str::FromStr,
     sync::Arc,
     time::{Duration, SystemTime},
 };
 
 use aliveness::query_aliveness;
+use clap::Parser;
 use color_eyre::{
     eyre::{bail, eyre},
     Result,
@@ -30,16 +32,20 @@ use nao::Nao;
 use panel::Panel;
 use panels::{
     BehaviorSimulatorPanel, EnumPlotPanel, ImagePanel, ImageSegmentsPanel, LookAtPanel,
-    ManualCalibrationPanel, MapPanel, ParameterPanel, PlotPanel, RemotePanel, TextPanel,
-    VisionTunerPanel,
+    ManualCalibrationPanel, MapPanel, ParameterDiffPanel, ParameterPanel, PlotPanel, RemotePanel,
+    RobotPanel, TeamMapPanel, TextPanel, VisionTunerPanel,
 };
 use repository::{get_repository_root, Repository};
 use serde_json::{from_str, to_string, Value};
+use timeline::{timeline_controls, Timeline};
 use tokio::{
     runtime::{Builder, Runtime},
     sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
 };
 use visuals::Visuals;
+use workspace::{
+    resolve_workspace, workspace_names, workspace_path, workspaces_directory, Workspace,
+};
 
 mod change_buffer;
 mod completion_edit;
@@ -47,12 +53,15 @@ mod image_buffer;
 mod nao;
 mod panel;
 mod panels;
+mod parameter_history;
 mod players_value_buffer;
 mod repository_parameters;
 mod selectable_panel_macro;
+mod timeline;
 mod twix_painter;
 mod value_buffer;
 mod visuals;
+mod workspace;
 
 fn setup_logger() -> Result<(), InitError> {
     Dispatch::new()
@@ -70,23 +79,49 @@ fn setup_logger() -> Result<(), InitError> {
     Ok(())
 }
 
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct CommandlineArguments {
+    /// Workspace to open, either a name in tools/twix/workspaces/ or a path to a file
+    #[clap(short, long)]
+    workspace: Option<String>,
+    /// Address of the NAO to connect to, overrides the address bound in the workspace
+    #[clap(short, long)]
+    address: Option<String>,
+}
+
 fn main() -> Result<(), eframe::Error> {
     setup_logger().unwrap();
+    let arguments = CommandlineArguments::parse();
 
     let runtime = Runtime::new().unwrap();
-    if let Ok(repository_root) = runtime.block_on(get_repository_root()) {
+    let repository_root = runtime.block_on(get_repository_root()).ok();
+    if let Some(repository_root) = &repository_root {
         Repository::new(repository_root)
             .check_new_version_available(env!("CARGO_PKG_VERSION"), "tools/twix")
             .unwrap();
     }
 
+    let workspaces_directory = workspaces_directory(repository_root.as_deref());
+    let workspace = arguments.workspace.and_then(|name_or_path| {
+        resolve_workspace(&workspaces_directory, &name_or_path)
+            .and_then(|path| Workspace::load(&path))
+            .map_err(|error| error!("{error:?}"))
+            .ok()
+    });
+
     let options = NativeOptions::default();
     run_native(
         "Twix",
         options,
         Box::new(|creation_context| {
             egui_extras::install_image_loaders(&creation_context.egui_ctx);
-            Box::new(TwixApp::create(creation_context))
+            Box::new(TwixApp::create(
+                creation_context,
+                workspace,
+                arguments.address,
+                workspaces_directory,
+            ))
         }),
     )
 }
@@ -141,14 +176,18 @@ impl_selectable_panel!(
     ManualCalibrationPanel,
     MapPanel,
     ParameterPanel,
+    ParameterDiffPanel,
     PlotPanel,
     EnumPlotPanel,
     RemotePanel,
+    RobotPanel,
+    TeamMapPanel,
     TextPanel,
     VisionTunerPanel
 );
 struct TwixApp {
     nao: Arc<Nao>,
+    timeline: Arc<Timeline>,
     reachable_naos: ReachableNaos,
     connection_intent: bool,
     ip_address: String,
@@ -156,37 +195,55 @@ struct TwixApp {
     last_focused_tab: (NodeIndex, TabIndex),
     dock_state: DockState<Tab>,
     visual: Visuals,
+    workspaces_directory: PathBuf,
+    workspace_name: String,
+    bind_address_to_workspace: bool,
 }
 
 impl TwixApp {
-    fn create(creation_context: &CreationContext) -> Self {
-        let ip_address = creation_context
-            .storage
-            .map(|storage| storage.get_string("ip_address"))
-            .unwrap_or(None);
-
-        let connection_intent = creation_context
-            .storage
-            .and_then(|storage| {
-                storage
-                    .get_string("connection_intent")
-                    .map(|stored| stored == "true")
-            })
-            .unwrap_or(false);
-
-        let nao = Arc::new(Nao::new(ip_address.clone(), connection_intent));
+    fn create(
+        creation_context: &CreationContext,
+        workspace: Option<Workspace>,
+        address: Option<String>,
+        workspaces_directory: PathBuf,
+    ) -> Self {
+        let address_is_given = address.is_some();
+        let bound_address = workspace
+            .as_ref()
+            .and_then(|workspace| workspace.address.clone());
+        let ip_address = address.or(bound_address).or_else(|| {
+            creation_context
+                .storage
+                .and_then(|storage| storage.get_string("ip_address"))
+        });
 
-        let dock_state: Option<DockState<Value>> = creation_context
-            .storage
-            .and_then(|storage| storage.get_string("dock_state"))
-            .and_then(|string| from_str(&string).ok());
+        let connection_intent = address_is_given
+            || creation_context
+                .storage
+                .and_then(|storage| {
+                    storage
+                        .get_string("connection_intent")
+                        .map(|stored| stored == "true")
+                })
+                .unwrap_or(false);
+
+        let timeline = Arc::new(Timeline::default());
+        let nao = Arc::new(Nao::new(
+            ip_address.clone(),
+            connection_intent,
+            timeline.clone(),
+        ));
+
+        let dock_state: Option<DockState<Value>> = match workspace {
+            Some(workspace) => Some(workspace.dock_state),
+            None => creation_context
+                .storage
+                .and_then(|storage| storage.get_string("dock_state"))
+                .and_then(|string| from_str(&string).ok()),
+        };
 
         let dock_state = match dock_state {
-            Some(dock_state) => dock_state.map_tabs(|value| {
-                SelectablePanel::new(nao.clone(), Some(value))
-                    .unwrap()
-                    .into()
-            }),
+            Some(dock_state) => tabs_from_saved_panels(&nao, dock_state),
             None => DockState::new(vec![SelectablePanel::TextPanel(TextPanel::new(
                 nao.clone(),
                 None,
@@ -209,6 +266,7 @@ impl TwixApp {
 
         Self {
             nao,
+            timeline,
             reachable_naos,
             connection_intent,
             ip_address: ip_address.unwrap_or_default(),
@@ -216,8 +274,32 @@ impl TwixApp {
             dock_state,
             last_focused_tab: (0.into(), 0.into()),
             visual,
+            workspaces_directory,
+            workspace_name: String::new(),
+            bind_address_to_workspace: false,
+        }
+    }
+}
+
+/// Panels of unknown type (e.g. stored by a newer Twix) are logged and skipped
+fn tabs_from_saved_panels(nao: &Arc<Nao>, mut dock_state: DockState<Value>) -> DockState<Tab> {
+    let registered_panels = SelectablePanel::registered();
+    let is_unknown = |value: &Value| {
+        !value
+            .get("_panel_type")
+            .and_then(Value::as_str)
+            .is_some_and(|panel_type| registered_panels.iter().any(|name| name == panel_type))
+    };
+    while let Some(index) = dock_state.find_tab_from(is_unknown) {
+        if let Some(value) = dock_state.remove_tab(index) {
+            error!("skipping panel which failed to load: {value}");
         }
     }
+    dock_state.map_tabs(|value| {
+        SelectablePanel::new(nao.clone(), Some(value))
+            .expect("panel types should have been checked")
+            .into()
+    })
 }
 
 impl App for TwixApp {
@@ -296,6 +378,8 @@ impl App for TwixApp {
                             Err(err) => error!("{err:?}"),
                         }
                     }
+
+                    self.timeline_controls(ui);
                 });
                 ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                     ui.menu_button("⚙", |ui| {
@@ -309,6 +393,21 @@ impl App for TwixApp {
                                 }
                             })
                         });
+                        ui.menu_button("Workspace", |ui| {
+                            for name in workspace_names(&self.workspaces_directory) {
+                                if ui.button(&name).clicked() {
+                                    self.load_workspace(&name);
+                                    ui.close_menu();
+                                }
+                            }
+                            ui.separator();
+                            ui.text_edit_singleline(&mut self.workspace_name);
+                            ui.checkbox(&mut self.bind_address_to_workspace, "Bind address");
+                            if ui.button("Save").clicked() {
+                                self.store_workspace();
+                                ui.close_menu();
+                            }
+                        });
                     })
                 });
             })
@@ -363,6 +462,47 @@ impl App for TwixApp {
 }
 
 impl TwixApp {
+    fn timeline_controls(&mut self, ui: &mut Ui) {
+        let toggle_requested = ui.input_mut(|input| input.consume_key(Modifiers::CTRL, Key::Space));
+        timeline_controls(&self.timeline, ui, toggle_requested);
+    }
+
+    fn load_workspace(&mut self, name: &str) {
+        let workspace = match workspace_path(&self.workspaces_directory, name)
+            .and_then(|path| Workspace::load(&path))
+        {
+            Ok(workspace) => workspace,
+            Err(error) => {
+                error!("{error:?}");
+                return;
+            }
+        };
+        if let Some(address) = workspace.address {
+            self.ip_address = address;
+            self.nao.set_address(&self.ip_address);
+        }
+        self.dock_state = tabs_from_saved_panels(&self.nao, workspace.dock_state);
+        self.workspace_name = name.to_string();
+    }
+
+    fn store_workspace(&self) {
+        if self.workspace_name.is_empty() {
+            error!("workspace needs a name to be saved");
+            return;
+        }
+        let workspace = Workspace {
+            address: self
+                .bind_address_to_workspace
+                .then(|| self.ip_address.clone()),
+            dock_state: self.dock_state.map_tabs(|tab| tab.panel.save()),
+        };
+        let result = workspace_path(&self.workspaces_directory, &self.workspace_name)
+            .and_then(|path| workspace.store(&path));
+        if let Err(error) = result {
+            error!("{error:?}");
+        }
+    }
+
     fn active_panel(&mut self) -> Option<&mut SelectablePanel> {
         let (_viewport, tab) = self.dock_state.find_active_focused()?;
         Some(&mut tab.panel)
@@ -1,4 +1,8 @@
-use std::{collections::BTreeSet, sync::Mutex};
+use std::{
+    collections::BTreeSet,
+    env::var,
+    sync::{Arc, Mutex},
+};
 
 use communication::{
     client::{Communication, ConnectionStatus, CyclerOutput},
@@ -12,25 +16,35 @@ use tokio::{
     sync::{broadcast::error::RecvError, watch},
 };
 
-use crate::{change_buffer::ChangeBuffer, image_buffer::ImageBuffer, value_buffer::ValueBuffer};
+use crate::{
+    change_buffer::ChangeBuffer,
+    image_buffer::ImageBuffer,
+    parameter_history::{ParameterEdit, ParameterHistory},
+    timeline::Timeline,
+    value_buffer::ValueBuffer,
+};
 
 pub struct Nao {
     communication: Communication,
     runtime: Runtime,
     address: Mutex<Option<String>>,
     connection_status_receiver: watch::Receiver<ConnectionStatus>,
+    timeline: Arc<Timeline>,
+    parameter_history: ParameterHistory,
 }
 
 impl Nao {
-    pub fn new(address: Option<String>, connect: bool) -> Self {
+    pub fn new(address: Option<String>, connect: bool, timeline: Arc<Timeline>) -> Self {
         let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
         let _guard = runtime.enter();
         let communication = Communication::new(
             address
                 .as_ref()
                 .map(|ip_address| ip_address_to_communication_url(ip_address)),
-            connect,
+            false,
         );
+        communication.set_access_key(var("TWIX_ACCESS_KEY").ok());
+        runtime.block_on(communication.set_connect(connect));
         let connection_status_receiver = communication.subscribe_connection_status_updates();
 
         Self {
@@ -38,6 +52,8 @@ impl Nao {
             runtime,
             address: Mutex::new(address),
             connection_status_receiver,
+            timeline,
+            parameter_history: ParameterHistory::default(),
         }
     }
 
@@ -59,12 +75,12 @@ impl Nao {
 
     pub fn subscribe_output(&self, output: CyclerOutput) -> ValueBuffer {
         let _guard = self.runtime.enter();
-        ValueBuffer::output(self.communication.clone(), output)
+        ValueBuffer::output(self.communication.clone(), output, self.timeline.clone())
     }
 
     pub fn subscribe_image(&self, output: CyclerOutput) -> ImageBuffer {
         let _guard = self.runtime.enter();
-        ImageBuffer::new(self.communication.clone(), output)
+        ImageBuffer::new(self.communication.clone(), output, self.timeline.clone())
     }
 
     pub fn subscribe_parameter(&self, path: &str) -> ValueBuffer {
@@ -77,6 +93,10 @@ impl Nao {
         ChangeBuffer::output(self.communication.clone(), output)
     }
 
+    pub fn timeline(&self) -> &Arc<Timeline> {
+        &self.timeline
+    }
+
     pub fn get_address(&self) -> Option<String> {
         self.address.lock().unwrap().clone()
     }
@@ -96,6 +116,32 @@ impl Nao {
             .block_on(self.communication.update_parameter_value(path, value));
     }
 
+    /// Updates a parameter and records the edit in the parameter history
+    pub fn edit_parameter_value(&self, path: &str, previous_value: Option<Value>, value: Value) {
+        self.parameter_history.record(ParameterEdit {
+            path: path.to_string(),
+            previous_value,
+            value: value.clone(),
+        });
+        self.update_parameter_value(path, value);
+    }
+
+    /// Restores the value a recorded edit replaced
+    pub fn revert_parameter_edit(&self, index: usize) {
+        if let Some(ParameterEdit {
+            path,
+            previous_value: Some(previous_value),
+            ..
+        }) = self.parameter_history.revert(index)
+        {
+            self.update_parameter_value(&path, previous_value);
+        }
+    }
+
+    pub fn parameter_history(&self) -> &ParameterHistory {
+        &self.parameter_history
+    }
+
     pub fn connection_status(&self) -> ConnectionStatus {
         self.connection_status_receiver.borrow().clone()
     }
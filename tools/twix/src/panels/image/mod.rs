@@ -32,14 +32,32 @@ enum ImageKind {
     Luminance,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+enum Resolution {
+    Full,
+    Half,
+    Quarter,
+}
+
+impl Resolution {
+    fn jpeg_field(&self) -> &'static str {
+        match self {
+            Resolution::Full => "jpeg",
+            Resolution::Half => "jpeg_half",
+            Resolution::Quarter => "jpeg_quarter",
+        }
+    }
+}
+
 impl ImageKind {
-    fn as_output(&self) -> Output {
+    fn as_output(&self, resolution: Resolution) -> Output {
+        let jpeg_field = resolution.jpeg_field();
         match self {
             ImageKind::YCbCr422 => Output::Main {
-                path: "image.jpeg".to_string(),
+                path: format!("image.{jpeg_field}"),
             },
             ImageKind::Luminance => Output::Additional {
-                path: "robot_detection.luminance_image.jpeg".to_string(),
+                path: format!("robot_detection.luminance_image.{jpeg_field}"),
             },
         }
     }
@@ -51,6 +69,7 @@ pub struct ImagePanel {
     cycler_selector: VisionCyclerSelector,
     overlays: Overlays,
     image_kind: ImageKind,
+    resolution: Resolution,
 }
 
 impl Panel for ImagePanel {
@@ -77,9 +96,13 @@ impl Panel for ImagePanel {
             .and_then(|value| value.get("image_kind"))
             .and_then(|value| from_value(value.clone()).ok())
             .unwrap_or(ImageKind::YCbCr422);
+        let resolution = value
+            .and_then(|value| value.get("resolution"))
+            .and_then(|value| from_value(value.clone()).ok())
+            .unwrap_or(Resolution::Full);
         let output = CyclerOutput {
             cycler,
-            output: image_kind.as_output(),
+            output: image_kind.as_output(resolution),
         };
         let image_buffer = nao.subscribe_image(output);
         let cycler_selector = VisionCyclerSelector::new(cycler);
@@ -94,6 +117,7 @@ impl Panel for ImagePanel {
             cycler_selector,
             overlays,
             image_kind,
+            resolution,
         }
     }
 
@@ -101,11 +125,13 @@ impl Panel for ImagePanel {
         let cycler = self.cycler_selector.selected_cycler();
         let overlays = self.overlays.save();
         let image_kind = format!("{:?}", self.image_kind);
+        let resolution = format!("{:?}", self.resolution);
 
         json!({
             "cycler": cycler.to_string(),
             "overlays": overlays,
             "image_kind": image_kind,
+            "resolution": resolution,
         })
     }
 }
@@ -116,7 +142,7 @@ impl Widget for &mut ImagePanel {
             if self.cycler_selector.ui(ui).changed() {
                 let output = CyclerOutput {
                     cycler: self.cycler_selector.selected_cycler(),
-                    output: self.image_kind.as_output(),
+                    output: self.image_kind.as_output(self.resolution),
                 };
                 self.image_buffer = self.nao.subscribe_image(output);
                 self.overlays
@@ -139,10 +165,26 @@ impl Widget for &mut ImagePanel {
                         image_selection_changed = true;
                     }
                 });
+            ComboBox::from_label("Resolution")
+                .selected_text(format!("{:?}", self.resolution))
+                .show_ui(ui, |ui| {
+                    for resolution in [Resolution::Full, Resolution::Half, Resolution::Quarter] {
+                        if ui
+                            .selectable_value(
+                                &mut self.resolution,
+                                resolution,
+                                format!("{resolution:?}"),
+                            )
+                            .changed()
+                        {
+                            image_selection_changed = true;
+                        }
+                    }
+                });
             if image_selection_changed {
                 let output = CyclerOutput {
                     cycler: self.cycler_selector.selected_cycler(),
-                    output: self.image_kind.as_output(),
+                    output: self.image_kind.as_output(self.resolution),
                 };
                 self.image_buffer = self.nao.subscribe_image(output);
                 self.overlays
@@ -5,7 +5,7 @@ use eframe::epaint::{Color32, Stroke};
 
 use communication::client::{Cycler, CyclerOutput, Output};
 use coordinate_systems::Ground;
-use linear_algebra::Point;
+use linear_algebra::{Point, Point2};
 use types::{
     field_dimensions::FieldDimensions,
     multivariate_normal_distribution::MultivariateNormalDistribution,
@@ -17,6 +17,7 @@ use crate::{
 
 pub struct BallFilter {
     ball_state: ValueBuffer,
+    predicted_ball_trajectory: ValueBuffer,
 }
 
 impl Layer<Ground> for BallFilter {
@@ -29,7 +30,16 @@ impl Layer<Ground> for BallFilter {
                 path: "best_ball_state".to_string(),
             },
         });
-        Self { ball_state }
+        let predicted_ball_trajectory = nao.subscribe_output(CyclerOutput {
+            cycler: Cycler::Control,
+            output: Output::Additional {
+                path: "predicted_ball_trajectory".to_string(),
+            },
+        });
+        Self {
+            ball_state,
+            predicted_ball_trajectory,
+        }
     }
 
     fn paint(
@@ -48,6 +58,19 @@ impl Layer<Ground> for BallFilter {
             painter.covariance(position, covariance, stroke, fill_color);
         }
 
+        let predicted_ball_trajectory: Vec<Point2<Ground>> =
+            self.predicted_ball_trajectory.parse_latest()?;
+        let stroke = Stroke::new(0.01, Color32::from_rgb(255, 128, 0));
+        for (start, end) in predicted_ball_trajectory
+            .iter()
+            .zip(predicted_ball_trajectory.iter().skip(1))
+        {
+            painter.line_segment(*start, *end, stroke);
+        }
+        if let Some(rest_position) = predicted_ball_trajectory.last() {
+            painter.circle_stroke(*rest_position, 0.05, stroke);
+        }
+
         Ok(())
     }
 }
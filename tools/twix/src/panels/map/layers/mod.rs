@@ -9,6 +9,7 @@ mod line_correspondences;
 mod lines;
 mod obstacle_filter;
 mod obstacles;
+mod particles;
 mod path;
 mod path_obstacles;
 mod robot_pose;
@@ -24,6 +25,7 @@ pub use line_correspondences::LineCorrespondences;
 pub use lines::Lines;
 pub use obstacle_filter::ObstacleFilter;
 pub use obstacles::Obstacles;
+pub use particles::Particles;
 pub use path::Path;
 pub use path_obstacles::PathObstacles;
 pub use robot_pose::RobotPose;
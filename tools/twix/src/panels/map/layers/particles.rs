@@ -0,0 +1,49 @@
+use std::{str::FromStr, sync::Arc};
+
+use color_eyre::Result;
+use eframe::epaint::{Color32, Stroke};
+
+use communication::client::CyclerOutput;
+use coordinate_systems::Field;
+use types::{field_dimensions::FieldDimensions, localization::Particle};
+
+use crate::{
+    nao::Nao, panels::map::layer::Layer, twix_painter::TwixPainter, value_buffer::ValueBuffer,
+};
+
+pub struct Particles {
+    particles: ValueBuffer,
+}
+
+impl Layer<Field> for Particles {
+    const NAME: &'static str = "Particles";
+
+    fn new(nao: Arc<Nao>) -> Self {
+        let particles = nao.subscribe_output(
+            CyclerOutput::from_str("Control.additional.localization.particles").unwrap(),
+        );
+        Self { particles }
+    }
+
+    fn paint(
+        &self,
+        painter: &TwixPainter<Field>,
+        _field_dimensions: &FieldDimensions,
+    ) -> Result<()> {
+        let particles: Vec<Particle> = self.particles.parse_latest()?;
+        let maximum_weight = particles
+            .iter()
+            .map(|particle| particle.weight)
+            .fold(0.0, f32::max);
+        if maximum_weight <= 0.0 {
+            return Ok(());
+        }
+
+        for particle in particles {
+            let alpha = (55.0 + 200.0 * particle.weight / maximum_weight) as u8;
+            let color = Color32::from_rgba_unmultiplied(255, 0, 255, alpha);
+            painter.pose(particle.pose, 0.02, 0.08, color, Stroke::new(0.005, color));
+        }
+        Ok(())
+    }
+}
@@ -78,6 +78,7 @@ pub struct MapPanel {
     feet_detection: EnabledLayer<layers::FeetDetection, Ground>,
     ball_filter: EnabledLayer<layers::BallFilter, Ground>,
     obstacle_filter: EnabledLayer<layers::ObstacleFilter, Ground>,
+    particles: EnabledLayer<layers::Particles, Field>,
 }
 
 impl Panel for MapPanel {
@@ -98,6 +99,7 @@ impl Panel for MapPanel {
         let feet_detection = EnabledLayer::new(nao.clone(), value, false);
         let ball_filter = EnabledLayer::new(nao.clone(), value, false);
         let obstacle_filter = EnabledLayer::new(nao.clone(), value, false);
+        let particles = EnabledLayer::new(nao.clone(), value, false);
 
         let field_dimensions = nao.subscribe_parameter("field_dimensions");
         let ground_to_field =
@@ -123,6 +125,7 @@ impl Panel for MapPanel {
             feet_detection,
             ball_filter,
             obstacle_filter,
+            particles,
         }
     }
 
@@ -143,6 +146,7 @@ impl Panel for MapPanel {
             "feet_detection": self.feet_detection.save(),
             "ball_filter": self.ball_filter.save(),
             "obstacle_filter": self.obstacle_filter.save(),
+            "particles": self.particles.save(),
         })
     }
 }
@@ -165,6 +169,7 @@ impl Widget for &mut MapPanel {
                 self.feet_detection.checkbox(ui);
                 self.ball_filter.checkbox(ui);
                 self.obstacle_filter.checkbox(ui);
+                self.particles.checkbox(ui);
             });
             ComboBox::from_id_source("plot_type_selector")
                 .selected_text(format!("{:?}", self.current_plot_type))
@@ -240,8 +245,11 @@ impl Widget for &mut MapPanel {
         let _ = self
             .obstacle_filter
             .generic_paint(&painter, ground_to_field, &field_dimensions);
+        let _ = self
+            .particles
+            .generic_paint(&painter, ground_to_field, &field_dimensions);
 
-        self.apply_zoom_and_pan(ui, &mut painter, &response);
+        apply_zoom_and_pan(&mut self.transformation, ui, &mut painter, &response);
         if response.double_clicked() {
             self.transformation = Similarity2::identity();
         }
@@ -250,30 +258,28 @@ impl Widget for &mut MapPanel {
     }
 }
 
-impl MapPanel {
-    fn apply_zoom_and_pan(
-        &mut self,
-        ui: &mut Ui,
-        painter: &mut TwixPainter<Field>,
-        response: &Response,
-    ) {
-        let pointer_position = match ui.input(|input| input.pointer.interact_pos()) {
-            Some(position) if response.rect.contains(position) => position,
-            _ => return,
-        };
+/// Zooms with the scroll wheel and pans by dragging, the pointer position stays fixed while zooming
+pub fn apply_zoom_and_pan(
+    transformation: &mut Similarity2<f32>,
+    ui: &mut Ui,
+    painter: &mut TwixPainter<Field>,
+    response: &Response,
+) {
+    let pointer_position = match ui.input(|input| input.pointer.interact_pos()) {
+        Some(position) if response.rect.contains(position) => position,
+        _ => return,
+    };
 
-        let pointer_in_world_before_zoom = painter.transform_pixel_to_world(pointer_position);
-        let zoom_factor = 1.01_f32.powf(ui.input(|input| input.scroll_delta.y));
-        let zoom_transform = Similarity2::from_scaling(zoom_factor);
-        painter.append_transform(zoom_transform);
-        let pointer_in_pixel_after_zoom =
-            painter.transform_world_to_pixel(pointer_in_world_before_zoom);
-        let shift_from_zoom = pointer_position - pointer_in_pixel_after_zoom;
-        let pixel_drag = vector![response.drag_delta().x, -response.drag_delta().y];
-        self.transformation.append_scaling_mut(zoom_factor);
-        self.transformation
-            .append_translation_mut(&Translation2::from(
-                pixel_drag + vector![shift_from_zoom.x, -shift_from_zoom.y],
-            ));
-    }
+    let pointer_in_world_before_zoom = painter.transform_pixel_to_world(pointer_position);
+    let zoom_factor = 1.01_f32.powf(ui.input(|input| input.scroll_delta.y));
+    let zoom_transform = Similarity2::from_scaling(zoom_factor);
+    painter.append_transform(zoom_transform);
+    let pointer_in_pixel_after_zoom =
+        painter.transform_world_to_pixel(pointer_in_world_before_zoom);
+    let shift_from_zoom = pointer_position - pointer_in_pixel_after_zoom;
+    let pixel_drag = vector![response.drag_delta().x, -response.drag_delta().y];
+    transformation.append_scaling_mut(zoom_factor);
+    transformation.append_translation_mut(&Translation2::from(
+        pixel_drag + vector![shift_from_zoom.x, -shift_from_zoom.y],
+    ));
 }
@@ -6,8 +6,11 @@ mod look_at;
 mod manual_camera_calibration;
 mod map;
 mod parameter;
+mod parameter_diff;
 mod plot;
 mod remote;
+mod robot;
+mod team_map;
 mod text;
 mod vision_tuner;
 
@@ -19,7 +22,10 @@ pub use look_at::LookAtPanel;
 pub use manual_camera_calibration::ManualCalibrationPanel;
 pub use map::MapPanel;
 pub use parameter::ParameterPanel;
+pub use parameter_diff::ParameterDiffPanel;
 pub use plot::PlotPanel;
 pub use remote::RemotePanel;
+pub use robot::RobotPanel;
+pub use team_map::TeamMapPanel;
 pub use text::TextPanel;
 pub use vision_tuner::VisionTunerPanel;
@@ -81,7 +81,12 @@ impl Widget for &mut ParameterPanel {
                     if ui.button("Set").clicked() {
                         match serde_json::from_str(&self.parameter_value) {
                             Ok(value) => {
-                                self.nao.update_parameter_value(&self.path, value);
+                                let previous_value = self
+                                    .value_buffer
+                                    .as_ref()
+                                    .and_then(|buffer| buffer.get_latest().ok());
+                                self.nao
+                                    .edit_parameter_value(&self.path, previous_value, value);
                             }
                             Err(error) => error!("Failed to serialize parameter value: {error:#?}"),
                         }
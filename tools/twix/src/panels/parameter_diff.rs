@@ -0,0 +1,256 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use color_eyre::Result;
+use eframe::egui::{Button, CollapsingHeader, ComboBox, Grid, Response, ScrollArea, Ui, Widget};
+use parameters::{
+    directory::{Id, Location, Scope},
+    json::merge_json,
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    nao::Nao, panel::Panel, repository_parameters::RepositoryParameters, value_buffer::ValueBuffer,
+};
+
+/// Compares the live parameters of the robot with the parameter files in the repository
+///
+/// Edits made in twix during this session are listed and can be reverted individually or exported
+/// to the parameter file of a scope.
+pub struct ParameterDiffPanel {
+    nao: Arc<Nao>,
+    repository_parameters: Result<RepositoryParameters>,
+    parameters: BTreeMap<String, ValueBuffer>,
+    layers: Option<Result<Vec<(Scope, Value)>>>,
+    export_scope: Scope,
+}
+
+impl Panel for ParameterDiffPanel {
+    const NAME: &'static str = "Parameter Diff";
+
+    fn new(nao: Arc<Nao>, _value: Option<&Value>) -> Self {
+        Self {
+            nao,
+            repository_parameters: RepositoryParameters::try_new(),
+            parameters: BTreeMap::new(),
+            layers: None,
+            export_scope: Scope {
+                location: Location::All,
+                id: Id::Head,
+            },
+        }
+    }
+}
+
+impl Widget for &mut ParameterDiffPanel {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let repository_parameters = match &self.repository_parameters {
+            Ok(repository_parameters) => repository_parameters,
+            Err(error) => return ui.label(format!("{error:?}")),
+        };
+        let Some(address) = self.nao.get_address() else {
+            return ui.label("No address");
+        };
+        if self.parameters.is_empty() {
+            self.parameters = subscribe_top_level_parameters(&self.nao);
+        }
+        let layers = self
+            .layers
+            .get_or_insert_with(|| repository_parameters.read_layers(&address));
+        let mut reload = false;
+
+        let response = ui
+            .vertical(|ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Reload from disk").clicked() {
+                        reload = true;
+                    }
+                    ui.separator();
+                    scope_selector(ui, &mut self.export_scope);
+                    let history = self.nao.parameter_history();
+                    let export_button =
+                        ui.add_enabled(!history.edits().is_empty(), Button::new("Export edits"));
+                    if export_button.clicked() {
+                        repository_parameters.write_all_to_scope(
+                            &address,
+                            history.latest_values(),
+                            self.export_scope,
+                        );
+                    }
+                });
+
+                history_ui(ui, &self.nao);
+
+                let layers = match layers {
+                    Ok(layers) => layers,
+                    Err(error) => {
+                        ui.label(format!("{error:?}"));
+                        return;
+                    }
+                };
+                let live_parameters = Value::Object(
+                    self.parameters
+                        .iter()
+                        .filter_map(|(key, buffer)| Some((key.clone(), buffer.get_latest().ok()?)))
+                        .collect::<Map<_, _>>(),
+                );
+                ScrollArea::vertical().show(ui, |ui| {
+                    diff_ui(ui, &self.nao, layers, &live_parameters);
+                });
+            })
+            .response;
+        if reload {
+            self.layers = None;
+        }
+        response
+    }
+}
+
+fn subscribe_top_level_parameters(nao: &Nao) -> BTreeMap<String, ValueBuffer> {
+    nao.get_parameter_fields()
+        .into_iter()
+        .flatten()
+        .filter(|path| !path.contains('.'))
+        .map(|path| {
+            let buffer = nao.subscribe_parameter(&path);
+            (path, buffer)
+        })
+        .collect()
+}
+
+fn scope_selector(ui: &mut Ui, scope: &mut Scope) {
+    ComboBox::from_id_source("export_location")
+        .selected_text(format!("{:?}", scope.location))
+        .show_ui(ui, |ui| {
+            for location in [Location::All, Location::Current] {
+                ui.selectable_value(&mut scope.location, location, format!("{location:?}"));
+            }
+        });
+    ComboBox::from_id_source("export_id")
+        .selected_text(format!("{:?}", scope.id))
+        .show_ui(ui, |ui| {
+            for id in [Id::All, Id::Body, Id::Head] {
+                ui.selectable_value(&mut scope.id, id, format!("{id:?}"));
+            }
+        });
+}
+
+fn history_ui(ui: &mut Ui, nao: &Nao) {
+    let edits = nao.parameter_history().edits();
+    CollapsingHeader::new(format!("History ({})", edits.len()))
+        .id_source("parameter_history")
+        .show(ui, |ui| {
+            let is_last_edit_revertible = edits
+                .last()
+                .is_some_and(|edit| edit.previous_value.is_some());
+            if ui
+                .add_enabled(is_last_edit_revertible, Button::new("Undo last edit"))
+                .clicked()
+            {
+                nao.revert_parameter_edit(edits.len() - 1);
+            }
+            Grid::new("parameter_history_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, edit) in edits.iter().enumerate() {
+                        ui.label(&edit.path);
+                        ui.label(
+                            edit.previous_value
+                                .as_ref()
+                                .map_or_else(|| "-".to_string(), Value::to_string),
+                        );
+                        ui.label("→");
+                        ui.label(edit.value.to_string());
+                        let revert_button =
+                            ui.add_enabled(edit.previous_value.is_some(), Button::new("Revert"));
+                        if revert_button.clicked() {
+                            nao.revert_parameter_edit(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn diff_ui(ui: &mut Ui, nao: &Nao, layers: &[(Scope, Value)], live_parameters: &Value) {
+    let mut stored_parameters = Value::Object(Default::default());
+    for (_scope, layer) in layers {
+        merge_json(&mut stored_parameters, layer);
+    }
+    let mut live_leaves = BTreeMap::new();
+    collect_leaves(live_parameters, String::new(), &mut live_leaves);
+
+    Grid::new("parameter_diff_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Path");
+            ui.strong("File");
+            ui.strong("On disk");
+            ui.strong("Live");
+            ui.end_row();
+            for (path, live_value) in live_leaves {
+                let stored_value = value_at(&stored_parameters, &path);
+                if stored_value == Some(live_value) {
+                    continue;
+                }
+                let file = layers
+                    .iter()
+                    .rev()
+                    .find(|(_scope, layer)| value_at(layer, &path).is_some())
+                    .map(|(scope, _layer)| scope_name(*scope))
+                    .unwrap_or("-");
+                ui.label(&path);
+                ui.label(file);
+                ui.label(stored_value.map_or_else(|| "-".to_string(), Value::to_string));
+                ui.label(live_value.to_string());
+                if let Some(stored_value) = stored_value {
+                    if ui.button("Revert").clicked() {
+                        nao.edit_parameter_value(
+                            &path,
+                            Some(live_value.clone()),
+                            stored_value.clone(),
+                        );
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Collects all non-object values with their dot separated paths
+fn collect_leaves<'value>(
+    value: &'value Value,
+    path: String,
+    leaves: &mut BTreeMap<String, &'value Value>,
+) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect_leaves(child, child_path, leaves);
+            }
+        }
+        _ => {
+            leaves.insert(path, value);
+        }
+    }
+}
+
+fn value_at<'value>(value: &'value Value, path: &str) -> Option<&'value Value> {
+    path.split('.')
+        .try_fold(value, |value, key| value.as_object()?.get(key))
+}
+
+fn scope_name(scope: Scope) -> &'static str {
+    match (scope.location, scope.id) {
+        (Location::All, Id::All) => "default",
+        (Location::Current, Id::All) => "location default",
+        (Location::All, Id::Body) => "body",
+        (Location::All, Id::Head) => "head",
+        (Location::Current, Id::Body) => "location body",
+        (Location::Current, Id::Head) => "location head",
+    }
+}
@@ -0,0 +1,460 @@
+use std::{f32::consts::FRAC_PI_2, str::FromStr, sync::Arc};
+
+use communication::client::CyclerOutput;
+use coordinate_systems::{Ground, Robot};
+use eframe::{
+    egui::{Painter, Response, Sense, Ui, Widget},
+    emath::{Pos2, Rect},
+    epaint::{Color32, Shape, Stroke},
+};
+use linear_algebra::{Isometry3, Point3};
+use nalgebra::{point, vector, Isometry3 as NalgebraIsometry3, Point2, Rotation3, Vector3};
+use projection::camera_matrices::CameraMatrices;
+use serde_json::{json, Value};
+use types::robot_kinematics::RobotKinematics;
+
+use crate::{nao::Nao, panel::Panel, value_buffer::ValueBuffer};
+
+/// Corners of the left sole in the sole frame, the right sole is mirrored
+const SOLE_CORNERS: [Point2<f32>; 4] = [
+    point![0.11, 0.045],
+    point![0.11, -0.035],
+    point![-0.05, -0.035],
+    point![-0.05, 0.045],
+];
+/// Soles lower than this above the lowest sole are considered to be on the ground
+const CONTACT_HEIGHT: f32 = 0.01;
+const FRUSTUM_DEPTH: f32 = 0.3;
+const GRID_SIZE: f32 = 0.5;
+const GRID_SPACING: f32 = 0.1;
+
+/// Camera which orbits around the robot, controlled by dragging and scrolling
+struct OrbitCamera {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    target: nalgebra::Point3<f32>,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            yaw: -2.4,
+            pitch: 0.4,
+            distance: 1.2,
+            target: point![0.0, 0.0, 0.25],
+        }
+    }
+}
+
+impl OrbitCamera {
+    fn project(&self, point: nalgebra::Point3<f32>, rect: Rect) -> Option<Pos2> {
+        // view looks along the x axis of the rotated frame, the target is in the center
+        let rotation = Rotation3::from_euler_angles(0.0, self.pitch, self.yaw);
+        let eye = self.target - rotation * vector![self.distance, 0.0, 0.0];
+        let point_in_view = rotation.inverse() * (point - eye);
+        if point_in_view.x < 0.01 {
+            return None;
+        }
+        let focal_length = rect.height().min(rect.width());
+        Some(Pos2::new(
+            rect.center().x - focal_length * point_in_view.y / point_in_view.x,
+            rect.center().y - focal_length * point_in_view.z / point_in_view.x,
+        ))
+    }
+
+    fn interact(&mut self, ui: &Ui, response: &Response) {
+        let drag = response.drag_delta();
+        self.yaw -= drag.x * 0.01;
+        self.pitch = (self.pitch + drag.y * 0.01).clamp(-FRAC_PI_2 + 0.01, FRAC_PI_2 - 0.01);
+        if response.hovered() {
+            let scroll = ui.input(|input| input.scroll_delta.y);
+            self.distance = (self.distance * 0.999_f32.powf(scroll)).clamp(0.2, 5.0);
+        }
+    }
+}
+
+struct Scene<'a> {
+    painter: &'a Painter,
+    rect: Rect,
+    camera: &'a OrbitCamera,
+    robot_to_ground: NalgebraIsometry3<f32>,
+}
+
+impl Scene<'_> {
+    fn project(&self, point_in_ground: nalgebra::Point3<f32>) -> Option<Pos2> {
+        self.camera.project(point_in_ground, self.rect)
+    }
+
+    fn line(&self, start: nalgebra::Point3<f32>, end: nalgebra::Point3<f32>, stroke: Stroke) {
+        if let (Some(start), Some(end)) = (self.project(start), self.project(end)) {
+            self.painter.line_segment([start, end], stroke);
+        }
+    }
+
+    fn robot_line(&self, start: nalgebra::Point3<f32>, end: nalgebra::Point3<f32>, stroke: Stroke) {
+        self.line(
+            self.robot_to_ground * start,
+            self.robot_to_ground * end,
+            stroke,
+        );
+    }
+
+    fn polygon(&self, points: &[nalgebra::Point3<f32>], fill: Color32, stroke: Stroke) {
+        let Some(points) = points
+            .iter()
+            .map(|point| self.project(*point))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+        self.painter
+            .add(Shape::convex_polygon(points, fill, stroke));
+    }
+}
+
+/// Kinematic stick model of the NAO with camera frusta, center of mass, and support polygon
+pub struct RobotPanel {
+    robot_kinematics: ValueBuffer,
+    robot_to_ground: ValueBuffer,
+    camera_matrices: ValueBuffer,
+    center_of_mass: ValueBuffer,
+    camera: OrbitCamera,
+}
+
+impl Panel for RobotPanel {
+    const NAME: &'static str = "Robot";
+
+    fn new(nao: Arc<Nao>, value: Option<&Value>) -> Self {
+        let subscribe = |path: &str| nao.subscribe_output(CyclerOutput::from_str(path).unwrap());
+        let mut camera = OrbitCamera::default();
+        if let Some(value) = value {
+            let get = |key: &str| value.get(key).and_then(|value| value.as_f64());
+            camera.yaw = get("yaw").unwrap_or(camera.yaw as f64) as f32;
+            camera.pitch = get("pitch").unwrap_or(camera.pitch as f64) as f32;
+            camera.distance = get("distance").unwrap_or(camera.distance as f64) as f32;
+        }
+        Self {
+            robot_kinematics: subscribe("Control.main.robot_kinematics"),
+            robot_to_ground: subscribe("Control.main.robot_to_ground"),
+            camera_matrices: subscribe("Control.main.camera_matrices"),
+            center_of_mass: subscribe("Control.main.center_of_mass"),
+            camera,
+        }
+    }
+
+    fn save(&self) -> Value {
+        json!({
+            "yaw": self.camera.yaw,
+            "pitch": self.camera.pitch,
+            "distance": self.camera.distance,
+        })
+    }
+}
+
+impl Widget for &mut RobotPanel {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let robot_kinematics: RobotKinematics = match self.robot_kinematics.parse_latest() {
+            Ok(robot_kinematics) => robot_kinematics,
+            Err(error) => return ui.label(format!("{error:?}")),
+        };
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size_before_wrap(), Sense::click_and_drag());
+        self.camera.interact(ui, &response);
+        if response.double_clicked() {
+            self.camera = OrbitCamera::default();
+        }
+
+        let soles = [
+            (robot_kinematics.left_sole_to_robot.inner, 1.0),
+            (robot_kinematics.right_sole_to_robot.inner, -1.0),
+        ];
+        // without a ground estimation, the ground is assumed to be below the lowest sole
+        let robot_to_ground = match self.robot_to_ground.parse_latest() {
+            Ok(Some(robot_to_ground)) => {
+                let robot_to_ground: Isometry3<Robot, Ground> = robot_to_ground;
+                robot_to_ground.inner
+            }
+            _ => {
+                let lowest_sole = soles
+                    .iter()
+                    .map(|(sole_to_robot, _)| sole_to_robot.translation.z)
+                    .fold(f32::INFINITY, f32::min);
+                NalgebraIsometry3::translation(0.0, 0.0, -lowest_sole)
+            }
+        };
+        let scene = Scene {
+            painter: &painter,
+            rect: response.rect,
+            camera: &self.camera,
+            robot_to_ground,
+        };
+
+        paint_ground_grid(&scene);
+        paint_support_polygon(&scene, &soles);
+        paint_skeleton(&scene, &robot_kinematics, &soles);
+        if let Ok(Some(camera_matrices)) = self
+            .camera_matrices
+            .parse_latest::<Option<CameraMatrices>>()
+        {
+            paint_camera_frusta(&scene, &camera_matrices);
+        }
+        if let Ok(center_of_mass) = self.center_of_mass.parse_latest::<Point3<Robot>>() {
+            paint_center_of_mass(&scene, center_of_mass.inner, &soles);
+        }
+
+        response
+    }
+}
+
+fn paint_ground_grid(scene: &Scene) {
+    let stroke = Stroke::new(1.0, Color32::from_gray(90));
+    let number_of_lines = (2.0 * GRID_SIZE / GRID_SPACING).round() as usize;
+    for index in 0..=number_of_lines {
+        let offset = -GRID_SIZE + index as f32 * GRID_SPACING;
+        scene.line(
+            point![offset, -GRID_SIZE, 0.0],
+            point![offset, GRID_SIZE, 0.0],
+            stroke,
+        );
+        scene.line(
+            point![-GRID_SIZE, offset, 0.0],
+            point![GRID_SIZE, offset, 0.0],
+            stroke,
+        );
+    }
+    scene.line(
+        point![0.0, 0.0, 0.0],
+        point![0.2, 0.0, 0.0],
+        Stroke::new(2.0, Color32::RED),
+    );
+    scene.line(
+        point![0.0, 0.0, 0.0],
+        point![0.0, 0.2, 0.0],
+        Stroke::new(2.0, Color32::GREEN),
+    );
+}
+
+fn sole_corners(
+    sole_to_robot: &NalgebraIsometry3<f32>,
+    side: f32,
+) -> impl Iterator<Item = nalgebra::Point3<f32>> + '_ {
+    SOLE_CORNERS
+        .iter()
+        .map(move |corner| sole_to_robot * point![corner.x, side * corner.y, 0.0])
+}
+
+fn paint_skeleton(
+    scene: &Scene,
+    robot_kinematics: &RobotKinematics,
+    soles: &[(NalgebraIsometry3<f32>, f32)],
+) {
+    let position = |isometry: &NalgebraIsometry3<f32>| -> nalgebra::Point3<f32> {
+        isometry.translation.vector.into()
+    };
+    let kinematics = robot_kinematics;
+    let chains = [
+        vec![
+            position(&kinematics.torso_to_robot.inner),
+            position(&kinematics.neck_to_robot.inner),
+            position(&kinematics.head_to_robot.inner),
+        ],
+        vec![
+            position(&kinematics.left_shoulder_to_robot.inner),
+            position(&kinematics.neck_to_robot.inner),
+            position(&kinematics.right_shoulder_to_robot.inner),
+        ],
+        vec![
+            position(&kinematics.left_shoulder_to_robot.inner),
+            position(&kinematics.left_elbow_to_robot.inner),
+            position(&kinematics.left_wrist_to_robot.inner),
+        ],
+        vec![
+            position(&kinematics.right_shoulder_to_robot.inner),
+            position(&kinematics.right_elbow_to_robot.inner),
+            position(&kinematics.right_wrist_to_robot.inner),
+        ],
+        vec![
+            position(&kinematics.left_hip_to_robot.inner),
+            position(&kinematics.right_hip_to_robot.inner),
+        ],
+        vec![
+            position(&kinematics.torso_to_robot.inner),
+            point![0.0, 0.0, 0.0],
+        ],
+        vec![
+            position(&kinematics.left_hip_to_robot.inner),
+            position(&kinematics.left_tibia_to_robot.inner),
+            position(&kinematics.left_ankle_to_robot.inner),
+            position(&kinematics.left_sole_to_robot.inner),
+        ],
+        vec![
+            position(&kinematics.right_hip_to_robot.inner),
+            position(&kinematics.right_tibia_to_robot.inner),
+            position(&kinematics.right_ankle_to_robot.inner),
+            position(&kinematics.right_sole_to_robot.inner),
+        ],
+    ];
+    let stroke = Stroke::new(3.0, Color32::LIGHT_GRAY);
+    for chain in chains {
+        for segment in chain.windows(2) {
+            scene.robot_line(segment[0], segment[1], stroke);
+        }
+    }
+
+    let head_to_robot = kinematics.head_to_robot.inner;
+    paint_box(
+        scene,
+        &head_to_robot,
+        vector![-0.06, -0.06, 0.0],
+        vector![0.07, 0.06, 0.12],
+    );
+
+    let sole_stroke = Stroke::new(2.0, Color32::LIGHT_GRAY);
+    for (sole_to_robot, side) in soles {
+        let corners: Vec<_> = sole_corners(sole_to_robot, *side).collect();
+        for index in 0..corners.len() {
+            scene.robot_line(
+                corners[index],
+                corners[(index + 1) % corners.len()],
+                sole_stroke,
+            );
+        }
+    }
+}
+
+fn paint_box(
+    scene: &Scene,
+    box_to_robot: &NalgebraIsometry3<f32>,
+    minimum: Vector3<f32>,
+    maximum: Vector3<f32>,
+) {
+    let corner = |x: bool, y: bool, z: bool| {
+        box_to_robot
+            * point![
+                if x { maximum.x } else { minimum.x },
+                if y { maximum.y } else { minimum.y },
+                if z { maximum.z } else { minimum.z }
+            ]
+    };
+    let stroke = Stroke::new(1.5, Color32::LIGHT_GRAY);
+    for a in [false, true] {
+        for b in [false, true] {
+            scene.robot_line(corner(false, a, b), corner(true, a, b), stroke);
+            scene.robot_line(corner(a, false, b), corner(a, true, b), stroke);
+            scene.robot_line(corner(a, b, false), corner(a, b, true), stroke);
+        }
+    }
+}
+
+fn paint_camera_frusta(scene: &Scene, camera_matrices: &CameraMatrices) {
+    for (camera_matrix, color) in [
+        (&camera_matrices.top, Color32::YELLOW),
+        (&camera_matrices.bottom, Color32::LIGHT_BLUE),
+    ] {
+        let camera_to_robot = (camera_matrix.head_to_camera * camera_matrix.robot_to_head)
+            .inverse()
+            .inner;
+        // camera frame: x right, y down, z along the optical axis
+        let half_width = FRUSTUM_DEPTH * (camera_matrix.field_of_view.x / 2.0).tan();
+        let half_height = FRUSTUM_DEPTH * (camera_matrix.field_of_view.y / 2.0).tan();
+        let apex = camera_to_robot * point![0.0, 0.0, 0.0];
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, y)| camera_to_robot * point![x * half_width, y * half_height, FRUSTUM_DEPTH]);
+        let stroke = Stroke::new(1.0, color);
+        for index in 0..corners.len() {
+            scene.robot_line(apex, corners[index], stroke);
+            scene.robot_line(corners[index], corners[(index + 1) % corners.len()], stroke);
+        }
+    }
+}
+
+/// Convex hull of the soles touching the ground, projected onto the ground
+fn support_polygon(
+    scene: &Scene,
+    soles: &[(NalgebraIsometry3<f32>, f32)],
+) -> Vec<nalgebra::Point3<f32>> {
+    let corners: Vec<_> = soles
+        .iter()
+        .flat_map(|(sole_to_robot, side)| sole_corners(sole_to_robot, *side))
+        .map(|corner| scene.robot_to_ground * corner)
+        .collect();
+    let lowest = corners
+        .iter()
+        .map(|corner| corner.z)
+        .fold(f32::INFINITY, f32::min);
+    let points_on_ground: Vec<_> = corners
+        .iter()
+        .filter(|corner| corner.z < lowest + CONTACT_HEIGHT)
+        .map(|corner| point![corner.x, corner.y])
+        .collect();
+    convex_hull(points_on_ground)
+        .into_iter()
+        .map(|point| point![point.x, point.y, 0.0])
+        .collect()
+}
+
+fn paint_support_polygon(scene: &Scene, soles: &[(NalgebraIsometry3<f32>, f32)]) {
+    let polygon = support_polygon(scene, soles);
+    scene.polygon(
+        &polygon,
+        Color32::from_rgba_unmultiplied(0, 200, 0, 40),
+        Stroke::new(1.0, Color32::DARK_GREEN),
+    );
+}
+
+fn paint_center_of_mass(
+    scene: &Scene,
+    center_of_mass: nalgebra::Point3<f32>,
+    soles: &[(NalgebraIsometry3<f32>, f32)],
+) {
+    let center_of_mass = scene.robot_to_ground * center_of_mass;
+    let projected = point![center_of_mass.x, center_of_mass.y, 0.0];
+    let polygon: Vec<_> = support_polygon(scene, soles)
+        .iter()
+        .map(|point| point![point.x, point.y])
+        .collect();
+    let color = if contains(&polygon, point![projected.x, projected.y]) {
+        Color32::GREEN
+    } else {
+        Color32::RED
+    };
+    scene.line(center_of_mass, projected, Stroke::new(1.0, color));
+    for point in [center_of_mass, projected] {
+        if let Some(position) = scene.project(point) {
+            scene.painter.circle_filled(position, 4.0, color);
+        }
+    }
+}
+
+/// Andrew's monotone chain, returns the hull in counterclockwise order
+fn convex_hull(mut points: Vec<Point2<f32>>) -> Vec<Point2<f32>> {
+    if points.len() < 3 {
+        return points;
+    }
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    let cross = |o: Point2<f32>, a: Point2<f32>, b: Point2<f32>| (a - o).perp(&(b - o));
+    let mut hull: Vec<Point2<f32>> = Vec::with_capacity(2 * points.len());
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+fn contains(polygon: &[Point2<f32>], point: Point2<f32>) -> bool {
+    polygon.len() >= 3
+        && (0..polygon.len()).all(|index| {
+            let start = polygon[index];
+            let end = polygon[(index + 1) % polygon.len()];
+            (end - start).perp(&(point - start)) >= 0.0
+        })
+}
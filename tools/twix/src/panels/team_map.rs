@@ -0,0 +1,233 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use communication::client::CyclerOutput;
+use coordinate_systems::{Field, Ground};
+use eframe::{
+    egui::{Button, Response, TextEdit, Ui, Widget},
+    epaint::{Color32, Stroke},
+};
+use linear_algebra::{point, Isometry2};
+use nalgebra::Similarity2;
+use serde_json::{json, Value};
+use spl_network_messages::PlayerNumber;
+use types::{
+    ball_position::BallPosition, field_dimensions::FieldDimensions, obstacles::Obstacle,
+    roles::Role,
+};
+
+use crate::{
+    nao::Nao,
+    panel::Panel,
+    panels::map::apply_zoom_and_pan,
+    timeline::{timeline_controls, Timeline},
+    twix_painter::TwixPainter,
+    value_buffer::ValueBuffer,
+};
+
+const REPAINT_INTERVAL: Duration = Duration::from_millis(50);
+
+struct Player {
+    address: String,
+    // keeps the connection alive as long as the player is shown
+    _nao: Arc<Nao>,
+    player_number: ValueBuffer,
+    ground_to_field: ValueBuffer,
+    ball_position: ValueBuffer,
+    obstacles: ValueBuffer,
+    role: ValueBuffer,
+}
+
+impl Player {
+    fn new(address: String, timeline: Arc<Timeline>) -> Self {
+        let nao = Arc::new(Nao::new(Some(address.clone()), true, timeline));
+        let subscribe = |path: &str| nao.subscribe_output(CyclerOutput::from_str(path).unwrap());
+        Self {
+            player_number: nao.subscribe_parameter("player_number"),
+            ground_to_field: subscribe("Control.main.ground_to_field"),
+            ball_position: subscribe("Control.main.ball_position"),
+            obstacles: subscribe("Control.main.obstacles"),
+            role: subscribe("Control.main.role"),
+            address,
+            _nao: nao,
+        }
+    }
+
+    fn snapshot(&self) -> PlayerSnapshot {
+        PlayerSnapshot {
+            address: self.address.clone(),
+            player_number: self.player_number.parse_latest().ok(),
+            ground_to_field: self.ground_to_field.parse_latest().ok().flatten(),
+            ball_position: self.ball_position.parse_latest().ok().flatten(),
+            obstacles: self.obstacles.parse_latest().unwrap_or_default(),
+            role: self.role.parse_latest().ok(),
+        }
+    }
+}
+
+struct PlayerSnapshot {
+    address: String,
+    player_number: Option<PlayerNumber>,
+    ground_to_field: Option<Isometry2<Ground, Field>>,
+    ball_position: Option<BallPosition<Ground>>,
+    obstacles: Vec<Obstacle>,
+    role: Option<Role>,
+}
+
+/// Shows poses, balls, obstacles and roles of all robots of the team in a common field frame
+///
+/// All robots share the timeline of the main connection, pausing and scrubbing in the panel shows
+/// the whole team at the selected time.
+pub struct TeamMapPanel {
+    timeline: Arc<Timeline>,
+    field_dimensions: ValueBuffer,
+    players: Vec<Player>,
+    new_address: String,
+    transformation: Similarity2<f32>,
+}
+
+impl Panel for TeamMapPanel {
+    const NAME: &'static str = "Team Map";
+
+    fn new(nao: Arc<Nao>, value: Option<&Value>) -> Self {
+        let timeline = nao.timeline().clone();
+        let players = value
+            .and_then(|value| value.get("addresses"))
+            .and_then(|addresses| addresses.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|address| address.as_str())
+            .map(|address| Player::new(address.to_string(), timeline.clone()))
+            .collect();
+        Self {
+            timeline,
+            field_dimensions: nao.subscribe_parameter("field_dimensions"),
+            players,
+            new_address: String::new(),
+            transformation: Similarity2::identity(),
+        }
+    }
+
+    fn save(&self) -> Value {
+        let addresses: Vec<_> = self
+            .players
+            .iter()
+            .map(|player| player.address.clone())
+            .collect();
+        json!({
+            "addresses": addresses,
+        })
+    }
+}
+
+impl Widget for &mut TeamMapPanel {
+    fn ui(self, ui: &mut Ui) -> Response {
+        // updates of the robots of the team do not trigger repaints of the main connection
+        ui.ctx().request_repaint_after(REPAINT_INTERVAL);
+
+        ui.horizontal(|ui| {
+            ui.menu_button("Robots", |ui| {
+                let mut player_to_remove = None;
+                for (index, player) in self.players.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(&player.address);
+                        if ui.button("✖").clicked() {
+                            player_to_remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = player_to_remove {
+                    self.players.remove(index);
+                }
+                ui.horizontal(|ui| {
+                    TextEdit::singleline(&mut self.new_address)
+                        .hint_text("Address")
+                        .desired_width(100.0)
+                        .ui(ui);
+                    if ui
+                        .add_enabled(!self.new_address.is_empty(), Button::new("Add"))
+                        .clicked()
+                    {
+                        let address = std::mem::take(&mut self.new_address);
+                        self.players
+                            .push(Player::new(address, self.timeline.clone()));
+                    }
+                });
+            });
+            timeline_controls(&self.timeline, ui, false);
+        });
+
+        let field_dimensions: FieldDimensions = match self.field_dimensions.parse_latest() {
+            Ok(field_dimensions) => field_dimensions,
+            Err(error) => return ui.label(format!("{error:?}")),
+        };
+        let (response, painter) = TwixPainter::allocate_new(ui);
+        let mut painter = painter.with_map_transforms(&field_dimensions);
+        painter.append_transform(self.transformation);
+
+        painter.field(&field_dimensions);
+        for player in &self.players {
+            paint_player(&painter, &player.snapshot(), &field_dimensions);
+        }
+
+        apply_zoom_and_pan(&mut self.transformation, ui, &mut painter, &response);
+        if response.double_clicked() {
+            self.transformation = Similarity2::identity();
+        }
+        response
+    }
+}
+
+fn player_color(player_number: Option<PlayerNumber>) -> Color32 {
+    match player_number {
+        Some(PlayerNumber::One) => Color32::from_rgb(230, 25, 75),
+        Some(PlayerNumber::Two) => Color32::from_rgb(60, 180, 75),
+        Some(PlayerNumber::Three) => Color32::from_rgb(255, 225, 25),
+        Some(PlayerNumber::Four) => Color32::from_rgb(0, 130, 200),
+        Some(PlayerNumber::Five) => Color32::from_rgb(245, 130, 48),
+        Some(PlayerNumber::Six) => Color32::from_rgb(145, 30, 180),
+        Some(PlayerNumber::Seven) => Color32::from_rgb(70, 240, 240),
+        None => Color32::GRAY,
+    }
+}
+
+fn paint_player(
+    painter: &TwixPainter<Field>,
+    player: &PlayerSnapshot,
+    field_dimensions: &FieldDimensions,
+) {
+    let Some(ground_to_field) = player.ground_to_field else {
+        return;
+    };
+    let color = player_color(player.player_number);
+    let stroke = Stroke {
+        width: 0.02,
+        color: Color32::BLACK,
+    };
+
+    for obstacle in &player.obstacles {
+        painter.circle_stroke(
+            ground_to_field * obstacle.position,
+            obstacle.radius_at_foot_height,
+            Stroke { width: 0.02, color },
+        );
+    }
+    if let Some(ball) = player.ball_position {
+        painter.circle(
+            ground_to_field * ball.position,
+            field_dimensions.ball_radius,
+            color,
+            stroke,
+        );
+    }
+    painter.pose(ground_to_field.as_pose(), 0.15, 0.25, color, stroke);
+
+    let number = player
+        .player_number
+        .map(|number| number.to_string())
+        .unwrap_or_else(|| player.address.clone());
+    let label = match player.role {
+        Some(role) => format!("{number}: {role:?}"),
+        None => number,
+    };
+    painter.text(ground_to_field * point![0.0, 0.25], label, color);
+}
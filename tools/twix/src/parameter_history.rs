@@ -0,0 +1,148 @@
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+#[derive(Clone, Debug)]
+pub struct ParameterEdit {
+    pub path: String,
+    /// `None` if the value was not known when editing, such edits cannot be reverted
+    pub previous_value: Option<Value>,
+    pub value: Value,
+}
+
+/// Parameter edits made during this session, oldest first
+#[derive(Debug, Default)]
+pub struct ParameterHistory {
+    edits: Mutex<Vec<ParameterEdit>>,
+}
+
+impl ParameterHistory {
+    pub fn record(&self, edit: ParameterEdit) {
+        self.edits.lock().unwrap().push(edit);
+    }
+
+    pub fn edits(&self) -> Vec<ParameterEdit> {
+        self.edits.lock().unwrap().clone()
+    }
+
+    /// Removes the edit at `index` together with all later edits of the same path
+    ///
+    /// Later edits of the same path were based on the reverted value and become obsolete. Returns
+    /// the removed edit, whose previous value has to be restored. Edits without previous value are
+    /// kept and `None` is returned.
+    pub fn revert(&self, index: usize) -> Option<ParameterEdit> {
+        let mut edits = self.edits.lock().unwrap();
+        let is_revertible = edits
+            .get(index)
+            .is_some_and(|edit| edit.previous_value.is_some());
+        if !is_revertible {
+            return None;
+        }
+        let reverted = edits.remove(index);
+        let mut position = 0;
+        edits.retain(|edit| {
+            position += 1;
+            position <= index || edit.path != reverted.path
+        });
+        Some(reverted)
+    }
+
+    /// Latest value of every edited path, in the order the paths were first edited
+    pub fn latest_values(&self) -> Vec<(String, Value)> {
+        let mut latest_values: Vec<(String, Value)> = Vec::new();
+        for edit in self.edits.lock().unwrap().iter() {
+            match latest_values
+                .iter_mut()
+                .find(|(path, _)| *path == edit.path)
+            {
+                Some((_, value)) => *value = edit.value.clone(),
+                None => latest_values.push((edit.path.clone(), edit.value.clone())),
+            }
+        }
+        latest_values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(path: &str, previous_value: Option<i64>, value: i64) -> ParameterEdit {
+        ParameterEdit {
+            path: path.to_string(),
+            previous_value: previous_value.map(Value::from),
+            value: Value::from(value),
+        }
+    }
+
+    fn paths_and_values(history: &ParameterHistory) -> Vec<(String, Value)> {
+        history
+            .edits()
+            .into_iter()
+            .map(|edit| (edit.path, edit.value))
+            .collect()
+    }
+
+    #[test]
+    fn edits_are_recorded_oldest_first() {
+        let history = ParameterHistory::default();
+        history.record(edit("a", Some(0), 1));
+        history.record(edit("b", Some(0), 2));
+
+        assert_eq!(
+            paths_and_values(&history),
+            vec![
+                ("a".to_string(), Value::from(1)),
+                ("b".to_string(), Value::from(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn revert_removes_later_edits_of_the_same_path() {
+        let history = ParameterHistory::default();
+        history.record(edit("a", Some(0), 1));
+        history.record(edit("b", Some(0), 2));
+        history.record(edit("a", Some(1), 3));
+        history.record(edit("b", Some(2), 4));
+        history.record(edit("a", Some(3), 5));
+
+        let reverted = history.revert(2).unwrap();
+
+        assert_eq!(reverted.previous_value, Some(Value::from(1)));
+        assert_eq!(
+            paths_and_values(&history),
+            vec![
+                ("a".to_string(), Value::from(1)),
+                ("b".to_string(), Value::from(2)),
+                ("b".to_string(), Value::from(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn edits_without_previous_value_are_not_reverted() {
+        let history = ParameterHistory::default();
+        history.record(edit("a", None, 1));
+
+        assert!(history.revert(0).is_none());
+        assert!(history.revert(1).is_none());
+        assert_eq!(history.edits().len(), 1);
+    }
+
+    #[test]
+    fn latest_values_are_ordered_by_first_edit() {
+        let history = ParameterHistory::default();
+        history.record(edit("b", Some(0), 1));
+        history.record(edit("a", Some(0), 2));
+        history.record(edit("b", Some(1), 3));
+
+        assert_eq!(
+            history.latest_values(),
+            vec![
+                ("b".to_string(), Value::from(3)),
+                ("a".to_string(), Value::from(2))
+            ]
+        );
+    }
+}
@@ -4,8 +4,8 @@ use color_eyre::{
 };
 use log::error;
 use parameters::{
-    directory::{serialize, Id, Location, Scope},
-    json::nest_value_at_path,
+    directory::{deserialize_layers, serialize, Id, Location, Scope},
+    json::{merge_json, nest_value_at_path},
 };
 use repository::{get_repository_root, HardwareIds, Repository};
 use serde_json::Value;
@@ -33,19 +33,43 @@ impl RepositoryParameters {
     }
 
     pub fn write(&self, address: &str, path: String, value: Value) {
+        self.write_to_scope(
+            address,
+            path,
+            value,
+            Scope {
+                location: Location::All,
+                id: Id::Head,
+            },
+        );
+    }
+
+    pub fn write_to_scope(&self, address: &str, path: String, value: Value, scope: Scope) {
+        let parameters = nest_value_at_path(&path, value);
+        self.serialize_to_scope(address, parameters, path, scope);
+    }
+
+    /// Writes several parameters to the file of a scope at once
+    ///
+    /// Separate writes would run concurrently and overwrite each other's edits of the same file.
+    pub fn write_all_to_scope(&self, address: &str, values: Vec<(String, Value)>, scope: Scope) {
+        let mut parameters = Value::Object(Default::default());
+        for (path, value) in values {
+            merge_json(&mut parameters, &nest_value_at_path(&path, value));
+        }
+        self.serialize_to_scope(address, parameters, String::new(), scope);
+    }
+
+    fn serialize_to_scope(&self, address: &str, parameters: Value, path: String, scope: Scope) {
         let repository = self.repository.clone();
         let Ok(hardware_ids) = self.hardware_ids_from_address(address) else {
             error!("failed to get head ID from address {address}");
             return;
         };
-        let parameters = nest_value_at_path(&path, value);
         self.runtime.spawn(async move {
             serialize(
                 &parameters,
-                Scope {
-                    location: Location::All,
-                    id: Id::Head,
-                },
+                scope,
                 &path,
                 repository.parameters_root(),
                 &hardware_ids.body_id,
@@ -56,6 +80,18 @@ impl RepositoryParameters {
         });
     }
 
+    /// Parameter files of the robot at `address` in the order they are merged
+    pub fn read_layers(&self, address: &str) -> Result<Vec<(Scope, Value)>> {
+        let hardware_ids = self.hardware_ids_from_address(address)?;
+        self.runtime
+            .block_on(deserialize_layers(
+                self.repository.parameters_root(),
+                &hardware_ids.body_id,
+                &hardware_ids.head_id,
+            ))
+            .wrap_err("failed to read parameter files")
+    }
+
     fn hardware_ids_from_address(&self, address: &str) -> Result<HardwareIds> {
         if address == "localhost" {
             return Ok(HardwareIds {
@@ -0,0 +1,107 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use eframe::egui::{Slider, Ui, Widget};
+
+/// Duration of the history kept by output buffers which can be scrubbed through while paused
+pub const HISTORY_DURATION: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Copy, Debug, Default)]
+enum State {
+    #[default]
+    Live,
+    Paused {
+        paused_at: SystemTime,
+        selected_time: SystemTime,
+    },
+}
+
+/// Global pause shared by all output buffers
+///
+/// While paused, buffers answer with the values received at the selected time, so that all panels
+/// show mutually consistent data.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    state: Mutex<State>,
+}
+
+impl Timeline {
+    pub fn pause(&self) {
+        let now = SystemTime::now();
+        *self.state.lock().unwrap() = State::Paused {
+            paused_at: now,
+            selected_time: now,
+        };
+    }
+
+    pub fn resume(&self) {
+        *self.state.lock().unwrap() = State::Live;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(*self.state.lock().unwrap(), State::Paused { .. })
+    }
+
+    /// Time shown by all panels, `None` while live
+    pub fn selected_time(&self) -> Option<SystemTime> {
+        match *self.state.lock().unwrap() {
+            State::Live => None,
+            State::Paused { selected_time, .. } => Some(selected_time),
+        }
+    }
+
+    /// How long before the pause the selected time is, `None` while live
+    pub fn selected_age(&self) -> Option<Duration> {
+        match *self.state.lock().unwrap() {
+            State::Live => None,
+            State::Paused {
+                paused_at,
+                selected_time,
+            } => Some(paused_at.duration_since(selected_time).unwrap_or_default()),
+        }
+    }
+
+    /// Selects the time `age` before the pause, does nothing while live
+    pub fn select_age(&self, age: Duration) {
+        if let State::Paused {
+            paused_at,
+            selected_time,
+        } = &mut *self.state.lock().unwrap()
+        {
+            *selected_time = *paused_at - age.min(HISTORY_DURATION);
+        }
+    }
+}
+
+/// Pause button and, while paused, a scrubber over the buffered history
+///
+/// `toggle_requested` toggles the pause like the button, e.g. for keyboard shortcuts.
+pub fn timeline_controls(timeline: &Timeline, ui: &mut Ui, toggle_requested: bool) {
+    let is_paused = timeline.is_paused();
+    let toggle_pause = ui
+        .button(if is_paused { "▶" } else { "⏸" })
+        .on_hover_text("Pause all panels (Ctrl+Space)")
+        .clicked()
+        || toggle_requested;
+    if toggle_pause {
+        if is_paused {
+            timeline.resume();
+        } else {
+            timeline.pause();
+        }
+    }
+    if let Some(selected_age) = timeline.selected_age() {
+        let mut seconds_before_pause = selected_age.as_secs_f32();
+        let slider = Slider::new(
+            &mut seconds_before_pause,
+            HISTORY_DURATION.as_secs_f32()..=0.0,
+        )
+        .suffix(" s before pause")
+        .ui(ui);
+        if slider.changed() {
+            timeline.select_age(Duration::from_secs_f32(seconds_before_pause));
+        }
+    }
+}
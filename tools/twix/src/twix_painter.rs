@@ -5,8 +5,8 @@ use std::{
 
 use eframe::{
     egui::{Painter, Response, Sense, Ui},
-    emath::{Pos2, Rect},
-    epaint::{Color32, PathShape, Shape, Stroke},
+    emath::{Align2, Pos2, Rect},
+    epaint::{Color32, FontId, PathShape, Shape, Stroke},
 };
 use nalgebra::{Rotation2, SMatrix, Similarity2};
 
@@ -304,6 +304,17 @@ impl<Frame> TwixPainter<Frame> {
         self.painter.circle_stroke(center, radius, stroke);
     }
 
+    pub fn text(&self, position: Point2<Frame>, text: impl ToString, color: Color32) {
+        let position = self.transform_world_to_pixel(position);
+        self.painter.text(
+            position,
+            Align2::CENTER_BOTTOM,
+            text,
+            FontId::default(),
+            color,
+        );
+    }
+
     pub fn ellipse(
         &self,
         position: Point2<Frame>,
@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, sync::Arc, time::SystemTime};
 
 use color_eyre::{
     eyre::{eyre, WrapErr},
@@ -6,7 +6,7 @@ use color_eyre::{
 };
 use communication::{
     client::{Communication, CyclerOutput, SubscriberMessage},
-    messages::Format,
+    messages::{Format, SubscriptionOptions},
 };
 use log::error;
 use serde::Deserialize;
@@ -19,6 +19,8 @@ use tokio::{
     },
 };
 
+use crate::timeline::{Timeline, HISTORY_DURATION};
+
 #[derive(Debug)]
 enum Message {
     GetLatest {
@@ -46,13 +48,28 @@ pub struct ValueBuffer {
 }
 
 impl ValueBuffer {
-    pub fn output(communication: Communication, output: CyclerOutput) -> Self {
+    pub fn output(
+        communication: Communication,
+        output: CyclerOutput,
+        timeline: Arc<Timeline>,
+    ) -> Self {
         let (command_sender, command_receiver) = mpsc::channel(10);
         spawn(async move {
             let (uuid, receiver) = communication
-                .subscribe_output(output.clone(), Format::Textual)
+                .subscribe_output(
+                    output.clone(),
+                    Format::Textual,
+                    SubscriptionOptions::default(),
+                )
                 .await;
-            value_buffer(receiver, command_receiver, communication.clone(), None).await;
+            value_buffer(
+                receiver,
+                command_receiver,
+                communication.clone(),
+                None,
+                Some(timeline),
+            )
+            .await;
             communication.unsubscribe_output(uuid).await;
         });
         Self { command_sender }
@@ -67,6 +84,7 @@ impl ValueBuffer {
                 command_receiver,
                 communication.clone(),
                 Some(path),
+                None,
             )
             .await;
             communication.unsubscribe_parameter(uuid).await;
@@ -149,13 +167,19 @@ impl ValueBuffer {
     }
 }
 
+/// Buffers received values with their receive time
+///
+/// Output buffers are given the timeline. They keep the values of the last [`HISTORY_DURATION`]
+/// in addition to the requested capacity and answer with the values at the selected time while
+/// the timeline is paused.
 async fn value_buffer(
     mut subscriber_receiver: mpsc::Receiver<SubscriberMessage>,
     mut command_receiver: mpsc::Receiver<Message>,
     communication: Communication,
     parameter_path: Option<String>,
+    timeline: Option<Arc<Timeline>>,
 ) {
-    let mut values: Option<Result<VecDeque<Value>, String>> = None;
+    let mut values: Option<Result<VecDeque<(SystemTime, Value)>, String>> = None;
     let mut update_listeners: Vec<mpsc::Sender<()>> = Vec::new();
     let mut buffer_capacity = 1;
     let mut skip_updates = 0;
@@ -171,7 +195,10 @@ async fn value_buffer(
                                     skip_updates -= 1;
                                     continue;
                                 }
-                                add_element(&mut values, buffer_capacity, value);
+                                if timeline.as_ref().is_some_and(|timeline| timeline.is_paused()) {
+                                    continue;
+                                }
+                                add_element(&mut values, buffer_capacity, value, timeline.is_some());
                                 update_listeners.retain(|listener| {
                                     if let Err(TrySendError::Closed(_)) = listener.try_send(()) {
                                             return false;
@@ -194,8 +221,12 @@ async fn value_buffer(
                 match maybe_command {
                     Some(command) => match command {
                         Message::GetLatest{response_sender} => {
+                            let selected_time = selected_time(&timeline);
                             let response = match &values {
-                                Some(Ok(values)) => Ok(values.front().unwrap().clone()),
+                                Some(Ok(values)) => values_until(values, selected_time)
+                                    .next()
+                                    .cloned()
+                                    .ok_or_else(|| "No value at selected time".to_string()),
                                 Some(Err(error)) => Err(error.clone()),
                                 None => Err("No response yet".to_string()),
                             };
@@ -203,7 +234,12 @@ async fn value_buffer(
                         },
                         Message::GetBuffered{response_sender} => {
                             let response = match &values {
-                                Some(Ok(values)) => Ok(values.iter().cloned().collect()),
+                                Some(Ok(values)) => Ok(
+                                    values_until(values, selected_time(&timeline))
+                                        .take(buffer_capacity)
+                                        .cloned()
+                                        .collect()
+                                ),
                                 Some(Err(error)) => Err(error.clone()),
                                 None => Err("No response yet".to_string()),
                             };
@@ -211,7 +247,7 @@ async fn value_buffer(
                         },
                         Message::GetSize{response_sender} => {
                             let response = match &values {
-                                Some(Ok(values)) => Ok(values.len()),
+                                Some(Ok(values)) => Ok(values.len().min(buffer_capacity)),
                                 Some(Err(error)) => Err(error.clone()),
                                 None => Err("No response yet".to_string())
                             };
@@ -220,7 +256,7 @@ async fn value_buffer(
                         Message::SetCapacity{buffer_capacity:new_buffer_capacity} => {
                             buffer_capacity = new_buffer_capacity;
                             if let Some(Ok(values)) = &mut values {
-                                values.truncate(buffer_capacity);
+                                truncate(values, buffer_capacity, timeline.is_some());
                             }
                         },
                         Message::ListenToUpdates{response_sender} => {
@@ -228,7 +264,7 @@ async fn value_buffer(
                         },
                         Message::UpdateParameterValue{value} => {
                             skip_updates += 1;
-                            add_element(&mut values, buffer_capacity, value.clone());
+                            add_element(&mut values, buffer_capacity, value.clone(), false);
                             communication.update_parameter_value(
                                 parameter_path.as_ref().expect(
                                     "tried updating parameter on output value buffer"
@@ -245,19 +281,55 @@ async fn value_buffer(
 }
 
 fn add_element(
-    values: &mut Option<Result<VecDeque<Value>, String>>,
+    values: &mut Option<Result<VecDeque<(SystemTime, Value)>, String>>,
     capacity: usize,
     value: Value,
+    keep_history: bool,
 ) {
+    let element = (SystemTime::now(), value);
     match values {
         Some(Ok(values)) => {
-            values.push_front(value);
-            values.truncate(capacity);
+            values.push_front(element);
+            truncate(values, capacity, keep_history);
         }
         _ => {
             let mut new_buffer = VecDeque::with_capacity(capacity);
-            new_buffer.push_back(value);
+            new_buffer.push_back(element);
             *values = Some(Ok(new_buffer));
         }
     }
 }
+
+fn truncate(values: &mut VecDeque<(SystemTime, Value)>, capacity: usize, keep_history: bool) {
+    let number_of_recent_values = if keep_history {
+        let now = SystemTime::now();
+        values
+            .iter()
+            .take_while(|(time, _)| {
+                now.duration_since(*time).unwrap_or_default() <= HISTORY_DURATION
+            })
+            .count()
+    } else {
+        0
+    };
+    values.truncate(capacity.max(number_of_recent_values));
+}
+
+fn selected_time(timeline: &Option<Arc<Timeline>>) -> Option<SystemTime> {
+    timeline
+        .as_ref()
+        .and_then(|timeline| timeline.selected_time())
+}
+
+/// Values received until the selected time, newest first
+fn values_until(
+    values: &VecDeque<(SystemTime, Value)>,
+    selected_time: Option<SystemTime>,
+) -> impl Iterator<Item = &Value> {
+    values
+        .iter()
+        .skip_while(move |(time, _)| {
+            selected_time.is_some_and(|selected_time| *time > selected_time)
+        })
+        .map(|(_, value)| value)
+}
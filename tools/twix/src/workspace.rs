@@ -0,0 +1,92 @@
+use std::{
+    fs::{create_dir_all, read_dir, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{
+    eyre::{bail, WrapErr},
+    Result,
+};
+use egui_dock::DockState;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string_pretty, Value};
+
+/// Named arrangement of panels which can be stored in the repository and shared across the team
+///
+/// Panels are stored with their saved state (subscribed paths, map layers, image overlays, ...).
+/// Workspaces are robot-agnostic unless an address is bound explicitly.
+#[derive(Deserialize, Serialize)]
+pub struct Workspace {
+    #[serde(default)]
+    pub address: Option<String>,
+    pub dock_state: DockState<Value>,
+}
+
+impl Workspace {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = read_to_string(path)
+            .wrap_err_with(|| format!("failed to read workspace {}", path.display()))?;
+        from_str(&contents)
+            .wrap_err_with(|| format!("failed to parse workspace {}", path.display()))
+    }
+
+    pub fn store(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)
+                .wrap_err_with(|| format!("failed to create {}", parent.display()))?;
+        }
+        let contents = to_string_pretty(self).wrap_err("failed to serialize workspace")?;
+        write(path, contents + "\n")
+            .wrap_err_with(|| format!("failed to write workspace {}", path.display()))
+    }
+}
+
+/// Directory of the workspaces shared via the repository
+pub fn workspaces_directory(repository_root: Option<&Path>) -> PathBuf {
+    repository_root
+        .map(|root| root.join("tools/twix/workspaces"))
+        .unwrap_or_else(|| PathBuf::from("workspaces"))
+}
+
+/// Resolves a workspace given either as path to a file or as name in the workspaces directory
+pub fn resolve_workspace(directory: &Path, name_or_path: &str) -> Result<PathBuf> {
+    let path = PathBuf::from(name_or_path);
+    if path.is_file() {
+        Ok(path)
+    } else {
+        workspace_path(directory, name_or_path)
+    }
+}
+
+/// Path of the workspace with the given name in the workspaces directory
+///
+/// Names may only contain letters, digits, `_`, and `-` such that they cannot point outside of the
+/// directory.
+pub fn workspace_path(directory: &Path, name: &str) -> Result<PathBuf> {
+    let is_valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|character| character.is_alphanumeric() || matches!(character, '_' | '-'));
+    if !is_valid {
+        bail!("invalid workspace name '{name}', only letters, digits, '_', and '-' are allowed");
+    }
+    Ok(directory.join(format!("{name}.json")))
+}
+
+/// Names of all workspaces in the directory, sorted alphabetically
+pub fn workspace_names(directory: &Path) -> Vec<String> {
+    let Ok(entries) = read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut names: Vec<_> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}